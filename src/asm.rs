@@ -0,0 +1,242 @@
+/// SREG bit tested by each conditional branch alias, `true` for branch if set
+const BRANCHES: &[(&str, u8, bool)] = &[
+    ("BRCS", 0, true),
+    ("BRLO", 0, true),
+    ("BRCC", 0, false),
+    ("BRSH", 0, false),
+    ("BREQ", 1, true),
+    ("BRNE", 1, false),
+    ("BRMI", 2, true),
+    ("BRPL", 2, false),
+    ("BRVS", 3, true),
+    ("BRVC", 3, false),
+    ("BRLT", 4, true),
+    ("BRGE", 4, false),
+    ("BRHS", 5, true),
+    ("BRHC", 5, false),
+    ("BRTS", 6, true),
+    ("BRTC", 6, false),
+    ("BRIE", 7, true),
+    ("BRID", 7, false),
+];
+
+/// SREG flag letters in bit order, used by the SEx/CLx aliases
+const FLAGS: &str = "CZNVSHTI";
+
+/// Assembles a single line of AVR assembly into flash bytes (little-endian words)
+///
+/// Operands are registers (`r0`..`r31`) and numbers (`42`, `-1`, `0x2A`, `$2A`, `0b101010`).
+/// Offsets of relative jumps and branches are in words from the next instruction, JMP/CALL
+/// take an absolute word address. Text after `;` is a comment.
+///
+/// # Errors
+///
+/// Line holds more or less than one instruction, unknown mnemonic or invalid operands.
+pub fn assemble_line(line: &str) -> Result<Vec<u8>, String> {
+    let mut lines = line
+        .lines()
+        .map(|l| l.split(';').next().unwrap_or("").trim())
+        .filter(|l| !l.is_empty());
+    let Some(code) = lines.next() else {
+        return Err(String::from("No instruction to assemble."));
+    };
+    if lines.next().is_some() {
+        return Err(String::from("Expected a single instruction."));
+    }
+
+    let (mnemonic, operands) = match code.split_once(char::is_whitespace) {
+        Some((mnemonic, operands)) => (mnemonic, operands.trim()),
+        None => (code, ""),
+    };
+    let mnemonic = mnemonic.to_ascii_uppercase();
+    let ops: Vec<&str> = match operands.is_empty() {
+        true => Vec::new(),
+        false => operands.split(',').map(str::trim).collect(),
+    };
+
+    let words = encode(&mnemonic, &ops)?;
+    Ok(words.iter().flat_map(|word| word.to_le_bytes()).collect())
+}
+
+fn encode(mnemonic: &str, ops: &[&str]) -> Result<Vec<u16>, String> {
+    let word = match mnemonic {
+        "NOP" => operands(ops, 0).map(|_| 0x0000)?,
+        "RET" => operands(ops, 0).map(|_| 0x9508)?,
+        "RETI" => operands(ops, 0).map(|_| 0x9518)?,
+        "ADD" => two_regs(0x0C00, ops)?,
+        "CP" => two_regs(0x1400, ops)?,
+        "SUB" => two_regs(0x1800, ops)?,
+        "ADC" => two_regs(0x1C00, ops)?,
+        "AND" => two_regs(0x2000, ops)?,
+        "EOR" => two_regs(0x2400, ops)?,
+        "OR" => two_regs(0x2800, ops)?,
+        "MOV" => two_regs(0x2C00, ops)?,
+        "MUL" => two_regs(0x9C00, ops)?,
+        "CLR" => two_regs(0x2400, &[operand(ops, 0, 1)?, ops[0]])?,
+        "LSL" => two_regs(0x0C00, &[operand(ops, 0, 1)?, ops[0]])?,
+        "ROL" => two_regs(0x1C00, &[operand(ops, 0, 1)?, ops[0]])?,
+        "TST" => two_regs(0x2000, &[operand(ops, 0, 1)?, ops[0]])?,
+        "ORI" | "SBR" => reg_imm(0x6000, ops)?,
+        "ANDI" => reg_imm(0x7000, ops)?,
+        "CBR" => {
+            operands(ops, 2)?;
+            let mask = number(ops[1], 0, 0xFF)? as u8;
+            reg_imm(0x7000, &[ops[0], &format!("{}", !mask)])?
+        }
+        "LDI" => reg_imm(0xE000, ops)?,
+        "SER" => reg_imm(0xE000, &[operand(ops, 0, 1)?, "0xFF"])?,
+        "COM" => one_reg(0x9400, ops)?,
+        "NEG" => one_reg(0x9401, ops)?,
+        "SWAP" => one_reg(0x9402, ops)?,
+        "LSR" => one_reg(0x9406, ops)?,
+        "ROR" => one_reg(0x9407, ops)?,
+        "POP" => one_reg(0x900F, ops)?,
+        "PUSH" => one_reg(0x920F, ops)?,
+        "INC" => one_reg(0x9403, ops)?,
+        "ASR" => one_reg(0x9405, ops)?,
+        "DEC" => one_reg(0x940A, ops)?,
+        "BSET" => 0x9408 | ((number(operand(ops, 0, 1)?, 0, 7)? as u16) << 4),
+        "BCLR" => 0x9488 | ((number(operand(ops, 0, 1)?, 0, 7)? as u16) << 4),
+        "ADIW" => {
+            operands(ops, 2)?;
+            let dest = register(ops[0])?;
+            if !matches!(dest, 24 | 26 | 28 | 30) {
+                return Err(format!(
+                    "ADIW works on r24, r26, r28 or r30, not {}",
+                    ops[0]
+                ));
+            }
+            let value = number(ops[1], 0, 63)? as u16;
+            0x9600 | ((value & 0x30) << 2) | (((dest - 24) / 2) << 4) | (value & 0x0F)
+        }
+        "CBI" | "SBI" => {
+            operands(ops, 2)?;
+            let base = if mnemonic == "CBI" { 0x9800 } else { 0x9A00 };
+            base | ((number(ops[0], 0, 31)? as u16) << 3) | number(ops[1], 0, 7)? as u16
+        }
+        "IN" => {
+            operands(ops, 2)?;
+            let addr = number(ops[1], 0, 63)? as u16;
+            0xB000 | ((addr & 0x30) << 5) | (register(ops[0])? << 4) | (addr & 0x0F)
+        }
+        "OUT" => {
+            operands(ops, 2)?;
+            let addr = number(ops[0], 0, 63)? as u16;
+            0xB800 | ((addr & 0x30) << 5) | (register(ops[1])? << 4) | (addr & 0x0F)
+        }
+        "RJMP" => 0xC000 | (number(operand(ops, 0, 1)?, -2048, 2047)? as u16 & 0x0FFF),
+        "RCALL" => 0xD000 | (number(operand(ops, 0, 1)?, -2048, 2047)? as u16 & 0x0FFF),
+        "BRBS" | "BRBC" => {
+            operands(ops, 2)?;
+            let base = if mnemonic == "BRBS" { 0xF000 } else { 0xF400 };
+            base | branch(number(ops[0], 0, 7)? as u16, ops[1])?
+        }
+        "JMP" | "CALL" => {
+            let dest = number(operand(ops, 0, 1)?, 0, 0x3F_FFFF)? as u32;
+            let base = if mnemonic == "JMP" { 0x940C } else { 0x940E };
+            let high = (((dest >> 17) & 0x1F) << 4) | ((dest >> 16) & 1);
+            return Ok(vec![base | high as u16, dest as u16]);
+        }
+        _ => {
+            if let Some((_, bit, set)) = BRANCHES.iter().find(|(name, ..)| *name == mnemonic) {
+                let base = if *set { 0xF000 } else { 0xF400 };
+                base | branch(*bit as u16, operand(ops, 0, 1)?)?
+            } else if let Some(bit) = flag_alias(mnemonic, "SE") {
+                operands(ops, 0)?;
+                0x9408 | (bit << 4)
+            } else if let Some(bit) = flag_alias(mnemonic, "CL") {
+                operands(ops, 0)?;
+                0x9488 | (bit << 4)
+            } else {
+                return Err(format!("Unknown instruction: {}", mnemonic));
+            }
+        }
+    };
+
+    Ok(vec![word])
+}
+
+/// SREG bit of SEC/CLZ style aliases
+fn flag_alias(mnemonic: &str, prefix: &str) -> Option<u16> {
+    let flag = mnemonic.strip_prefix(prefix)?;
+    if flag.len() != 1 {
+        return None;
+    }
+    FLAGS.find(flag).map(|bit| bit as u16)
+}
+
+fn operands(ops: &[&str], count: usize) -> Result<(), String> {
+    if ops.len() == count {
+        Ok(())
+    } else {
+        Err(format!("Expected {} operands, got {}", count, ops.len()))
+    }
+}
+
+/// Checks operand count and returns operand at `idx`
+fn operand<'a>(ops: &[&'a str], idx: usize, count: usize) -> Result<&'a str, String> {
+    operands(ops, count)?;
+    Ok(ops[idx])
+}
+
+fn two_regs(base: u16, ops: &[&str]) -> Result<u16, String> {
+    operands(ops, 2)?;
+    let dest = register(ops[0])?;
+    let src = register(ops[1])?;
+    Ok(base | (dest << 4) | ((src & 0x10) << 5) | (src & 0x0F))
+}
+
+fn reg_imm(base: u16, ops: &[&str]) -> Result<u16, String> {
+    operands(ops, 2)?;
+    let dest = register(ops[0])?;
+    if dest < 16 {
+        return Err(format!(
+            "Immediate instructions work on r16 to r31, not {}",
+            ops[0]
+        ));
+    }
+    let value = number(ops[1], -128, 255)? as u8 as u16;
+    Ok(base | ((value & 0xF0) << 4) | ((dest - 16) << 4) | (value & 0x0F))
+}
+
+fn one_reg(base: u16, ops: &[&str]) -> Result<u16, String> {
+    Ok(base | (register(operand(ops, 0, 1)?)? << 4))
+}
+
+fn branch(bit: u16, offset: &str) -> Result<u16, String> {
+    let offset = number(offset, -64, 63)? as u16;
+    Ok(((offset & 0x7F) << 3) | bit)
+}
+
+fn register(op: &str) -> Result<u16, String> {
+    op.strip_prefix(['r', 'R'])
+        .and_then(|num| num.parse::<u16>().ok())
+        .filter(|reg| *reg < 32)
+        .ok_or_else(|| format!("Invalid register: {}", op))
+}
+
+fn number(op: &str, min: i64, max: i64) -> Result<i64, String> {
+    let (negative, digits) = match op.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, op),
+    };
+
+    let value = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or(digits.strip_prefix("0X"))
+        .or(digits.strip_prefix('$'))
+    {
+        i64::from_str_radix(hex, 16)
+    } else if let Some(bin) = digits.strip_prefix("0b").or(digits.strip_prefix("0B")) {
+        i64::from_str_radix(bin, 2)
+    } else {
+        digits.parse::<i64>()
+    }
+    .map_err(|_| format!("Invalid number: {}", op))?;
+
+    let value = if negative { -value } else { value };
+    if value < min || value > max {
+        return Err(format!("{} out of range {}..={}", op, min, max));
+    }
+    Ok(value)
+}
@@ -0,0 +1,100 @@
+use std::ffi::OsString;
+use std::io::Write;
+
+use crate::memory::ATmemory;
+
+/// Runs a headless subcommand and writes its result to `out`
+///
+/// * `decode EF1F` prints the instruction of one or more opcode words, a two-word instruction
+///   takes its operand from the next word, e.g. `decode "940E 0100"`
+/// * `exec "1F EF 00 00" [--steps N]` loads raw bytes, executes N instructions or until the
+///   program ends and prints the resulting state
+///
+/// `args` are the command line arguments without the program name. Returns false when they
+/// don't start with a subcommand, so a frontend should be started.
+///
+/// # Errors
+///
+/// Arguments are not valid UTF-8 or cannot be parsed, the program doesn't load or a step failed.
+pub fn run(args: &[OsString], out: &mut impl Write) -> Result<bool, String> {
+    let args = match args.first().and_then(|command| command.to_str()) {
+        Some("decode" | "exec") => args
+            .iter()
+            .map(|arg| {
+                arg.to_str().ok_or_else(|| {
+                    format!("Argument is not valid UTF-8: {}", arg.to_string_lossy())
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        _ => return Ok(false),
+    };
+    match args.as_slice() {
+        ["decode", words] => decode(words, out)?,
+        ["exec", bytes] => exec(bytes, None, out)?,
+        ["exec", bytes, "--steps", steps] => {
+            let steps = steps
+                .parse()
+                .map_err(|_| format!("Invalid step count: {}", steps))?;
+            exec(bytes, Some(steps), out)?
+        }
+        _ => {
+            return Err(format!(
+                "Usage: breadboard decode WORDS | breadboard exec BYTES [--steps N], got {}",
+                args.join(" ")
+            ));
+        }
+    }
+    Ok(true)
+}
+
+/// Prints one line per instruction of space or comma separated hex words
+fn decode(text: &str, out: &mut impl Write) -> Result<(), String> {
+    let mut bytes = Vec::new();
+    for token in text
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+    {
+        let digits = token.trim_start_matches("0x").trim_start_matches("0X");
+        let word = u16::from_str_radix(digits, 16)
+            .map_err(|_| format!("{} is not a 16-bit hex word", token))?;
+        bytes.extend(word.to_le_bytes());
+    }
+
+    let mut cpu = ATmemory::init();
+    let len = bytes.len();
+    cpu.load_flash_from_vec(bytes).map_err(|e| e.to_string())?;
+    for (_, _, mnemonic) in cpu.disassemble_range(0, len) {
+        writeln!(out, "{}", mnemonic).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Executes raw hex bytes from address 0 and prints PC, SP, SREG, cycles and nonzero registers
+fn exec(text: &str, steps: Option<usize>, out: &mut impl Write) -> Result<(), String> {
+    let mut cpu = ATmemory::init();
+    cpu.load_from_text(text).map_err(|e| e.to_string())?;
+    let result = match steps {
+        Some(steps) => cpu.step_n(steps).map(|_| ()),
+        None => cpu.run_to_completion(&[]),
+    };
+
+    let registers: Vec<String> = cpu.memory()[..32]
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| **value != 0)
+        .map(|(reg, value)| format!("r{}={:#04X}", reg, value))
+        .collect();
+    writeln!(
+        out,
+        "PC={:04X} SP={:04X} SREG={:#04X} cyc={}",
+        u32::from(cpu.pc()) * 2,
+        cpu.sp(),
+        cpu.sreg(),
+        cpu.cycle_cnt()
+    )
+    .map_err(|e| e.to_string())?;
+    if !registers.is_empty() {
+        writeln!(out, "{}", registers.join(" ")).map_err(|e| e.to_string())?;
+    }
+    result.map_err(|e| e.to_string())
+}
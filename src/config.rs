@@ -1,24 +1,54 @@
-use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::path::{Path, PathBuf};
 use std::{fmt, fs};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Config {
     pub display: DisplayConfig,
     pub theme: ThemeConfig,
     pub display_base: DisplayBaseConfig,
     pub bridge_address: String,
+    #[serde(default)]
+    pub sram_init: SramInit,
+    #[serde(default)]
+    pub start_pc: u16, // Word address execution starts at after reset
+    #[serde(
+        default,
+        alias = "skip_undecodable",
+        deserialize_with = "error_policy_or_flag"
+    )]
+    pub error_policy: ErrorPolicy, // Pause on a failed step or run undecodable words as NOP
+    #[serde(default)]
+    pub lint_zero_register: bool, // Warn when r1 is nonzero at a call or return
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DisplayConfig {
     pub memory_bytes_per_row: usize,
     pub memory_bytes_per_column: usize,
+    #[serde(default)]
+    pub flash_as_words: bool,
+    #[serde(default = "default_hex_font_size")]
+    pub hex_font_size: u16, // Text size of the flash, register and SRAM dumps
+    #[serde(default)]
+    pub word_addresses: bool, // Flash addresses and PC in words, like avr-objdump listings
+    #[serde(default)]
+    pub registers_grid: bool, // Register file as a 4-column grid instead of one column
+    #[serde(default)]
+    pub highlight_duration: u32, // Auto Run ticks a changed register or flag stays highlighted
+    #[serde(default)]
+    pub address_base: AddressBase, // Base of the flash and SRAM address columns
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_hex_font_size() -> u16 {
+    16
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ThemeConfig {
     pub mode: String,
+    #[serde(default)]
+    pub pc_highlight: Option<String>, // #RRGGBB, theme primary color when unset
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Copy)]
@@ -39,10 +69,126 @@ impl DisplayBase {
     pub const ALL: &'static [Self] = &[Self::Binary, Self::Decimal, Self::Hexadecimal];
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Base addresses are written in, binary is left out as too long for an address column
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum AddressBase {
+    #[default]
+    Hexadecimal,
+    Decimal,
+    Octal,
+}
+
+impl fmt::Display for AddressBase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl AddressBase {
+    /// A list with all the address bases.
+    pub const ALL: &'static [Self] = &[Self::Hexadecimal, Self::Decimal, Self::Octal];
+
+    /// Address padded to the digits a 16-bit address needs in this base
+    pub fn format(&self, addr: usize) -> String {
+        match self {
+            Self::Hexadecimal => format!("{:04X}", addr),
+            Self::Decimal => format!("{:05}", addr),
+            Self::Octal => format!("{:06o}", addr),
+        }
+    }
+}
+
+/// What Auto Run does when a step fails, pausing and skipping are exclusive
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum ErrorPolicy {
+    #[default]
+    Pause, // Stop on any error, scroll to the failing instruction and show a banner
+    SkipUndecodable, // Run undecodable words as NOP, other errors still stop
+}
+
+impl fmt::Display for ErrorPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Pause => write!(f, "Pause and show the error"),
+            Self::SkipUndecodable => write!(f, "Skip undecodable words as NOP"),
+        }
+    }
+}
+
+impl ErrorPolicy {
+    /// A list with all the policies.
+    pub const ALL: &'static [Self] = &[Self::Pause, Self::SkipUndecodable];
+
+    pub fn skips_undecodable(&self) -> bool {
+        matches!(self, Self::SkipUndecodable)
+    }
+}
+
+/// Reads `error_policy`, or the `skip_undecodable` flag configs were saved with before it
+fn error_policy_or_flag<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<ErrorPolicy, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Saved {
+        Policy(ErrorPolicy),
+        SkipUndecodable(bool),
+    }
+
+    Ok(match Saved::deserialize(deserializer)? {
+        Saved::Policy(policy) => policy,
+        Saved::SkipUndecodable(true) => ErrorPolicy::SkipUndecodable,
+        Saved::SkipUndecodable(false) => ErrorPolicy::Pause,
+    })
+}
+
+/// Content of SRAM after power-up and reset
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum SramInit {
+    #[default]
+    Zero,
+    Pattern(u8),
+    Random, // Same pseudo-random bytes on every reset
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DisplayBaseConfig {
     pub registers: DisplayBase,
     pub stack: DisplayBase,
+    #[serde(default)]
+    pub registers_signed: bool,
+    #[serde(default)]
+    pub pair_order: PairOrder,
+}
+
+/// Order the two registers of a pointer pair are shown in
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum PairOrder {
+    #[default]
+    HighLow, // r31:r30, as AVR documentation writes pairs
+    LowHigh, // r30:r31, bytes in memory order
+}
+
+impl fmt::Display for PairOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::HighLow => write!(f, "High:Low"),
+            Self::LowHigh => write!(f, "Low:High"),
+        }
+    }
+}
+
+impl PairOrder {
+    /// A list with both orders.
+    pub const ALL: &'static [Self] = &[Self::HighLow, Self::LowHigh];
+
+    /// Register names and pair value in this order, `low` is the register with the low byte
+    pub fn format_pair(&self, low: u8, value: u16) -> String {
+        match self {
+            Self::HighLow => format!("r{}:r{} = {:#06X}", low + 1, low, value),
+            Self::LowHigh => format!("r{}:r{} = {:#06X}", low, low + 1, value.swap_bytes()),
+        }
+    }
 }
 
 impl Default for Config {
@@ -51,35 +197,71 @@ impl Default for Config {
             display: DisplayConfig {
                 memory_bytes_per_row: 8,
                 memory_bytes_per_column: 128,
+                flash_as_words: false,
+                hex_font_size: default_hex_font_size(),
+                word_addresses: false,
+                registers_grid: false,
+                highlight_duration: 0,
+                address_base: AddressBase::Hexadecimal,
             },
             theme: ThemeConfig {
                 mode: "Dark".to_string(),
+                pc_highlight: None,
             },
             display_base: DisplayBaseConfig {
                 registers: DisplayBase::Decimal,
                 stack: DisplayBase::Hexadecimal,
+                registers_signed: false,
+                pair_order: PairOrder::HighLow,
             },
             bridge_address: "127.0.0.1:9000".to_string(),
+            sram_init: SramInit::Zero,
+            start_pc: 0,
+            error_policy: ErrorPolicy::Pause,
+            lint_zero_register: false,
         }
     }
 }
 
 impl Config {
-    pub fn load() -> Result<Self, String> {
-        let path = Self::get_config_path()?;
+    pub fn load() -> Result<(Self, Option<String>), String> {
+        Self::load_from(&Self::get_config_path()?)
+    }
+
+    /// Reads config at `path`, a file that fails to parse is moved to `config.toml.bak`
+    /// and defaults are used instead
+    ///
+    /// The second value is a warning for the user when the file was replaced by defaults.
+    pub fn load_from(path: &Path) -> Result<(Self, Option<String>), String> {
         if !path.exists() {
-            return Ok(Self::default());
+            return Ok((Self::default(), None));
         }
 
         let contents =
-            fs::read_to_string(&path).map_err(|e| format!("Failed to read config: {}", e))?;
+            fs::read_to_string(path).map_err(|e| format!("Failed to read config: {}", e))?;
 
-        toml::from_str(&contents).map_err(|e| format!("Failed to parse config: {}", e))
+        match toml::from_str(&contents) {
+            Ok(config) => Ok((config, None)),
+            Err(e) => {
+                let backup = path.with_extension("toml.bak");
+                fs::rename(path, &backup)
+                    .map_err(|e| format!("Failed to back up config: {}", e))?;
+                let warning = format!(
+                    "Failed to parse config: {}, using defaults, old config kept in {}",
+                    e,
+                    backup.display()
+                );
+                Ok((Self::default(), Some(warning)))
+            }
+        }
     }
 
     pub fn save(&self) -> Result<(), String> {
-        let path = Self::get_config_path()?;
+        self.save_to(&Self::get_config_path()?)
+    }
 
+    /// Writes config to `path`, creating its directory when missing
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)
                 .map_err(|e| format!("Failed to create config directory: {}", e))?;
@@ -88,7 +270,25 @@ impl Config {
         let toml_string = toml::to_string_pretty(self)
             .map_err(|e| format!("Failed to seialize config: {}", e))?;
 
-        fs::write(&path, toml_string).map_err(|e| format!("Failed to write config: {}", e))
+        fs::write(path, toml_string).map_err(|e| format!("Failed to write config: {}", e))
+    }
+
+    /// Whether this config differs from the saved one, a missing file counts as defaults
+    pub fn is_unsaved(&self) -> bool {
+        Self::get_config_path().map_or(true, |path| self.is_unsaved_at(&path))
+    }
+
+    /// Whether this config differs from the one at `path`
+    ///
+    /// Unlike `load_from` a file that fails to parse is left in place, it counts as unsaved.
+    pub fn is_unsaved_at(&self, path: &Path) -> bool {
+        if !path.exists() {
+            return *self != Self::default();
+        }
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| toml::from_str::<Self>(&contents).ok())
+            .is_none_or(|saved| saved != *self)
     }
 
     fn get_config_path() -> Result<PathBuf, String> {
@@ -0,0 +1,180 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Timer interrupt flag (TIFR) and mask (TIMSK) registers with the name and vector of each bit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimerInterrupts {
+    pub tifr: u16,                        // Data space address of the flag register
+    pub timsk: u16,                       // Data space address of the enable register
+    pub sources: [(&'static str, u8); 8], // Flag name and vector number, bit 0 first
+}
+
+const ATMEGA16_TIMERS: TimerInterrupts = TimerInterrupts {
+    tifr: 0x58,
+    timsk: 0x59,
+    sources: [
+        ("TOV0", 9),
+        ("OCF0", 19),
+        ("TOV1", 8),
+        ("OCF1B", 7),
+        ("OCF1A", 6),
+        ("ICF1", 5),
+        ("TOV2", 4),
+        ("OCF2", 3),
+    ],
+};
+
+const ATMEGA128_TIMERS: TimerInterrupts = TimerInterrupts {
+    tifr: 0x56,
+    timsk: 0x57,
+    sources: [
+        ("TOV0", 16),
+        ("OCF0", 15),
+        ("TOV1", 14),
+        ("OCF1B", 13),
+        ("OCF1A", 12),
+        ("ICF1", 11),
+        ("TOV2", 10),
+        ("OCF2", 9),
+    ],
+};
+
+/// AVR part the instructions are decoded for
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Device {
+    #[default]
+    ATmega16,
+    ATmega128,
+    ATtiny10,
+}
+
+impl Device {
+    /// A list with all the supported devices.
+    pub const ALL: &'static [Self] = &[Self::ATmega16, Self::ATmega128, Self::ATtiny10];
+
+    /// Bytes of program flash
+    pub fn flash_size(&self) -> usize {
+        match self {
+            Self::ATmega16 => 16 * 1024,
+            Self::ATmega128 => 128 * 1024,
+            Self::ATtiny10 => 1024,
+        }
+    }
+
+    /// Bits of the program counter, it counts words so jumps past flash wrap around
+    pub fn pc_mask(&self) -> u16 {
+        (self.flash_size() / 2 - 1) as u16
+    }
+
+    /// RAMPZ extends Z past the first 64K bytes of flash for ELPM
+    pub fn has_rampz(&self) -> bool {
+        matches!(self, Self::ATmega128)
+    }
+
+    /// Bytes of one flash page written by SPM, parts without self-programming have none
+    pub fn spm_page_size(&self) -> Option<usize> {
+        match self {
+            Self::ATmega16 => Some(128),
+            Self::ATmega128 => Some(256),
+            Self::ATtiny10 => None,
+        }
+    }
+
+    /// Data space address of SPMCSR, called SPMCR on the ATmega16
+    pub fn spmcsr(&self) -> u16 {
+        match self {
+            Self::ATmega128 => 0x68,
+            _ => 0x57,
+        }
+    }
+
+    /// Interrupt vectors including reset, vector 1 is INT0 on every supported part
+    pub fn vector_count(&self) -> u8 {
+        match self {
+            Self::ATmega16 => 21,
+            Self::ATmega128 => 35,
+            Self::ATtiny10 => 11,
+        }
+    }
+
+    /// Words per vector, parts with more than 8K bytes of flash have room for a JMP
+    pub fn vector_words(&self) -> u16 {
+        match self.flash_size() > 8 * 1024 {
+            true => 2,
+            false => 1,
+        }
+    }
+
+    /// Bytes of the interrupt vector table at the start of flash, one JMP or RJMP per vector
+    pub fn vector_table_size(&self) -> usize {
+        usize::from(self.vector_count()) * usize::from(self.vector_words()) * 2
+    }
+
+    /// First data space address of internal SRAM, past the registers and I/O space
+    pub fn sram_start(&self) -> u16 {
+        match self {
+            Self::ATmega16 => 0x60,
+            Self::ATmega128 => 0x100,
+            Self::ATtiny10 => 0x40,
+        }
+    }
+
+    /// Bytes of internal SRAM
+    pub fn sram_size(&self) -> usize {
+        match self {
+            Self::ATmega16 => 1024,
+            Self::ATmega128 => 4 * 1024,
+            Self::ATtiny10 => 32,
+        }
+    }
+
+    /// Last data space address, the top of internal SRAM
+    pub fn ram_end(&self) -> u16 {
+        *self.stack_region().end()
+    }
+
+    /// Bytes of data space, registers and I/O through the end of SRAM
+    pub fn data_size(&self) -> usize {
+        usize::from(self.ram_end()) + 1
+    }
+
+    /// Addresses the stack may occupy, the whole internal SRAM
+    pub fn stack_region(&self) -> RangeInclusive<u16> {
+        stack_region(self.sram_start(), self.sram_size())
+    }
+
+    /// Timer 0-2 interrupt registers, the ATtiny10 keeps its single timer elsewhere
+    pub fn timer_interrupts(&self) -> Option<TimerInterrupts> {
+        match self {
+            Self::ATmega16 => Some(ATMEGA16_TIMERS),
+            Self::ATmega128 => Some(ATMEGA128_TIMERS),
+            Self::ATtiny10 => None,
+        }
+    }
+
+    /// Reduced AVRrc core, LDS and STS take one word and reach only 0x40..=0xBF
+    pub fn reduced_core(&self) -> bool {
+        matches!(self, Self::ATtiny10)
+    }
+}
+
+/// Stack bounds of an SRAM layout, SP starts at the last byte (RAMEND) and grows down
+///
+/// ```
+/// use breadboard::device::stack_region;
+///
+/// assert_eq!(stack_region(0x60, 1024), 0x60..=0x45F);
+/// ```
+pub fn stack_region(sram_start: u16, sram_size: usize) -> RangeInclusive<u16> {
+    sram_start..=sram_start + (sram_size - 1) as u16
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ATmega16 => write!(f, "ATmega16"),
+            Self::ATmega128 => write!(f, "ATmega128"),
+            Self::ATtiny10 => write!(f, "ATtiny10"),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+use std::io;
+use std::ops::Range;
+
+use crate::device::Device;
+
+/// Errors raised while loading programs or executing them
+#[derive(Debug)]
+pub enum EmuError {
+    FileRead(io::Error),
+    HexParse { line: usize, msg: String },
+    Checksum { line: usize },
+    ProgramTooLarge { size: usize, max: usize },
+    OutOfBounds { addr: usize },
+    DecodeFailed { opcode: u16, pc: u16 },
+    ExecFailed { pc: u16 },
+    StackOverflow { pc: u16, sp: u16 },
+    StackUnderflow { sp: u16 },
+    Parse(String),
+    RangeOutOfBounds { range: Range<usize>, size: usize },
+    NotInSubroutine,
+    RunLimit { limit: usize },
+    StoppedAfter { done: usize, error: Box<EmuError> },
+    NoInterruptVector { device: Device, vector: u8 },
+    InterruptsDisabled,
+}
+
+impl fmt::Display for EmuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EmuError::FileRead(e) => write!(f, "Failed to read file: {}", e),
+            EmuError::HexParse { line, msg } => write!(f, "HEX line {}: {}", line, msg),
+            EmuError::Checksum { line } => write!(f, "HEX line {}: checksum mismatch", line),
+            EmuError::ProgramTooLarge { size, max } => {
+                write!(f, "Binary too large: {} bytes (max: {})", size, max)
+            }
+            EmuError::OutOfBounds { addr } => write!(f, "Address {:#06X} out of bounds", addr),
+            EmuError::DecodeFailed { opcode, pc } => {
+                write!(f, "Cannot decode {:#06X} at {:#06X}", opcode, pc)
+            }
+            EmuError::ExecFailed { pc } => {
+                write!(f, "Unable to execute instruction at {:#06X}", pc)
+            }
+            EmuError::StackOverflow { pc, sp } => {
+                write!(f, "Stack overflow at PC={:#06X} (SP={:#06X})", pc, sp)
+            }
+            EmuError::StackUnderflow { sp } => write!(f, "Stack underflow! SP={:#04X}", sp),
+            EmuError::Parse(msg) => write!(f, "{}", msg),
+            EmuError::RangeOutOfBounds { range, size } => write!(
+                f,
+                "Range {:#06X}..{:#06X} out of bounds (size {:#06X})",
+                range.start, range.end, size
+            ),
+            EmuError::NotInSubroutine => write!(f, "Not inside a subroutine"),
+            EmuError::RunLimit { limit } => {
+                write!(f, "Run did not stop within {} instructions", limit)
+            }
+            EmuError::StoppedAfter { done, error } => {
+                write!(f, "Stopped after {} instructions: {}", done, error)
+            }
+            EmuError::NoInterruptVector { device, vector } => {
+                write!(f, "{} has no interrupt vector {}", device, vector)
+            }
+            EmuError::InterruptsDisabled => {
+                write!(f, "Global interrupts are disabled, I is cleared")
+            }
+        }
+    }
+}
+
+/// I/O errors can't be compared, so file errors are equal when their kinds are
+impl PartialEq for EmuError {
+    fn eq(&self, other: &Self) -> bool {
+        use EmuError::*;
+        match (self, other) {
+            (FileRead(a), FileRead(b)) => a.kind() == b.kind(),
+            (HexParse { line: a, msg: m }, HexParse { line: b, msg: n }) => a == b && m == n,
+            (Checksum { line: a }, Checksum { line: b }) => a == b,
+            (ProgramTooLarge { size: a, max: m }, ProgramTooLarge { size: b, max: n }) => {
+                a == b && m == n
+            }
+            (OutOfBounds { addr: a }, OutOfBounds { addr: b }) => a == b,
+            (DecodeFailed { opcode: a, pc: p }, DecodeFailed { opcode: b, pc: q }) => {
+                a == b && p == q
+            }
+            (ExecFailed { pc: a }, ExecFailed { pc: b }) => a == b,
+            (StackOverflow { pc: a, sp: p }, StackOverflow { pc: b, sp: q }) => a == b && p == q,
+            (StackUnderflow { sp: a }, StackUnderflow { sp: b }) => a == b,
+            (Parse(a), Parse(b)) => a == b,
+            (RangeOutOfBounds { range: a, size: m }, RangeOutOfBounds { range: b, size: n }) => {
+                a == b && m == n
+            }
+            (NotInSubroutine, NotInSubroutine) => true,
+            (RunLimit { limit: a }, RunLimit { limit: b }) => a == b,
+            (StoppedAfter { done: a, error: e }, StoppedAfter { done: b, error: f }) => {
+                a == b && e == f
+            }
+            (
+                NoInterruptVector { device, vector },
+                NoInterruptVector {
+                    device: d,
+                    vector: v,
+                },
+            ) => device == d && vector == v,
+            (InterruptsDisabled, InterruptsDisabled) => true,
+            _ => false,
+        }
+    }
+}
+
+impl std::error::Error for EmuError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EmuError::FileRead(e) => Some(e),
+            EmuError::StoppedAfter { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for EmuError {
+    fn from(e: io::Error) -> Self {
+        EmuError::FileRead(e)
+    }
+}
@@ -0,0 +1,45 @@
+use std::fmt;
+
+/// Tiny programs bundled for trying the emulator without a toolchain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExampleProgram {
+    Blink,
+    Counter,
+    AddLoop,
+}
+
+impl ExampleProgram {
+    /// A list with all the bundled examples.
+    pub const ALL: &'static [Self] = &[Self::Blink, Self::Counter, Self::AddLoop];
+
+    pub fn bytes(&self) -> &'static [u8] {
+        match self {
+            Self::Blink => BLINK,
+            Self::Counter => COUNTER,
+            Self::AddLoop => ADD_LOOP,
+        }
+    }
+}
+
+impl fmt::Display for ExampleProgram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Blink => write!(f, "Blink PB0"),
+            Self::Counter => write!(f, "Count on PORTB"),
+            Self::AddLoop => write!(f, "Sum 10..1"),
+        }
+    }
+}
+
+/// ldi r16, 0xFF; out DDRB, r16; loop: sbi PORTB, 0; cbi PORTB, 0; rjmp loop
+const BLINK: &[u8] = &[0x0F, 0xEF, 0x07, 0xBB, 0xC0, 0x9A, 0xC0, 0x98, 0xFD, 0xCF];
+
+/// ldi r16, 0xFF; out DDRB, r16; clr r17; loop: out PORTB, r17; inc r17; rjmp loop
+const COUNTER: &[u8] = &[
+    0x0F, 0xEF, 0x07, 0xBB, 0x11, 0x27, 0x18, 0xBB, 0x13, 0x95, 0xFD, 0xCF,
+];
+
+/// ldi r16, 0; ldi r17, 10; loop: add r16, r17; dec r17; brne loop; end: rjmp end
+const ADD_LOOP: &[u8] = &[
+    0x00, 0xE0, 0x1A, 0xE0, 0x01, 0x0F, 0x1A, 0x95, 0xE9, 0xF7, 0xFF, 0xCF,
+];
@@ -0,0 +1,178 @@
+use std::io::{self, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use crate::memory::ATmemory;
+
+/// avr-gdb addresses data space from this offset, lower addresses are flash
+const DATA_OFFSET: usize = 0x80_0000;
+
+/// Stop reply after a step or a breakpoint, SIGTRAP
+const STOP_TRAP: &str = "S05";
+
+/// Stop reply after a failed step, SIGILL
+const STOP_ILLEGAL: &str = "S04";
+
+/// Remote serial protocol stub serving one avr-gdb session
+///
+/// Supports reading registers (`g`) and memory (`m`), stepping (`s`), continuing (`c`) and
+/// software breakpoints (`Z0`/`z0`). Other packets get the empty "unsupported" reply.
+#[derive(Debug)]
+pub struct GdbStub {
+    cpu: ATmemory,
+}
+
+impl GdbStub {
+    pub fn new(cpu: ATmemory) -> Self {
+        Self { cpu }
+    }
+    pub fn cpu(&self) -> &ATmemory {
+        &self.cpu
+    }
+
+    /// Waits for one debugger on `addr` and serves it until it detaches
+    ///
+    /// # Errors
+    ///
+    /// Address cannot be bound or the connection failed.
+    pub fn listen(&mut self, addr: impl ToSocketAddrs) -> io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        let (stream, _) = listener.accept()?;
+        self.serve(stream)
+    }
+
+    /// Answers packets on an accepted connection until detach, kill or disconnect
+    ///
+    /// # Errors
+    ///
+    /// Reading from or writing to the socket failed.
+    pub fn serve(&mut self, stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+        while let Some(packet) = read_packet(&mut reader, &mut writer)? {
+            let reply = self.handle(&packet);
+            writer.write_all(frame(&reply).as_bytes())?;
+            if packet == "D" || packet == "k" {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reply payload for one packet payload, without the `$` framing and checksum
+    pub fn handle(&mut self, packet: &str) -> String {
+        match packet.split_at(packet.chars().next().map_or(0, char::len_utf8)) {
+            ("?", _) => STOP_TRAP.to_string(),
+            ("g", _) => self.registers(),
+            ("m", args) => self.read_memory(args).unwrap_or_else(|| "E01".to_string()),
+            ("s", _) => match self.cpu.step() {
+                Ok(_) => STOP_TRAP.to_string(),
+                Err(_) => STOP_ILLEGAL.to_string(),
+            },
+            ("c", _) => match self.cpu.run_to_completion(&[]) {
+                Ok(()) => STOP_TRAP.to_string(),
+                Err(_) => STOP_ILLEGAL.to_string(),
+            },
+            ("Z", args) | ("z", args) => self.breakpoint(packet.starts_with('Z'), args),
+            ("D", _) => "OK".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// r0..r31, SREG, SP (2 bytes) and PC as a byte address (4 bytes), little endian hex
+    fn registers(&self) -> String {
+        let mut bytes = self.cpu.memory()[..32].to_vec();
+        bytes.push(self.cpu.sreg());
+        bytes.extend(self.cpu.sp().to_le_bytes());
+        bytes.extend((u32::from(self.cpu.pc()) * 2).to_le_bytes());
+        to_hex(&bytes)
+    }
+
+    /// `addr,len` in hex, data space is reached through `DATA_OFFSET`
+    fn read_memory(&self, args: &str) -> Option<String> {
+        let (addr, len) = args.split_once(',')?;
+        let addr = usize::from_str_radix(addr, 16).ok()?;
+        let len = usize::from_str_radix(len, 16).ok()?;
+        let bytes: Vec<u8> = match addr.checked_sub(DATA_OFFSET) {
+            Some(data) if data.checked_add(len)? <= self.cpu.memory().len() => (data..data + len)
+                .map(|addr| self.cpu.read_memory(addr as u16))
+                .collect(),
+            None if addr.checked_add(len)? <= self.cpu.flash().len() => {
+                self.cpu.flash()[addr..addr + len].to_vec()
+            }
+            _ => return None,
+        };
+        Some(to_hex(&bytes))
+    }
+
+    /// `0,addr,kind` sets (`Z`) or removes (`z`) a breakpoint at a flash byte address
+    fn breakpoint(&mut self, set: bool, args: &str) -> String {
+        let mut fields = args.split(',');
+        let addr = match (fields.next(), fields.next()) {
+            (Some("0"), Some(addr)) => u32::from_str_radix(addr, 16).ok(),
+            _ => return String::new(),
+        };
+        let Some(word_addr) = addr.and_then(|addr| u16::try_from(addr / 2).ok()) else {
+            return "E01".to_string();
+        };
+        if set {
+            self.cpu.set_breakpoint(word_addr, 1);
+        } else {
+            self.cpu.remove_breakpoint(word_addr);
+        }
+        "OK".to_string()
+    }
+}
+
+/// Reads and acks the next `$payload#xx` packet, skipping acks and interrupts, None on disconnect
+///
+/// A packet with a wrong checksum is NAKed and skipped, the debugger then sends it again.
+fn read_packet(reader: &mut impl Read, writer: &mut impl Write) -> io::Result<Option<String>> {
+    let mut byte = [0u8];
+    loop {
+        loop {
+            if reader.read(&mut byte)? == 0 {
+                return Ok(None);
+            }
+            if byte[0] == b'$' {
+                break;
+            }
+        }
+
+        let mut payload = Vec::new();
+        loop {
+            if reader.read(&mut byte)? == 0 {
+                return Ok(None);
+            }
+            if byte[0] == b'#' {
+                break;
+            }
+            payload.push(byte[0]);
+        }
+        let mut checksum = [0u8; 2];
+        reader.read_exact(&mut checksum)?;
+
+        let expected = payload
+            .iter()
+            .fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        let received = std::str::from_utf8(&checksum)
+            .ok()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        if received == Some(expected) {
+            writer.write_all(b"+")?;
+            return Ok(Some(String::from_utf8_lossy(&payload).into_owned()));
+        }
+        writer.write_all(b"-")?;
+    }
+}
+
+/// Wraps a reply payload as `$payload#xx`
+pub fn frame(payload: &str) -> String {
+    let checksum = payload
+        .bytes()
+        .fold(0u8, |sum, byte| sum.wrapping_add(byte));
+    format!("${}#{:02x}", payload, checksum)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -1,113 +1,538 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::path::PathBuf;
 use std::time::Duration;
 
 use iced::event::{self, Event};
 use iced::keyboard::key;
 use iced::theme::Mode;
+use iced::widget::scrollable::{RelativeOffset, Viewport};
 use iced::widget::{
-    button, checkbox, column, container, pick_list, row, rule, scrollable, slider, text, text_input,
+    Text, button, center, checkbox, column, container, mouse_area, opaque, operation, pick_list,
+    row, rule, scrollable, slider, stack, text, text_input, tooltip,
 };
 use iced::Length::Fill;
 use iced::{keyboard, window};
-use iced::{system, Element, Font, Task, Theme};
+use iced::{system, Alignment, Color, Element, Font, Size, Task, Theme};
 use rfd::FileDialog;
 
-use crate::config::{Config, DisplayBase};
-use crate::memory::ATmemory;
+use breadboard::config::{AddressBase, Config, DisplayBase, ErrorPolicy, PairOrder, SramInit};
+use breadboard::error::EmuError;
+use breadboard::examples::ExampleProgram;
+use breadboard::listing;
+use breadboard::memory::{
+    ATmemory, FREE_RUN_LIMIT, FlashLayout, FlashRegion, Instruction, IoOverride, MemSpace,
+};
+use breadboard::scenario::Scenario;
+use breadboard::snapshot::{self, Snapshot};
+
+const FLASH_SCROLLABLE: &str = "flash";
+/// Narrower windows stack the panes instead of placing them side by side
+const COMPACT_WIDTH: f32 = 1000.0;
+/// Text sizes offered for the flash, register and SRAM dumps
+const HEX_FONT_SIZES: std::ops::RangeInclusive<f64> = 10.0..=32.0;
+/// Auto Run ticks offered for highlighting changed registers and flags, 0 turns it off
+const HIGHLIGHT_DURATIONS: std::ops::RangeInclusive<f64> = 0.0..=10.0;
+/// Instructions Step N executes per message, the UI handles input between batches
+const STEP_BATCH: usize = 10_000;
 
 #[derive(Debug)]
 pub struct GUInterface {
+    address_base: AddressBase,
+    annotations: HashMap<u16, String>, // Listing labels and comments by word address
+    batch: Option<StepBatch>,          // Step N run in progress
+    break_addr: String,
+    break_hits: String,
     cpu: ATmemory,
+    decode_text: String, // Hex word typed into the decoder
+    disasm_len: String,
+    disasm_start: String,
+    disassembly: Option<Vec<String>>, // Lines shown in the disassembly popup
     display_base_registers: DisplayBase,
     display_base_stack: DisplayBase,
-    flash_file: Option<PathBuf>,
+    display_signed_registers: bool,
+    eeprom_addr: String,
+    eeprom_value: String,
+    error_banner: Option<String>, // Failed step shown above the panes until dismissed
+    fill_end: String,
+    fill_space: MemSpace,
+    fill_start: String,
+    fill_value: String,
+    flash_as_words: bool,
+    flash_changes: HashSet<usize>, // Flash bytes the last reload changed, highlighted briefly
+    flash_files: Vec<PathBuf>,     // Files loaded into flash in order, Reload loads them again
+    flash_viewport: Option<Viewport>,
+    hex_font_size: u16,
+    highlight_duration: u32,
+    highlights: Highlights, // Registers and flags changed during Auto Run, fading per tick
+    inspected_instruction: Option<u16>, // Word address shown in the inspector
     instructions_per_second: u32,
+    interrupt_vector: String,
+    io_override_addr: String,
+    io_override_toggle: bool,
+    io_override_value: String,
+    last_instruction: Option<String>, // Instruction executed by the last single step
+    last_program: Option<Vec<u8>>,    // Flash content of the last load, for Rerun
     memory_bytes_per_column: usize,
     memory_bytes_per_row: usize,
+    pair_order: PairOrder,
+    patch_error: Option<String>,
+    patch_text: String,
+    pause_snapshot: Option<Snapshot>, // State at the last breakpoint pause
+    poke_target: PokeTarget,
+    poke_value: String,
+    pc_highlight: Option<String>,
+    prev_sreg: u8, // SREG before the last step, for highlighting flipped flags
+    program_loaded: bool,
+    quick_load_text: String,
+    registers_grid: bool, // Register file as 4 columns of 8 instead of one tall column
+    reference_snapshot: Option<Snapshot>,
+    scenario: Option<Scenario>, // Loaded lab exercise, its breakpoints pause Auto Run
     show_ascii_in_flash: bool,
     show_settings: bool,
+    error_policy: ErrorPolicy,
+    lint_zero_register: bool,
+    sram_init: SramInit,
+    start_pc: u16,
     status_message: Option<String>,
+    step_count_text: String,
+    temp_address_base: AddressBase,
     temp_display_base_registers: DisplayBase,
     temp_display_base_stack: DisplayBase,
+    temp_display_signed_registers: bool,
+    temp_hex_font_size: u16,
+    temp_highlight_duration: u32,
     temp_instructions_per_second: u32,
     temp_memory_bytes_per_column: usize,
     temp_memory_bytes_per_row: usize,
+    temp_pair_order: PairOrder,
+    temp_pc_highlight: String,
+    temp_registers_grid: bool,
     temp_show_ascii_in_flash: bool,
+    temp_error_policy: ErrorPolicy,
+    temp_lint_zero_register: bool,
+    temp_word_addresses: bool,
     theme: Theme,
     theme_mode: Mode,
+    window_width: f32,
+    word_addresses: bool, // Flash addresses and PC shown as word addresses
     run_active: bool,
+    exit_save_failed: bool, // Settings failed to save on the last Exit, the next one closes anyway
     bridge_address: String,
     temp_bridge_address: String,
 }
 
+/// Arrangement of the main view panes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Layout {
+    Wide,    // Registers, SRAM, flash and ports side by side
+    Compact, // Registers above SRAM, ports below flash
+}
+
+/// CPU state the poke form edits while paused
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PokeTarget {
+    Register(u8),
+    Pc,
+    Sp,
+}
+
+impl PokeTarget {
+    fn all() -> Vec<Self> {
+        let mut targets = vec![Self::Pc, Self::Sp];
+        targets.extend((0..32).map(Self::Register));
+        targets
+    }
+}
+
+impl fmt::Display for PokeTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Register(idx) => write!(f, "r{}", idx),
+            Self::Pc => write!(f, "PC"),
+            Self::Sp => write!(f, "SP"),
+        }
+    }
+}
+
+/// Flash byte as drawn in the ASCII column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum AsciiCell {
+    Printable(char),
+    Placeholder, // Non-printable byte, drawn as a dimmed dot
+}
+
+/// Registers and SREG flags changed during Auto Run, each highlighted for a number of ticks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Highlights {
+    registers: [u32; 32], // Ticks left per register
+    flags: [u32; 8],      // Ticks left per SREG bit, bit 0 is C
+}
+
+impl Highlights {
+    /// Ages every highlight by one tick, then highlights what the last instruction changed
+    pub(crate) fn tick(&mut self, registers: &[u8], flags: u8, duration: u32) {
+        for ticks in self.registers.iter_mut().chain(self.flags.iter_mut()) {
+            *ticks = ticks.saturating_sub(1);
+        }
+        for &reg in registers {
+            self.registers[usize::from(reg)] = duration;
+        }
+        for bit in (0..8).filter(|bit| flags >> bit & 1 == 1) {
+            self.flags[bit] = duration;
+        }
+    }
+
+    pub(crate) fn register(&self, reg: usize) -> bool {
+        self.registers[reg] > 0
+    }
+
+    pub(crate) fn flag(&self, bit: usize) -> bool {
+        self.flags[bit] > 0
+    }
+}
+
+/// Condition that ends a batched run before its instruction count runs out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BatchGoal {
+    Count,
+    Address(u16),                // Word address Run Until stops at
+    Return { pc: u16, sp: u16 }, // Step Over, PC and SP after the call returns
+    Depth(u16),                  // Step Out, call depth the run started at
+}
+
+/// Step N run split into batches, so a long run keeps the UI responsive and can be stopped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct StepBatch {
+    pub(crate) done: usize,
+    pub(crate) count: usize,
+    pub(crate) goal: BatchGoal,
+}
+
+impl StepBatch {
+    pub(crate) fn new(count: usize) -> Self {
+        Self {
+            done: 0,
+            count,
+            goal: BatchGoal::Count,
+        }
+    }
+
+    /// Run Until, free-runs until PC reaches given word address
+    pub(crate) fn until(word_addr: u16) -> Self {
+        Self {
+            goal: BatchGoal::Address(word_addr),
+            ..Self::new(FREE_RUN_LIMIT)
+        }
+    }
+
+    /// Step Over, none if the instruction at PC is not a call and a single step does
+    pub(crate) fn over(cpu: &ATmemory) -> Option<Self> {
+        let pc = match cpu.instruction_at(cpu.pc()) {
+            Ok(Instruction::CALL { .. }) => cpu.pc().wrapping_add(2),
+            Ok(Instruction::RCALL { .. }) => cpu.pc().wrapping_add(1),
+            _ => return None,
+        };
+        Some(Self {
+            goal: BatchGoal::Return {
+                pc: pc & cpu.device().pc_mask(),
+                sp: cpu.sp(),
+            },
+            ..Self::new(FREE_RUN_LIMIT)
+        })
+    }
+
+    /// Step Out, free-runs until the current subroutine returns
+    ///
+    /// # Errors
+    ///
+    /// PC is not inside a subroutine.
+    pub(crate) fn out(cpu: &ATmemory) -> Result<Self, EmuError> {
+        match cpu.call_depth() {
+            0 => Err(EmuError::NotInSubroutine),
+            depth => Ok(Self {
+                goal: BatchGoal::Depth(depth),
+                ..Self::new(FREE_RUN_LIMIT)
+            }),
+        }
+    }
+
+    /// Whether the CPU is where the run should stop
+    pub(crate) fn reached(&self, cpu: &ATmemory) -> bool {
+        match self.goal {
+            BatchGoal::Count => false,
+            BatchGoal::Address(word_addr) => cpu.pc() == word_addr,
+            BatchGoal::Return { pc, sp } => cpu.pc() == pc && cpu.sp() == sp,
+            BatchGoal::Depth(depth) => cpu.call_depth() < depth,
+        }
+    }
+
+    /// Executes up to `size` of the remaining instructions, true while the run should continue
+    ///
+    /// A write to a watched address, a breakpoint reaching its hit count or reaching the goal
+    /// ends the run early, a watch hit is left for the caller to report.
+    ///
+    /// # Errors
+    ///
+    /// Step failed, the error tells how many instructions of the run were executed before.
+    pub(crate) fn advance(&mut self, cpu: &mut ATmemory, size: usize) -> Result<bool, EmuError> {
+        for _ in 0..size.min(self.count - self.done) {
+            if let Err(e) = cpu.step() {
+                return Err(EmuError::StoppedAfter {
+                    done: self.done,
+                    error: Box::new(e),
+                });
+            }
+            self.done += 1;
+            let hit = cpu.check_breakpoint();
+            if hit || cpu.watch_hit().is_some() || self.reached(cpu) {
+                return Ok(false);
+            }
+        }
+        Ok(self.done < self.count)
+    }
+
+    /// Status message once the run has ended
+    pub(crate) fn summary(&self, cpu: &ATmemory) -> String {
+        match self.goal {
+            BatchGoal::Address(word_addr) if self.reached(cpu) => {
+                format!("Stopped at {:#06X}", u32::from(word_addr) * 2)
+            }
+            BatchGoal::Return { .. } | BatchGoal::Depth(_) if self.reached(cpu) => {
+                format!("Returned to {:#06X}", u32::from(cpu.pc()) * 2)
+            }
+            BatchGoal::Count if self.done == self.count => {
+                format!("Stepped {} instructions", self.done)
+            }
+            BatchGoal::Count => {
+                format!("Stopped after {} of {} instructions", self.done, self.count)
+            }
+            _ if self.done == self.count => {
+                format!("Goal not reached within {} instructions", self.count)
+            }
+            _ => format!("Stopped after {} instructions", self.done),
+        }
+    }
+}
+
+/// Direction of a GPIO pin and the level displayed for it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PinState {
+    Input(bool),
+    Output(bool),
+}
+
 #[derive(Debug, Clone)]
 pub enum Message {
+    AppendHexToFlash,
     CPUstep,
+    ClearFlashChanges,
+    CheckScenario,
+    ClearReference,
+    CloseDisassembly,
     CloseSettings,
+    CopyFlashAsRust,
+    CopyValue(String),
+    BreakAddrChanged(String),
+    BreakHitsChanged(String),
+    DecodeWord(String),
+    DisasmLenChanged(String),
+    DisasmStartChanged(String),
+    DisassembleRange { start: u16, len: u16 },
+    DismissError,
+    EepromAddrChanged(String),
+    EepromValueChanged(String),
     Event(Event),
+    Exit,
+    ExportEep,
+    ExportHex,
+    FillEndChanged(String),
+    FillMemory {
+        space: MemSpace,
+        start: usize,
+        end: usize,
+        value: u8,
+    },
+    FillSpaceChanged(MemSpace),
+    FillStartChanged(String),
+    FillValueChanged(String),
+    FlashScrolled(Viewport),
+    InspectInstruction(u16),
+    InterruptVectorChanged(String),
+    IoOverrideAddrChanged(String),
+    IoOverrideToggleChanged(bool),
+    IoOverrideValueChanged(String),
     LoadBinToFlash,
+    LoadEepToEeprom,
+    LoadExample(ExampleProgram),
+    LoadFromText(String),
     LoadHexToFlash,
+    LoadListing,
+    LoadScenario,
     OpenSettings,
+    PatchInstruction { word_addr: u16, asm: String },
+    PatchTextChanged(String),
+    Poke { target: PokeTarget, value: u16 },
+    PokeTargetChanged(PokeTarget),
+    PokeValueChanged(String),
     PollIO,
+    QuickLoadTextChanged(String),
+    RemoveBreakpoint(u16),
     Reset,
+    ResetCounters,
     Restart,
+    RestartKeepingProgram,
     RunTick,
+    ReloadFlash,
     RunToggle,
+    RunUntil(u16),
+    SaveReference,
     SaveSettings,
     SettingsColumnChanged(usize),
+    SettingsHexFontSizeChanged(u16),
+    SettingsHighlightDurationChanged(u32),
     SettingsASCIIChanged(bool),
+    SettingsAddressBaseChanged(AddressBase),
     SettingsDisplayBaseRegistersChanged(DisplayBase),
     SettingsDisplayBaseStackChanged(DisplayBase),
+    SettingsDisplaySignedChanged(bool),
+    SettingsPairOrderChanged(PairOrder),
     SettingsInsSecChanged(u32),
+    SettingsLintZeroRegisterChanged(bool),
+    SettingsRegistersGridChanged(bool),
     SettingsRowChanged(usize),
     SettingsBridgeChanged(String),
+    SettingsPcHighlightChanged(String),
+    SettingsErrorPolicyChanged(ErrorPolicy),
+    SettingsWordAddressesChanged(bool),
+    SetBreakpoint { word_addr: u16, target: u32 },
+    SetEeprom { addr: usize, value: u8 },
+    SetIoOverride { addr: u16, value: Option<IoOverride> },
+    SetSreg(u8),
+    StopBatch,
+    StepCountChanged(String),
+    StepBatch,
+    StepN(usize),
+    StepOut,
+    StepOver,
     ThemeChanged(Mode),
+    ToggleFlashMode,
+    ToggleWatchpoint(u16),
+    TriggerInterrupt(u8),
+    TriggerReset,
+    WindowResized(Size),
 }
 
 impl GUInterface {
-    fn byte_to_ascii(byte: u8) -> char {
+    pub(crate) fn byte_to_ascii(byte: u8) -> AsciiCell {
         let range = 32..126;
         if range.contains(&byte) {
-            char::from(byte)
+            AsciiCell::Printable(char::from(byte))
         } else {
-            '.'
+            AsciiCell::Placeholder
+        }
+    }
+
+    /// Flash address in the unit the address column shows
+    pub(crate) fn shown_flash_addr(byte_addr: usize, word_addresses: bool) -> usize {
+        match word_addresses {
+            true => byte_addr / 2,
+            false => byte_addr,
+        }
+    }
+
+    /// Flash byte address of an address typed in the unit the address column shows
+    pub(crate) fn flash_byte_addr(shown: usize, word_addresses: bool) -> usize {
+        match word_addresses {
+            true => shown * 2,
+            false => shown,
+        }
+    }
+
+    /// SRAM address label, hexadecimal keeps its 0x prefix
+    pub(crate) fn sram_address(addr: usize, base: AddressBase) -> String {
+        match base {
+            AddressBase::Hexadecimal => format!("{:#05X}", addr),
+            base => base.format(addr),
         }
     }
 
-    fn format_memory_row(&self, addr: usize) -> Element<'_, Message> {
+    fn format_memory_row(
+        &self,
+        addr: usize,
+        layout: &FlashLayout,
+        hottest: u32,
+    ) -> Element<'_, Message> {
+        let size = Self::hex_text_size(self.hex_font_size);
         let mut row = row![];
 
-        row = row.push(text!("{:04X}:", addr).font(Font::MONOSPACE));
+        // Clicking the address copies the whole row
+        let row_bytes = &self.cpu.flash()[addr..addr + self.memory_bytes_per_row];
+        let shown = Self::shown_flash_addr(addr, self.word_addresses);
+        let label = text!("{}:", self.address_base.format(shown))
+            .font(Font::MONOSPACE)
+            .size(size);
+        let copy = Message::CopyValue(Self::clipboard_text(row_bytes, DisplayBase::Hexadecimal));
+        row = row.push(mouse_area(label).on_press(copy));
 
+        let pc_addr = usize::from(self.cpu.pc()) * 2;
         for seg in addr..addr + self.memory_bytes_per_row {
-            let seg_byte = if usize::from(self.cpu.pc() * 2) == seg
-                || usize::from((self.cpu.pc() * 2) + 1) == seg
-            {
-                text!(" {:02X}", self.cpu.flash()[seg]).style(text::primary)
+            let seg_byte = if pc_addr == seg || pc_addr + 1 == seg {
+                self.highlight_pc(text!(" {:02X}", self.cpu.flash()[seg]))
+            } else if self.flash_changes.contains(&seg) {
+                text!(" {:02X}", self.cpu.flash()[seg]).style(text::danger)
             } else {
-                text!(" {:02X}", self.cpu.flash()[seg])
+                // Vector table and the .data image stand out from the program code
+                let byte = text!(" {:02X}", self.cpu.flash()[seg]);
+                match layout.region_of(seg) {
+                    FlashRegion::Vectors => byte.style(text::primary),
+                    FlashRegion::Data => byte.style(text::success),
+                    FlashRegion::Text | FlashRegion::Unused => byte,
+                }
             };
-            row = row.push(seg_byte.font(Font::MONOSPACE));
+            let seg_byte = seg_byte.font(Font::MONOSPACE).size(size);
+            let heat = Self::heat_color(self.cpu.exec_count_at((seg / 2) as u16), hottest);
+            row = row.push(Self::with_heat(seg_byte, heat));
         }
 
         if self.show_ascii_in_flash {
-            row = row.push(text("        ").font(Font::MONOSPACE));
+            row = row.push(text("        ").font(Font::MONOSPACE).size(size));
 
             for seg in addr..addr + self.memory_bytes_per_row {
-                let seg_char = if usize::from(self.cpu.pc() * 2) == seg
-                    || usize::from((self.cpu.pc() * 2) + 1) == seg
-                {
-                    text!("{}", Self::byte_to_ascii(self.cpu.flash()[seg])).style(text::primary)
+                let seg_char = match Self::byte_to_ascii(self.cpu.flash()[seg]) {
+                    AsciiCell::Printable(c) => text!("{}", c),
+                    AsciiCell::Placeholder => text(".").style(text::secondary),
+                };
+                let seg_char = if pc_addr == seg || pc_addr + 1 == seg {
+                    self.highlight_pc(seg_char)
                 } else {
-                    text!("{}", Self::byte_to_ascii(self.cpu.flash()[seg]))
+                    seg_char
                 };
-                row = row.push(seg_char.font(Font::MONOSPACE));
+                row = row.push(seg_char.font(Font::MONOSPACE).size(size));
             }
         }
 
         row.spacing(2).into()
     }
 
+    /// Highlights PC bytes with the configured color, theme primary color otherwise
+    fn highlight_pc<'a>(&self, segment: Text<'a>) -> Text<'a> {
+        match self.pc_highlight.as_deref().and_then(Self::parse_color) {
+            Some(color) => segment.style(move |_: &Theme| text::Style { color: Some(color) }),
+            None => segment.style(text::primary),
+        }
+    }
+
+    /// Parses `#RRGGBB` color strings
+    pub(crate) fn parse_color(hex: &str) -> Option<Color> {
+        let digits = hex.trim().strip_prefix('#')?;
+        if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |idx: usize| u8::from_str_radix(&digits[idx..idx + 2], 16).ok();
+        Some(Color::from_rgb8(channel(0)?, channel(2)?, channel(4)?))
+    }
+
     fn get_memory_window_boundary(&self) -> (usize, usize) {
         let pc = self.cpu.pc() as i32;
         let half_window = self.memory_bytes_per_column as i32;
@@ -130,8 +555,11 @@ impl GUInterface {
     }
 
     pub fn new() -> Self {
-        let config = Config::load().unwrap_or_default();
-        let mut cpu = ATmemory::init();
+        let (config, warning) = Config::load().unwrap_or_else(|e| (Config::default(), Some(e)));
+        let mut cpu = ATmemory::init_with_sram(config.sram_init);
+        cpu.set_start_pc(config.start_pc);
+        cpu.set_skip_undecodable(config.error_policy.skips_undecodable());
+        cpu.set_lint_zero_register(config.lint_zero_register);
         cpu.connect_to_hw(&config.bridge_address).ok();
 
         Self {
@@ -141,124 +569,1087 @@ impl GUInterface {
                 _ => Mode::None,
             },
             theme: Theme::Dark,
+            annotations: HashMap::new(),
+            batch: None,
+            break_addr: String::new(),
+            break_hits: String::new(),
             cpu,
-            flash_file: None,
+            decode_text: String::new(),
+            disasm_len: String::new(),
+            disasm_start: String::new(),
+            disassembly: None,
+            eeprom_addr: String::new(),
+            eeprom_value: String::new(),
+            error_banner: None,
+            fill_end: String::new(),
+            fill_space: MemSpace::Data,
+            poke_target: PokeTarget::Pc,
+            poke_value: String::new(),
+            fill_start: String::new(),
+            fill_value: String::new(),
+            flash_as_words: config.display.flash_as_words,
+            flash_changes: HashSet::new(),
+            flash_files: Vec::new(),
+            flash_viewport: None,
+            patch_error: None,
+            patch_text: String::new(),
+            pc_highlight: config.theme.pc_highlight.clone(),
+            temp_pc_highlight: config.theme.pc_highlight.clone().unwrap_or_default(),
+            prev_sreg: 0,
+            last_program: None,
+            program_loaded: false,
+            quick_load_text: String::new(),
+            reference_snapshot: None,
+            pause_snapshot: None,
+            scenario: None,
             memory_bytes_per_row: config.display.memory_bytes_per_row,
             memory_bytes_per_column: config.display.memory_bytes_per_column,
+            hex_font_size: config.display.hex_font_size,
+            highlight_duration: config.display.highlight_duration,
+            highlights: Highlights::default(),
             show_ascii_in_flash: true,
             show_settings: false,
+            error_policy: config.error_policy,
+            lint_zero_register: config.lint_zero_register,
+            sram_init: config.sram_init,
+            start_pc: config.start_pc,
             temp_memory_bytes_per_row: config.display.memory_bytes_per_row,
             temp_memory_bytes_per_column: config.display.memory_bytes_per_column,
+            temp_hex_font_size: config.display.hex_font_size,
+            temp_highlight_duration: config.display.highlight_duration,
+            address_base: config.display.address_base,
+            temp_address_base: config.display.address_base,
             temp_show_ascii_in_flash: true,
+            temp_error_policy: config.error_policy,
+            temp_lint_zero_register: config.lint_zero_register,
+            temp_word_addresses: config.display.word_addresses,
+            registers_grid: config.display.registers_grid,
+            temp_registers_grid: config.display.registers_grid,
+            word_addresses: config.display.word_addresses,
+            inspected_instruction: None,
             instructions_per_second: 1,
+            interrupt_vector: String::new(),
+            io_override_addr: String::new(),
+            io_override_toggle: false,
+            io_override_value: String::new(),
+            last_instruction: None,
             temp_instructions_per_second: 1,
             temp_display_base_registers: DisplayBase::Decimal,
             display_base_registers: config.display_base.registers,
             temp_display_base_stack: DisplayBase::Hexadecimal,
             display_base_stack: config.display_base.stack,
+            temp_display_signed_registers: config.display_base.registers_signed,
+            display_signed_registers: config.display_base.registers_signed,
+            temp_pair_order: config.display_base.pair_order,
+            pair_order: config.display_base.pair_order,
             run_active: false,
-            status_message: None,
+            exit_save_failed: false,
+            window_width: window::Settings::default().size.width,
+            status_message: warning,
+            step_count_text: String::new(),
             bridge_address: config.bridge_address.clone(),
             temp_bridge_address: config.bridge_address.clone(),
         }
     }
 
     fn save_config(&self) -> Result<(), String> {
-        let config = Config {
-            display: crate::config::DisplayConfig {
+        self.current_config().save()
+    }
+
+    /// Settings in effect, as they would be written to disk
+    fn current_config(&self) -> Config {
+        Config {
+            display: breadboard::config::DisplayConfig {
                 memory_bytes_per_row: self.memory_bytes_per_row,
                 memory_bytes_per_column: self.memory_bytes_per_column,
+                flash_as_words: self.flash_as_words,
+                hex_font_size: self.hex_font_size,
+                word_addresses: self.word_addresses,
+                registers_grid: self.registers_grid,
+                highlight_duration: self.highlight_duration,
+                address_base: self.address_base,
             },
-            theme: crate::config::ThemeConfig {
+            theme: breadboard::config::ThemeConfig {
                 mode: match self.theme_mode {
                     Mode::Light => "Light".to_string(),
                     Mode::Dark => "Dark".to_string(),
                     Mode::None => String::new(),
                 },
+                pc_highlight: self.pc_highlight.clone(),
             },
-            display_base: crate::config::DisplayBaseConfig {
+            display_base: breadboard::config::DisplayBaseConfig {
                 registers: self.display_base_registers,
                 stack: self.display_base_stack,
+                registers_signed: self.display_signed_registers,
+                pair_order: self.pair_order,
             },
             bridge_address: self.bridge_address.clone(),
+            sram_init: self.sram_init,
+            start_pc: self.start_pc,
+            error_policy: self.error_policy,
+            lint_zero_register: self.lint_zero_register,
+        }
+    }
+
+    /// Puts the values edited in the settings dialog into effect
+    fn apply_settings(&mut self) {
+        self.memory_bytes_per_column = self.temp_memory_bytes_per_column;
+        self.memory_bytes_per_row = self.temp_memory_bytes_per_row;
+        self.hex_font_size = self.temp_hex_font_size;
+        self.highlight_duration = self.temp_highlight_duration;
+        self.address_base = self.temp_address_base;
+        self.show_ascii_in_flash = self.temp_show_ascii_in_flash;
+        self.word_addresses = self.temp_word_addresses;
+        self.registers_grid = self.temp_registers_grid;
+        self.error_policy = self.temp_error_policy;
+        self.cpu
+            .set_skip_undecodable(self.error_policy.skips_undecodable());
+        self.lint_zero_register = self.temp_lint_zero_register;
+        self.cpu.set_lint_zero_register(self.lint_zero_register);
+        self.instructions_per_second = self.temp_instructions_per_second;
+        self.display_base_registers = self.temp_display_base_registers;
+        self.display_base_stack = self.temp_display_base_stack;
+        self.display_signed_registers = self.temp_display_signed_registers;
+        self.pair_order = self.temp_pair_order;
+        self.bridge_address = self.temp_bridge_address.trim().to_string();
+        self.pc_highlight = match self.temp_pc_highlight.trim() {
+            "" => None,
+            color => Some(color.to_string()),
+        };
+    }
+
+    fn format_instruction_row(
+        &self,
+        addr: usize,
+        len: usize,
+        mnemonic: String,
+        hottest: u32,
+    ) -> Element<'_, Message> {
+        let mut bytes = String::new();
+        for seg in addr..(addr + len).min(self.cpu.flash().len()) {
+            bytes.push_str(&format!(" {:02X}", self.cpu.flash()[seg]));
+        }
+
+        let shown = self
+            .address_base
+            .format(Self::shown_flash_addr(addr, self.word_addresses));
+        let row = text!("{}:{:<12}  {}", shown, bytes, mnemonic)
+            .font(Font::MONOSPACE)
+            .size(Self::hex_text_size(self.hex_font_size));
+        let row = if usize::from(self.cpu.pc()) * 2 == addr {
+            self.highlight_pc(row)
+        } else if (addr..addr + len).any(|seg| self.flash_changes.contains(&seg)) {
+            row.style(text::danger)
+        } else {
+            row
+        };
+
+        let heat = Self::heat_color(self.cpu.exec_count_at((addr / 2) as u16), hottest);
+        Self::with_heat(container(row).width(Fill), heat)
+    }
+
+    /// Shades executed instructions, the most executed one is the brightest
+    pub(crate) fn heat_color(count: u32, hottest: u32) -> Option<Color> {
+        (count > 0).then(|| Color {
+            a: 0.6 * count as f32 / hottest.max(count) as f32,
+            ..Color::from_rgb8(0xFF, 0x70, 0x20)
+        })
+    }
+
+    /// Puts a heatmap background behind a flash cell or row
+    fn with_heat<'a>(
+        content: impl Into<Element<'a, Message>>,
+        heat: Option<Color>,
+    ) -> Element<'a, Message> {
+        match heat {
+            Some(heat) => container(content)
+                .style(move |_: &Theme| container::Style {
+                    background: Some(heat.into()),
+                    ..container::Style::default()
+                })
+                .into(),
+            None => content.into(),
+        }
+    }
+
+    /// Fresh CPU set up from the configuration
+    fn new_cpu(&self) -> ATmemory {
+        let mut cpu = ATmemory::init_with_sram(self.sram_init);
+        cpu.set_start_pc(self.start_pc);
+        cpu.set_skip_undecodable(self.error_policy.skips_undecodable());
+        cpu.set_lint_zero_register(self.lint_zero_register);
+        cpu
+    }
+
+    /// Restart Keeping Program, the kept program bytes go into a fresh CPU with current settings
+    ///
+    /// # Errors
+    ///
+    /// Program doesn't fit the flash of the fresh CPU, e.g. after switching to a smaller device.
+    pub(crate) fn restart_with_program(
+        mut fresh: ATmemory,
+        program: &[u8],
+    ) -> Result<ATmemory, EmuError> {
+        fresh.load_flash_from_vec(program.to_vec())?;
+        Ok(fresh)
+    }
+
+    /// Swaps in a fresh CPU for a program about to be loaded, state of the old program is dropped
+    fn replace_cpu(&mut self) {
+        self.run_active = false;
+        self.batch = None;
+        self.cpu = self.new_cpu();
+        self.prev_sreg = self.cpu.sreg();
+        self.last_instruction = None;
+        self.flash_files.clear();
+        self.flash_changes.clear();
+        self.scenario = None;
+        self.program_loaded = false;
+    }
+
+    /// Toolbar label with the instruction the next step executes
+    pub(crate) fn next_instruction_label(cpu: &ATmemory, program_loaded: bool) -> String {
+        match program_loaded {
+            true => format!("Next: {}", cpu.get_instruction()),
+            false => String::from("Next: —"),
+        }
+    }
+
+    /// Status bar label with the instruction the last single step executed
+    pub(crate) fn last_instruction_label(last: Option<&str>) -> String {
+        format!("Last: {}", last.unwrap_or("—"))
+    }
+
+    /// Steps the CPU once, keeping the executed instruction in `last`
+    pub(crate) fn step_recording(
+        cpu: &mut ATmemory,
+        last: &mut Option<String>,
+    ) -> Result<u8, EmuError> {
+        *last = Some(cpu.get_instruction());
+        cpu.step()
+    }
+
+    /// Banner for a step that failed during Auto Run, none unless the policy pauses on errors
+    ///
+    /// The failing address is only shown once, in the unit and base of the flash view.
+    pub(crate) fn error_banner(
+        cpu: &ATmemory,
+        error: &EmuError,
+        policy: ErrorPolicy,
+        word_addresses: bool,
+        base: AddressBase,
+    ) -> Option<String> {
+        let pc = Self::shown_flash_addr(usize::from(cpu.pc()) * 2, word_addresses);
+        let detail = match error {
+            EmuError::DecodeFailed { opcode, .. } => format!("Cannot decode {:#06X}", opcode),
+            EmuError::ExecFailed { .. } => "Unable to execute instruction".to_string(),
+            EmuError::StackOverflow { sp, .. } => {
+                format!("Stack overflow (SP={})", base.format(usize::from(*sp)))
+            }
+            error => error.to_string(),
+        };
+        (policy == ErrorPolicy::Pause)
+            .then(|| format!("Auto Run paused at {}: {}", base.format(pc), detail))
+    }
+
+    /// Position of the PC row within the flash window as a fraction of all rows
+    pub(crate) fn pc_row_fraction(pc_byte: usize, start: usize, end: usize, per_row: usize) -> f32 {
+        let rows = end.saturating_sub(start).div_ceil(per_row).max(1);
+        let pc_row = pc_byte.saturating_sub(start) / per_row;
+        (pc_row.min(rows - 1) as f32) / (rows as f32)
+    }
+
+    /// Appends warnings collected by the CPU to the status message
+    fn report_warnings(&mut self) {
+        let warnings = self.cpu.take_warnings();
+        if !warnings.is_empty() {
+            let status = self.status_message.take().unwrap_or_default();
+            self.status_message = Some(format!("{} | Warning: {}", status, warnings.join("; ")));
+        }
+    }
+
+    /// Pauses execution and reports the write if the last step hit a watchpoint
+    fn check_watch_hit(&mut self) {
+        if let Some((addr, value)) = self.cpu.take_watch_hit() {
+            self.run_active = false;
+            self.status_message = Some(format!("Watchpoint {:#06X} written: {:#04X}", addr, value));
+        }
+    }
+
+    /// Scrolls the flash view only when the PC row has left the visible region
+    fn follow_pc(&self) -> Task<Message> {
+        let Some(viewport) = self.flash_viewport else {
+            return Task::none();
+        };
+        let (start, end) = Self::get_memory_window_boundary(self);
+        let per_row = match self.flash_as_words {
+            true => 2,
+            false => self.memory_bytes_per_row,
         };
-        config.save()
+        let fraction = Self::pc_row_fraction(usize::from(self.cpu.pc()) * 2, start, end, per_row);
+
+        let content_height = viewport.content_bounds().height.max(1.0);
+        let top = viewport.absolute_offset().y / content_height;
+        let bottom = (viewport.absolute_offset().y + viewport.bounds().height) / content_height;
+        if (top..bottom).contains(&fraction) {
+            Task::none()
+        } else {
+            // Snapping to the row's own fraction always brings it into view
+            operation::snap_to(
+                FLASH_SCROLLABLE,
+                RelativeOffset {
+                    x: 0.0,
+                    y: fraction,
+                },
+            )
+        }
     }
 
     fn render_flash_memory(&self) -> Element<'_, Message> {
         let (start, end) = Self::get_memory_window_boundary(self);
         let mut rows = column![].spacing(2);
 
-        for addr in (start..end).step_by(self.memory_bytes_per_row) {
-            let row = self.format_memory_row(addr);
-            rows = rows.push(row);
+        let mode_toggle = match self.flash_as_words {
+            true => button(text("Show bytes")),
+            false => button(text("Show instructions")),
+        };
+
+        if self.flash_as_words {
+            let hottest = self.cpu.max_exec_count();
+            for (addr, len, mnemonic) in self.cpu.disassemble_range(start, end) {
+                let mnemonic = Self::annotate(mnemonic, self.annotations.get(&((addr / 2) as u16)));
+                // Click inspects the instruction, right click runs to it
+                let row = mouse_area(self.format_instruction_row(addr, len, mnemonic, hottest))
+                    .on_press(Message::InspectInstruction((addr / 2) as u16))
+                    .on_right_press(Message::RunUntil((addr / 2) as u16));
+                rows = rows.push(row);
+            }
+        } else {
+            let layout = self.cpu.flash_layout();
+            let hottest = self.cpu.max_exec_count();
+            for addr in (start..end).step_by(self.memory_bytes_per_row) {
+                // Right click runs to the first instruction starting on the row
+                let row = mouse_area(self.format_memory_row(addr, &layout, hottest))
+                    .on_right_press(Message::RunUntil(addr.div_ceil(2) as u16));
+                rows = rows.push(row);
+            }
         }
 
-        scrollable(rows.padding(4)).width(Fill).into()
+        // Assembles a replacement for the instruction at PC
+        let mut patch_bar = row![
+            mode_toggle.on_press(Message::ToggleFlashMode),
+            text_input("Patch instruction at PC, e.g. inc r16", &self.patch_text)
+                .on_input(Message::PatchTextChanged)
+                .on_submit(Message::PatchInstruction {
+                    word_addr: self.cpu.pc(),
+                    asm: self.patch_text.clone(),
+                })
+                .width(280.0),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+        if let Some(error) = &self.patch_error {
+            patch_bar = patch_bar.push(text(error).style(text::danger));
+        }
+
+        column![
+            patch_bar,
+            scrollable(rows.padding(4))
+                .id(FLASH_SCROLLABLE)
+                .on_scroll(Message::FlashScrolled)
+                .width(Fill)
+                .height(Fill),
+            self.render_inspector()
+        ]
+        .into()
+    }
+
+    /// Details of the instruction clicked in the disassembly
+    fn render_inspector(&self) -> Element<'_, Message> {
+        let Some(word_addr) = self.inspected_instruction else {
+            return column![].into();
+        };
+
+        let mut inspector = column![rule::horizontal(2)].padding(4);
+        for line in Self::inspect_lines(&self.cpu, word_addr, self.address_base) {
+            inspector = inspector.push(text(line).font(Font::MONOSPACE));
+        }
+        inspector.into()
+    }
+
+    /// Encoding, timing, register use and affected flags of the instruction at a word address
+    pub(crate) fn inspect_lines(cpu: &ATmemory, word_addr: u16, base: AddressBase) -> Vec<String> {
+        let instruction = match cpu.instruction_at(word_addr) {
+            Ok(instruction) => instruction,
+            Err(e) => return vec![e.to_string()],
+        };
+
+        let start = word_addr as usize * 2;
+        let encoding: Vec<String> = (start..start + instruction.words() as usize * 2)
+            .step_by(2)
+            .filter_map(|idx| cpu.flash().get(idx..idx + 2))
+            .map(|word| format!("{:04X}", u16::from_le_bytes([word[0], word[1]])))
+            .collect();
+        let registers = |regs: Vec<u8>| match regs.is_empty() {
+            true => String::from("—"),
+            false => regs
+                .iter()
+                .map(|reg| format!("r{}", reg))
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+        let flags: Vec<String> = "ITHSVNZC"
+            .chars()
+            .enumerate()
+            .filter(|(idx, _)| Self::sreg_flag_set(instruction.flags_affected(), *idx))
+            .map(|(_, flag)| flag.to_string())
+            .collect();
+
+        vec![
+            format!("{}: {}", base.format(word_addr.into()), instruction),
+            format!("Encoding: {}", encoding.join(" ")),
+            format!("Cycles: {}", instruction.cycles()),
+            format!("Reads: {}", registers(instruction.reads())),
+            format!("Writes: {}", registers(instruction.writes())),
+            match flags.is_empty() {
+                true => String::from("Flags: —"),
+                false => format!("Flags: {}", flags.join(" ")),
+            },
+        ]
+    }
+
+    /// Marks data space addresses armed with a watchpoint
+    fn watch_marker(&self, addr: usize) -> &'static str {
+        match self.cpu.memory_watchpoints().contains(&(addr as u16)) {
+            true => " W",
+            false => "",
+        }
     }
 
     fn render_registers(&self) -> Element<'_, Message> {
+        if self.registers_grid {
+            let columns = (0..4).map(|col| {
+                let regs = (0..32).filter(|&reg| Self::register_grid_cell(reg).1 == col);
+                column(regs.map(|reg| self.register_cell(reg)))
+                    .spacing(2)
+                    .into()
+            });
+            let grid = row(columns).spacing(12);
+            return scrollable(grid.padding(4)).width(Fill).into();
+        }
+
         let mut rows = column![].spacing(2);
         for reg in 0..32 {
-            rows = rows.push(
-                text!(
-                    "R{:02}={}",
-                    reg,
-                    Self::format_value(self.cpu.memory()[reg], self.display_base_registers)
-                )
-                .font(Font::MONOSPACE),
-            );
+            rows = rows.push(self.register_cell(reg));
         }
 
         scrollable(rows.padding(4)).width(Fill).into()
     }
 
+    fn register_cell(&self, reg: usize) -> Element<'_, Message> {
+        let value = Self::format_register(
+            self.cpu.memory()[reg],
+            self.display_base_registers,
+            self.display_signed_registers,
+        );
+        let cell = text!("R{:02}={}{}", reg, value, self.watch_marker(reg))
+            .font(Font::MONOSPACE)
+            .size(Self::hex_text_size(self.hex_font_size));
+        let cell = match (self.highlights.register(reg), self.changed_since_pause(reg)) {
+            (true, _) => cell.style(text::success),
+            // Changed earlier, between the breakpoint pause and the last step
+            (false, true) => cell.style(text::warning),
+            (false, false) => cell,
+        };
+        // Click copies the value, right click arms a watchpoint on the register
+        mouse_area(cell)
+            .on_press(Message::CopyValue(value))
+            .on_right_press(Message::ToggleWatchpoint(reg as u16))
+            .into()
+    }
+
+    /// Whether a data space byte differs from the state at the last breakpoint pause
+    fn changed_since_pause(&self, addr: usize) -> bool {
+        self.pause_snapshot
+            .as_ref()
+            .is_some_and(|pause| pause.memory.get(addr) != self.cpu.memory().get(addr))
+    }
+
+    /// (row, column) of a register in the grid, columns hold r0-r7, r8-r15, r16-r23, r24-r31
+    pub(crate) fn register_grid_cell(reg: usize) -> (usize, usize) {
+        (reg % 8, reg / 8)
+    }
+
+    /// Text size of the hex dumps, sizes outside of the settings slider are clamped into it
+    pub(crate) fn hex_text_size(font_size: u16) -> f32 {
+        (font_size as f64).clamp(*HEX_FONT_SIZES.start(), *HEX_FONT_SIZES.end()) as f32
+    }
+
+    /// Bytes pushed on the stack, it grows down from `top`
+    pub(crate) fn stack_used(sp: u16, top: u16) -> u16 {
+        top.saturating_sub(sp)
+    }
+
     fn render_sram(&self) -> Element<'_, Message> {
         let mut rows = column![].spacing(2);
-        for sp in (0x0060..0x0460).rev() {
-            match sp == self.cpu.sp() as usize {
-                true => {
-                    rows = rows.push(
-                        text!(
-                            "{:#05X}={}",
-                            sp,
-                            Self::format_value(self.cpu.memory()[sp], self.display_base_stack)
-                        )
-                        .font(Font::MONOSPACE)
-                        .style(text::primary),
-                    );
-                }
-                false => {
-                    rows = rows.push(
-                        text!(
-                            "{:#05X}={}",
-                            sp,
-                            Self::format_value(self.cpu.memory()[sp], self.display_base_stack)
-                        )
-                        .font(Font::MONOSPACE),
-                    );
+        let used = Color {
+            a: 0.25,
+            ..Color::from_rgb8(0x20, 0x90, 0xFF)
+        };
+        let region = self.cpu.stack_region();
+        let (bottom, top) = (usize::from(*region.start()), usize::from(*region.end()));
+        for sp in (bottom..=top).rev() {
+            let row = text!(
+                "{}={}{}",
+                Self::sram_address(sp, self.address_base),
+                Self::format_value(self.cpu.memory()[sp], self.display_base_stack),
+                self.watch_marker(sp)
+            )
+            .font(Font::MONOSPACE)
+            .size(Self::hex_text_size(self.hex_font_size));
+            let row = match sp == self.cpu.sp() as usize {
+                true => row.style(text::primary),
+                false => row,
+            };
+            // Click copies the value, right click arms a watchpoint on the address
+            let row = mouse_area(row)
+                .on_press(Message::CopyValue(Self::format_value(
+                    self.cpu.memory()[sp],
+                    self.display_base_stack,
+                )))
+                .on_right_press(Message::ToggleWatchpoint(sp as u16));
+            // Shades bytes between the top of the stack and SP
+            let row = container(row).width(Fill);
+            rows = rows.push(match sp > self.cpu.sp() as usize {
+                true => row.style(move |_: &Theme| container::Style {
+                    background: Some(used.into()),
+                    ..container::Style::default()
+                }),
+                false => row,
+            });
+        }
+
+        column![
+            text!(
+                "Stack used: {} bytes",
+                Self::stack_used(self.cpu.sp(), top as u16)
+            )
+            .font(Font::MONOSPACE),
+            scrollable(rows.padding(4)).width(Fill)
+        ]
+        .into()
+    }
+
+    /// Parses decimal or `0x` prefixed hexadecimal numbers
+    fn parse_number(text: &str) -> Option<usize> {
+        let text = text.trim();
+        match text.strip_prefix("0x").or(text.strip_prefix("0X")) {
+            Some(hex) => usize::from_str_radix(hex, 16).ok(),
+            None => text.parse().ok(),
+        }
+    }
+
+    fn render_fill_form(&self) -> Element<'_, Message> {
+        let fill = match (
+            Self::parse_number(&self.fill_start),
+            Self::parse_number(&self.fill_end),
+            Self::parse_number(&self.fill_value).and_then(|v| u8::try_from(v).ok()),
+        ) {
+            (Some(start), Some(end), Some(value)) => Some(Message::FillMemory {
+                space: self.fill_space,
+                start,
+                end,
+                value,
+            }),
+            _ => None,
+        };
+
+        row![
+            pick_list(
+                MemSpace::ALL,
+                Some(self.fill_space),
+                Message::FillSpaceChanged
+            ),
+            text_input("Start", &self.fill_start)
+                .on_input(Message::FillStartChanged)
+                .width(64.0),
+            text_input("End", &self.fill_end)
+                .on_input(Message::FillEndChanged)
+                .width(64.0),
+            text_input("Value", &self.fill_value)
+                .on_input(Message::FillValueChanged)
+                .width(48.0),
+            button(text("Fill")).on_press_maybe(fill),
+        ]
+        .spacing(4)
+        .into()
+    }
+
+    fn render_disassemble_form(&self) -> Element<'_, Message> {
+        let disassemble = match (
+            Self::parse_number(&self.disasm_start).and_then(|v| u16::try_from(v).ok()),
+            Self::parse_number(&self.disasm_len).and_then(|v| u16::try_from(v).ok()),
+        ) {
+            (Some(start), Some(len)) => Some(Message::DisassembleRange { start, len }),
+            _ => None,
+        };
+
+        row![
+            text_input("Start", &self.disasm_start)
+                .on_input(Message::DisasmStartChanged)
+                .width(64.0),
+            text_input("Length", &self.disasm_len)
+                .on_input(Message::DisasmLenChanged)
+                .width(64.0),
+            button(text("Disassemble")).on_press_maybe(disassemble),
+        ]
+        .spacing(4)
+        .into()
+    }
+
+    fn render_poke_form(&self) -> Element<'_, Message> {
+        let value = Self::parse_number(&self.poke_value).and_then(|v| u16::try_from(v).ok());
+        let poke = match (self.poke_target, value) {
+            (PokeTarget::Register(_), Some(value)) if value > 0xFF => None,
+            (target, Some(value)) => Some(Message::Poke { target, value }),
+            (_, None) => None,
+        };
+
+        row![
+            pick_list(
+                PokeTarget::all(),
+                Some(self.poke_target),
+                Message::PokeTargetChanged
+            ),
+            text_input("Value", &self.poke_value)
+                .on_input(Message::PokeValueChanged)
+                .width(64.0),
+            button(text("Set")).on_press_maybe(poke),
+        ]
+        .spacing(4)
+        .into()
+    }
+
+    /// EEPROM dump, 16 bytes per row, with a form writing one byte
+    fn render_eeprom(&self) -> Element<'_, Message> {
+        let eeprom = self.cpu.eeprom();
+        let addr = Self::parse_number(&self.eeprom_addr).filter(|addr| *addr < eeprom.len());
+        let value = Self::parse_number(&self.eeprom_value).and_then(|v| u8::try_from(v).ok());
+        let set = match (addr, value) {
+            (Some(addr), Some(value)) => Some(Message::SetEeprom { addr, value }),
+            _ => None,
+        };
+
+        let mut rows = column![].spacing(2);
+        for (idx, chunk) in eeprom.chunks(16).enumerate() {
+            let bytes: Vec<String> = chunk.iter().map(|byte| format!("{:02X}", byte)).collect();
+            rows = rows.push(
+                text!("{:03X}: {}", idx * 16, bytes.join(" "))
+                    .font(Font::MONOSPACE)
+                    .size(Self::hex_text_size(self.hex_font_size)),
+            );
+        }
+
+        column![
+            text("EEPROM"),
+            row![
+                text_input("Address", &self.eeprom_addr)
+                    .on_input(Message::EepromAddrChanged)
+                    .width(64.0),
+                text_input("Value", &self.eeprom_value)
+                    .on_input(Message::EepromValueChanged)
+                    .width(48.0),
+                button(text("Write")).on_press_maybe(set),
+            ]
+            .spacing(4),
+            scrollable(rows).height(160.0),
+        ]
+        .spacing(4)
+        .into()
+    }
+
+    fn render_breakpoint_form(&self) -> Element<'_, Message> {
+        let word_addr = Self::parse_number(&self.break_addr)
+            .map(|addr| Self::flash_byte_addr(addr, self.word_addresses) / 2)
+            .and_then(|addr| u16::try_from(addr).ok());
+        let target = match self.break_hits.trim() {
+            "" => Some(1),
+            hits => Self::parse_number(hits).and_then(|hits| u32::try_from(hits).ok()),
+        };
+        let set = match (word_addr, target) {
+            (Some(word_addr), Some(target)) => Some(Message::SetBreakpoint { word_addr, target }),
+            _ => None,
+        };
+
+        let mut breakpoints: Vec<_> = self.cpu.breakpoints().iter().collect();
+        breakpoints.sort_by_key(|(word_addr, _)| **word_addr);
+        let mut form = column![
+            row![
+                text_input("Break at", &self.break_addr)
+                    .on_input(Message::BreakAddrChanged)
+                    .width(64.0),
+                text_input("Hits", &self.break_hits)
+                    .on_input(Message::BreakHitsChanged)
+                    .width(48.0),
+                button(text("Break")).on_press_maybe(set),
+                button(text("Clear")).on_press_maybe(word_addr.map(Message::RemoveBreakpoint)),
+            ]
+            .spacing(4)
+        ]
+        .spacing(2);
+        for (word_addr, cond) in breakpoints {
+            form = form.push(text!(
+                "{:#06X} | {}/{}",
+                Self::shown_flash_addr(usize::from(*word_addr) * 2, self.word_addresses),
+                cond.hits,
+                cond.target
+            ));
+        }
+        form.into()
+    }
+
+    /// Pins unmodeled I/O registers, by data space address, so polling loops can progress
+    fn render_io_override_form(&self) -> Element<'_, Message> {
+        let addr =
+            Self::parse_number(&self.io_override_addr).and_then(|addr| u16::try_from(addr).ok());
+        let value =
+            Self::parse_number(&self.io_override_value).and_then(|value| u8::try_from(value).ok());
+        let pin = match (addr, value) {
+            (Some(addr), Some(value)) => Some(Message::SetIoOverride {
+                addr,
+                value: Some(match self.io_override_toggle {
+                    true => IoOverride::Toggle { value, high: true },
+                    false => IoOverride::Fixed(value),
+                }),
+            }),
+            _ => None,
+        };
+
+        let mut overrides: Vec<_> = self.cpu.io_overrides().iter().collect();
+        overrides.sort_by_key(|(addr, _)| **addr);
+        let mut form = column![
+            row![
+                text_input("I/O addr", &self.io_override_addr)
+                    .on_input(Message::IoOverrideAddrChanged)
+                    .width(64.0),
+                text_input("Value", &self.io_override_value)
+                    .on_input(Message::IoOverrideValueChanged)
+                    .width(48.0),
+                checkbox(self.io_override_toggle)
+                    .label("Toggle")
+                    .on_toggle(Message::IoOverrideToggleChanged),
+                button(text("Pin")).on_press_maybe(pin),
+                button(text("Unpin"))
+                    .on_press_maybe(addr.map(|addr| Message::SetIoOverride { addr, value: None })),
+            ]
+            .spacing(4)
+        ]
+        .spacing(2);
+        for (addr, value) in overrides {
+            form = form.push(match value {
+                IoOverride::Fixed(value) => text!("{:#06X} | {:#04X}", addr, value),
+                IoOverride::Toggle { value, .. } => {
+                    text!("{:#06X} | {:#04X} / 0x00", addr, value)
                 }
+            });
+        }
+        form.into()
+    }
+
+    /// Global interrupt enable and pending timer interrupts, None when nothing is pending
+    pub(crate) fn pending_interrupt_status(cpu: &ATmemory) -> Option<String> {
+        let pending = cpu.pending_interrupts();
+        if pending.is_empty() {
+            return None;
+        }
+        let sources: Vec<String> = pending
+            .iter()
+            .map(|(name, vector)| format!("{} (vector {})", name, vector))
+            .collect();
+        Some(match Self::sreg_flag_set(cpu.sreg(), 0) {
+            true => format!("Interrupt pending: {}", sources.join(", ")),
+            // Students wonder why the ISR doesn't run, the I flag is the answer
+            false => format!("Interrupt pending (disabled): {}", sources.join(", ")),
+        })
+    }
+
+    fn render_interrupt_state(&self) -> Element<'_, Message> {
+        let enabled = match Self::sreg_flag_set(self.cpu.sreg(), 0) {
+            true => text("Interrupts | enabled (I=1)").style(text::success),
+            false => text("Interrupts | disabled (I=0)").style(text::danger),
+        };
+        let mut state = column![enabled];
+        if let Some(pending) = Self::pending_interrupt_status(&self.cpu) {
+            state = state.push(text(pending).style(text::warning));
+        }
+        state.into()
+    }
+
+    /// Fires an interrupt vector or jumps to the reset vector without a peripheral
+    fn render_interrupt_form(&self) -> Element<'_, Message> {
+        let vector =
+            Self::parse_number(&self.interrupt_vector).and_then(|vector| u8::try_from(vector).ok());
+        row![
+            text_input("Vector", &self.interrupt_vector)
+                .on_input(Message::InterruptVectorChanged)
+                .width(64.0),
+            button(text("Fire")).on_press_maybe(
+                vector
+                    .filter(|_| self.program_loaded)
+                    .map(Message::TriggerInterrupt)
+            ),
+            button(text("Reset vector"))
+                .on_press_maybe(self.program_loaded.then_some(Message::TriggerReset)),
+        ]
+        .spacing(4)
+        .into()
+    }
+
+    fn render_decoder(&self) -> Element<'_, Message> {
+        let mut decoder = column![
+            text_input("Decode hex word", &self.decode_text)
+                .on_input(Message::DecodeWord)
+                .width(140.0)
+        ]
+        .spacing(2);
+        if !self.decode_text.trim().is_empty() {
+            for line in Self::decode_word_lines(&self.cpu, &self.decode_text) {
+                decoder = decoder.push(text(line).font(Font::MONOSPACE));
             }
         }
+        decoder.into()
+    }
 
-        scrollable(rows.padding(4)).width(Fill).into()
+    /// Mnemonic, operand fields and timing of a typed opcode, decoded for the current device
+    pub(crate) fn decode_word_lines(cpu: &ATmemory, text: &str) -> Vec<String> {
+        let text = text.trim();
+        let digits = text
+            .strip_prefix("0x")
+            .or(text.strip_prefix("0X"))
+            .unwrap_or(text);
+        let Ok(opcode) = u16::from_str_radix(digits, 16) else {
+            return vec![format!("{} is not a 16-bit hex word", text)];
+        };
+        match cpu.decode(opcode) {
+            Ok(instruction) => vec![
+                instruction.to_string(),
+                format!("Operands: {:?}", instruction),
+                format!("Cycles: {}", instruction.cycles()),
+            ],
+            Err(_) => vec![format!("{:#06X} is not a supported instruction", opcode)],
+        }
+    }
+
+    /// Flash bytes from `start` decoded as code, undecodable words are listed as `.dw`
+    pub(crate) fn disassembly_lines(
+        cpu: &ATmemory,
+        start: u16,
+        len: u16,
+        annotations: &HashMap<u16, String>,
+        base: AddressBase,
+    ) -> Vec<String> {
+        let start = start as usize;
+        cpu.disassemble_range(start, start + len as usize)
+            .into_iter()
+            .map(|(addr, _, mnemonic)| {
+                let line = format!("{}: {}", base.format(addr), mnemonic);
+                Self::annotate(line, annotations.get(&((addr / 2) as u16)))
+            })
+            .collect()
+    }
+
+    /// Appends the listing annotation of an instruction after its disassembly
+    fn annotate(line: String, annotation: Option<&String>) -> String {
+        match annotation {
+            Some(annotation) => format!("{:<24} {}", line, annotation),
+            None => line,
+        }
+    }
+
+    /// Popup with the disassembled selection, drawn over the main view
+    fn render_disassembly_popup<'a>(&'a self, lines: &'a [String]) -> Element<'a, Message> {
+        let mut listing = column![].spacing(2);
+        for line in lines {
+            listing = listing.push(text(line).font(Font::MONOSPACE));
+        }
+
+        opaque(center(
+            container(
+                column![
+                    row![
+                        text("Disassembly").size(20).width(Fill),
+                        button(text("Close")).on_press(Message::CloseDisassembly)
+                    ],
+                    scrollable(listing).height(400.0)
+                ]
+                .spacing(8),
+            )
+            .width(480.0)
+            .padding(12)
+            .style(container::rounded_box),
+        ))
+    }
+
+    /// Lists state changed since a stored snapshot, nothing when there is none
+    fn render_diff(&self, title: &str, reference: Option<&Snapshot>) -> Element<'_, Message> {
+        let Some(reference) = reference else {
+            return column![].into();
+        };
+
+        let deltas = reference.diff(&self.cpu.snapshot());
+        let mut diff = column![text!("{} | {} changes", title, deltas.len())];
+        for delta in deltas {
+            diff = diff.push(text(delta.to_string()).font(Font::MONOSPACE));
+        }
+        diff.into()
+    }
+
+    /// Executed instructions per mnemonic since reset, most frequent first
+    fn render_instruction_mix(&self) -> Element<'_, Message> {
+        let counts = self.cpu.mnemonic_counts();
+        if counts.is_empty() {
+            return column![].into();
+        }
+
+        let mut mix = column![text("Instruction Mix")];
+        for (mnemonic, count) in counts {
+            mix = mix.push(text!("{}: {}", mnemonic, count).font(Font::MONOSPACE));
+        }
+        mix.into()
     }
 
     fn render_sreg(&self) -> Element<'_, Message> {
-        let mut cols = row![text("Status Register | ")].spacing(2);
-        let flags = ["I", "T", "H", "S", "V", "N", "Z", "C"];
+        let mut cols = row![text("Status Register | ")].spacing(4);
+        let flags = [
+            ("I", "Global Interrupt Enable"),
+            ("T", "Bit Copy Storage"),
+            ("H", "Half Carry Flag"),
+            ("S", "Sign Flag (N xor V)"),
+            ("V", "Two's Complement Overflow Flag"),
+            ("N", "Negative Flag"),
+            ("Z", "Zero Flag"),
+            ("C", "Carry Flag"),
+        ];
 
-        for (idx, val) in flags.iter().enumerate() {
-            match (self.cpu.sreg() << idx & 0x80) == 128 {
-                true => cols = cols.push(text!("{}", val).style(text::primary)),
-                false => cols = cols.push(text!("{}", val)),
-            }
+        for (idx, (val, description)) in flags.iter().enumerate() {
+            let changed = Self::sreg_flag_changed(self.prev_sreg, self.cpu.sreg(), idx)
+                || self.highlights.flag(7 - idx);
+            let since_pause = self.pause_snapshot.as_ref().is_some_and(|pause| {
+                Self::sreg_flag_changed(pause.memory[0x5F], self.cpu.sreg(), idx)
+            });
+            let flag = match (Self::sreg_flag_set(self.cpu.sreg(), idx), changed) {
+                // Flipped by the last step
+                (set, true) => column![
+                    text!("{}", val).style(text::success),
+                    text!("{}", u8::from(set))
+                        .font(Font::MONOSPACE)
+                        .style(text::success)
+                ],
+                // Flipped since the breakpoint pause
+                (set, false) if since_pause => column![
+                    text!("{}", val).style(text::warning),
+                    text!("{}", u8::from(set))
+                        .font(Font::MONOSPACE)
+                        .style(text::warning)
+                ],
+                (true, false) => column![
+                    text!("{}", val).style(text::primary),
+                    text("1").font(Font::MONOSPACE).style(text::primary)
+                ],
+                (false, false) => column![text!("{}", val), text("0").font(Font::MONOSPACE)],
+            };
+            // Clicking a flag letter flips its bit
+            let flag = mouse_area(flag.align_x(Alignment::Center)).on_press(Message::SetSreg(
+                Self::toggle_sreg_flag(self.cpu.sreg(), idx),
+            ));
+            cols = cols.push(tooltip(
+                flag,
+                container(text(*description))
+                    .padding(4)
+                    .style(container::rounded_box),
+                tooltip::Position::Bottom,
+            ));
         }
 
         scrollable(cols).height(Fill).into()
     }
 
-    fn render_bits(label: &str, value: u8) -> Element<'_, Message> {
+    /// Tests SREG bit for letter at `idx` of I, T, H, S, V, N, Z, C (bit 7 down to bit 0)
+    pub(crate) fn sreg_flag_set(sreg: u8, idx: usize) -> bool {
+        (sreg << idx & 0x80) == 128
+    }
+
+    /// SREG as ITHSVNZC, set flags uppercase and clear ones lowercase
+    pub(crate) fn sreg_letters(sreg: u8) -> String {
+        "ITHSVNZC"
+            .chars()
+            .enumerate()
+            .map(|(idx, flag)| match Self::sreg_flag_set(sreg, idx) {
+                true => flag,
+                false => flag.to_ascii_lowercase(),
+            })
+            .collect()
+    }
+
+    /// One-line machine state for the footer, PC in the unit the address column shows
+    pub(crate) fn state_footer(cpu: &ATmemory, word_addresses: bool, base: AddressBase) -> String {
+        let pc = Self::shown_flash_addr(usize::from(cpu.pc()) * 2, word_addresses);
+        format!(
+            "PC={} SP={} SREG={} cyc={}",
+            base.format(pc),
+            base.format(cpu.sp().into()),
+            Self::sreg_letters(cpu.sreg()),
+            cpu.cycle_cnt()
+        )
+    }
+
+    /// Tests whether the flag at `idx` differs between `prev` and `sreg`
+    pub(crate) fn sreg_flag_changed(prev: u8, sreg: u8, idx: usize) -> bool {
+        Self::sreg_flag_set(prev ^ sreg, idx)
+    }
+
+    /// Flips SREG bit for letter at `idx` of I, T, H, S, V, N, Z, C (bit 7 down to bit 0)
+    pub(crate) fn toggle_sreg_flag(sreg: u8, idx: usize) -> u8 {
+        sreg ^ (0x80 >> idx)
+    }
+
+    /// State shown for each pin, indexed by bit, outputs follow PORTx and inputs PINx
+    pub(crate) fn pin_states(ddr: u8, port: u8, pin: u8) -> [PinState; 8] {
+        std::array::from_fn(|bit| match (ddr >> bit) & 1 {
+            1 => PinState::Output((port >> bit) & 1 == 1),
+            _ => PinState::Input((pin >> bit) & 1 == 1),
+        })
+    }
+
+    /// DDRx, PORTx and PINx of every port followed by the resulting pin states
+    fn render_ports(&self) -> Element<'_, Message> {
+        let mut ports = column![].spacing(2);
+        for (name, pin_addr) in [('A', 0x39), ('B', 0x36), ('C', 0x33), ('D', 0x30)] {
+            let pin = self.cpu.memory()[pin_addr];
+            let ddr = self.cpu.memory()[pin_addr + 1];
+            let port = self.cpu.memory()[pin_addr + 2];
+
+            // Outputs are drawn filled, inputs hollow, high levels are colored
+            let mut pins = row![text!("P{name} | ")].spacing(2);
+            for state in Self::pin_states(ddr, port, pin).iter().rev() {
+                pins = pins.push(match state {
+                    PinState::Output(true) => text("▪").style(text::success),
+                    PinState::Output(false) => text("▪"),
+                    PinState::Input(true) => text("▫").style(text::primary),
+                    PinState::Input(false) => text("▫"),
+                });
+            }
+
+            ports = ports.push(Self::render_bits(&format!("DDR{name}"), ddr));
+            ports = ports.push(Self::render_bits(&format!("Port{name}"), port));
+            ports = ports.push(Self::render_bits(&format!("Pin{name}"), pin));
+            ports = ports.push(pins);
+            ports = ports.push(rule::horizontal(2));
+        }
+        ports.into()
+    }
+
+    fn render_bits<'a>(label: &str, value: u8) -> Element<'a, Message> {
         let mut cols = row![text!("{label} | ")].spacing(2);
         for idx in 0..8 {
             match ((value << idx) & 0x80) == 128 {
@@ -276,14 +1667,21 @@ impl GUInterface {
 
         let keyboard_sub = event::listen().map(Message::Event);
 
+        let resize_sub = window::resize_events().map(|(_, size)| Message::WindowResized(size));
+
+        let mut subs = vec![theme_sub, io_poll_sub, keyboard_sub, resize_sub];
         if self.run_active {
             let interval_ms: u64 = (1000.0 / self.instructions_per_second as f64) as u64;
             let timer_sub =
                 iced::time::every(Duration::from_millis(interval_ms)).map(|_| Message::RunTick);
-            iced::Subscription::batch(vec![theme_sub, io_poll_sub, timer_sub, keyboard_sub])
-        } else {
-            iced::Subscription::batch(vec![theme_sub, io_poll_sub, keyboard_sub])
+            subs.push(timer_sub);
         }
+        if !self.flash_changes.is_empty() {
+            let clear_sub =
+                iced::time::every(Duration::from_secs(3)).map(|_| Message::ClearFlashChanges);
+            subs.push(clear_sub);
+        }
+        iced::Subscription::batch(subs)
     }
 
     pub fn theme(&self) -> Theme {
@@ -302,9 +1700,7 @@ impl GUInterface {
                 Task::none()
             }
             Message::LoadBinToFlash => {
-                state.run_active = false;
-                state.cpu = ATmemory::init();
-                state.flash_file = None;
+                state.replace_cpu();
                 let file = FileDialog::new()
                     .add_filter("Binary file", &["bin"])
                     .set_directory(std::env::current_dir().unwrap_or(std::env::home_dir().unwrap()))
@@ -312,77 +1708,613 @@ impl GUInterface {
                     .pick_file();
 
                 if let Some(path) = file.clone() {
-                    if let Some(path_str) = path.to_str() {
-                        let _ = state.cpu.load_bin(path_str);
-                    } else {
-                        state.status_message = Some("Error: Path is not valid UTF-8.".to_string());
+                    if let Err(e) = state.cpu.load_bin(&path) {
+                        state.status_message = Some(format!("Error: {}", e));
                         return Task::none();
                     }
                 } else {
                     state.status_message = Some("Error: No file selected.".to_string());
                     return Task::none();
                 }
-                state.flash_file = file.clone();
+                let path = file.unwrap();
+                state.status_message = Some(format!("Loaded {}", path.display()));
+                state.flash_files = vec![path];
+                state.program_loaded = true;
+                state.last_program = Some(state.cpu.program().to_vec());
                 state.cpu.connect_to_hw(&state.bridge_address).ok();
+                state.report_warnings();
+                Task::none()
+            }
+            Message::LoadEepToEeprom => {
+                let file = FileDialog::new()
+                    .add_filter("EEPROM file", &["eep"])
+                    .set_directory(std::env::current_dir().unwrap_or(std::env::home_dir().unwrap()))
+                    .set_title("Open EEPROM file")
+                    .pick_file();
+
+                let Some(path) = file else {
+                    state.status_message = Some("Error: No file selected.".to_string());
+                    return Task::none();
+                };
+                state.status_message = match state.cpu.load_eep(&path) {
+                    Ok(_) => Some(format!("Loaded EEPROM from {}", path.display())),
+                    Err(e) => Some(format!("Error: {}", e)),
+                };
+                Task::none()
+            }
+            Message::LoadHexToFlash => {
+                state.replace_cpu();
+                let file = FileDialog::new()
+                    .add_filter("Hex file", &["hex"])
+                    .set_directory(std::env::current_dir().unwrap_or(std::env::home_dir().unwrap()))
+                    .set_title("Open hex file")
+                    .pick_file();
+
+                if let Some(path) = file.clone() {
+                    if let Err(e) = state.cpu.load_hex(&path) {
+                        state.status_message = Some(format!("Error: {}", e));
+                        return Task::none();
+                    }
+                } else {
+                    state.status_message = Some("Error: No file selected.".to_string());
+                    return Task::none();
+                }
+
+                let path = file.unwrap();
+                state.status_message = Some(format!("Loaded {}", path.display()));
+                state.flash_files = vec![path];
+                state.program_loaded = true;
+                state.last_program = Some(state.cpu.program().to_vec());
+                state.cpu.connect_to_hw(&state.bridge_address).ok();
+                Task::none()
+            }
+            Message::ReloadFlash => {
+                if state.flash_files.is_empty() {
+                    return Task::none();
+                }
+                state.run_active = false;
+                state.batch = None;
+                let previous = state.cpu.flash().to_vec();
+                let mut cpu = state.new_cpu();
+                for path in &state.flash_files {
+                    let result = match path.extension().and_then(|ext| ext.to_str()) {
+                        Some("bin") => cpu.load_bin(path),
+                        _ => cpu.load_hex(path),
+                    };
+                    if let Err(e) = result {
+                        state.status_message = Some(format!("Error: {}", e));
+                        return Task::none();
+                    }
+                }
+
+                state.cpu = cpu;
+                state.last_program = Some(state.cpu.program().to_vec());
+                state.prev_sreg = state.cpu.sreg();
+                state.last_instruction = None;
+                state.cpu.connect_to_hw(&state.bridge_address).ok();
+                state.flash_changes = snapshot::changed_addresses(&previous, state.cpu.flash())
+                    .into_iter()
+                    .collect();
                 state.status_message = Some(format!(
-                    "Loaded {}",
-                    state.flash_file.clone().unwrap().as_os_str().display()
+                    "Reloaded {} file(s), {} bytes changed",
+                    state.flash_files.len(),
+                    state.flash_changes.len()
                 ));
+                state.report_warnings();
+                Task::none()
+            }
+            Message::ClearFlashChanges => {
+                state.flash_changes.clear();
+                Task::none()
+            }
+            Message::AppendHexToFlash => {
+                state.run_active = false;
+                state.batch = None;
+                let file = FileDialog::new()
+                    .add_filter("Hex file", &["hex"])
+                    .set_directory(std::env::current_dir().unwrap_or(std::env::home_dir().unwrap()))
+                    .set_title("Append hex file")
+                    .pick_file();
+
+                let Some(path) = file else {
+                    state.status_message = Some("Error: No file selected.".to_string());
+                    return Task::none();
+                };
+                // Flash is kept, the file only overwrites the addresses its records cover
+                if let Err(e) = state.cpu.load_hex(&path) {
+                    state.status_message = Some(format!("Error: {}", e));
+                    return Task::none();
+                }
+
+                state.status_message = Some(format!("Appended {}", path.display()));
+                state.flash_files.push(path);
+                state.flash_changes.clear();
+                state.program_loaded = true;
+                state.last_program = Some(state.cpu.program().to_vec());
+                Task::none()
+            }
+            Message::LoadFromText(text) => {
+                state.replace_cpu();
+                match state.cpu.load_from_text(&text) {
+                    Ok(_) => {
+                        state.program_loaded = true;
+                        state.last_program = Some(state.cpu.program().to_vec());
+                        state.status_message = Some("Loaded pasted program".to_string());
+                        state.report_warnings();
+                    }
+                    Err(e) => state.status_message = Some(format!("Error: {}", e)),
+                }
+                state.cpu.connect_to_hw(&state.bridge_address).ok();
+                Task::none()
+            }
+            Message::LoadExample(example) => {
+                state.replace_cpu();
+                match state.cpu.load_flash_from_vec(example.bytes().to_vec()) {
+                    Ok(_) => {
+                        state.program_loaded = true;
+                        state.last_program = Some(state.cpu.program().to_vec());
+                        state.status_message = Some(format!("Loaded example: {}", example));
+                    }
+                    Err(e) => state.status_message = Some(format!("Error: {}", e)),
+                }
+                state.cpu.connect_to_hw(&state.bridge_address).ok();
+                Task::none()
+            }
+            Message::LoadScenario => {
+                state.replace_cpu();
+                let file = FileDialog::new()
+                    .add_filter("Scenario", &["toml"])
+                    .set_directory(std::env::current_dir().unwrap_or(std::env::home_dir().unwrap()))
+                    .set_title("Open scenario")
+                    .pick_file();
+
+                let Some(path) = file else {
+                    state.status_message = Some("Error: No file selected.".to_string());
+                    return Task::none();
+                };
+                let scenario = match Scenario::load(&path) {
+                    Ok(scenario) => scenario,
+                    Err(e) => {
+                        state.status_message = Some(format!("Error: {}", e));
+                        return Task::none();
+                    }
+                };
+                if let Err(e) = scenario.apply(&mut state.cpu) {
+                    state.status_message = Some(format!("Error: {}", e));
+                    return Task::none();
+                }
+
+                state.flash_files = vec![scenario.program.clone()];
+                state.program_loaded = true;
+                state.last_program = Some(state.cpu.program().to_vec());
+                state.scenario = Some(scenario);
+                state.cpu.connect_to_hw(&state.bridge_address).ok();
+                state.status_message = Some(format!("Loaded scenario {}", path.display()));
+                state.report_warnings();
+                Task::none()
+            }
+            Message::LoadListing => {
+                let file = FileDialog::new()
+                    .add_filter("avr-objdump listing", &["lst", "lss", "txt"])
+                    .set_directory(std::env::current_dir().unwrap_or(std::env::home_dir().unwrap()))
+                    .set_title("Open listing")
+                    .pick_file();
+
+                let Some(path) = file else {
+                    state.status_message = Some("Error: No file selected.".to_string());
+                    return Task::none();
+                };
+                match std::fs::read_to_string(&path) {
+                    Ok(text) => {
+                        state.annotations = listing::parse_objdump_lst(&text);
+                        state.status_message = Some(format!(
+                            "Loaded {} annotations from {}",
+                            state.annotations.len(),
+                            path.display()
+                        ));
+                    }
+                    Err(e) => state.status_message = Some(format!("Error: {}", e)),
+                }
+                Task::none()
+            }
+            Message::CheckScenario => {
+                let Some(scenario) = &state.scenario else {
+                    return Task::none();
+                };
+                let mismatches = scenario.check(&state.cpu);
+                state.status_message = match mismatches.is_empty() {
+                    true => Some("Scenario passed".to_string()),
+                    false => Some(format!("Scenario failed: {}", mismatches.join(", "))),
+                };
+                Task::none()
+            }
+            Message::ExportHex => {
+                state.run_active = false;
+                let file = FileDialog::new()
+                    .add_filter("Hex file", &["hex"])
+                    .set_directory(std::env::current_dir().unwrap_or(std::env::home_dir().unwrap()))
+                    .set_title("Export hex file")
+                    .save_file();
+
+                let Some(path) = file else {
+                    state.status_message = Some("Error: No file selected.".to_string());
+                    return Task::none();
+                };
+                match std::fs::write(&path, state.cpu.dump_hex()) {
+                    Ok(_) => {
+                        state.status_message =
+                            Some(format!("Exported {}", path.as_os_str().display()))
+                    }
+                    Err(e) => state.status_message = Some(format!("Error: {}", e)),
+                }
+                Task::none()
+            }
+            Message::ExportEep => {
+                let file = FileDialog::new()
+                    .add_filter("EEPROM file", &["eep"])
+                    .set_directory(std::env::current_dir().unwrap_or(std::env::home_dir().unwrap()))
+                    .set_title("Export EEPROM file")
+                    .save_file();
+
+                let Some(path) = file else {
+                    state.status_message = Some("Error: No file selected.".to_string());
+                    return Task::none();
+                };
+                state.status_message = match std::fs::write(&path, state.cpu.dump_eep()) {
+                    Ok(_) => Some(format!("Exported EEPROM to {}", path.display())),
+                    Err(e) => Some(format!("Error: {}", e)),
+                };
+                Task::none()
+            }
+            Message::DismissError => {
+                state.error_banner = None;
+                Task::none()
+            }
+            Message::EepromAddrChanged(addr) => {
+                state.eeprom_addr = addr;
+                Task::none()
+            }
+            Message::EepromValueChanged(value) => {
+                state.eeprom_value = value;
+                Task::none()
+            }
+            Message::SetEeprom { addr, value } => {
+                state.status_message = match state.cpu.write_eeprom(addr, value) {
+                    Ok(_) => Some(format!("EEPROM {:#05X} = {:#04X}", addr, value)),
+                    Err(e) => Some(format!("Error: {}", e)),
+                };
+                Task::none()
+            }
+            Message::PatchInstruction { word_addr, asm } => {
+                match state.cpu.patch_instruction(word_addr, &asm) {
+                    Ok(_) => {
+                        state.patch_error = None;
+                        state.patch_text.clear();
+                        let byte_addr = u32::from(word_addr) * 2;
+                        state.status_message = Some(format!("Patched {:#06X}", byte_addr));
+                    }
+                    Err(e) => state.patch_error = Some(e.to_string()),
+                }
+                Task::none()
+            }
+            Message::PatchTextChanged(text) => {
+                state.patch_text = text;
+                state.patch_error = None;
+                Task::none()
+            }
+            Message::FillMemory {
+                space,
+                start,
+                end,
+                value,
+            } => {
+                state.status_message = match state.cpu.fill(space, start, end, value) {
+                    Ok(_) => Some(format!(
+                        "Filled {} {:#06X}..{:#06X} with {:#04X}",
+                        space, start, end, value
+                    )),
+                    Err(e) => Some(format!("Error: {}", e)),
+                };
+                Task::none()
+            }
+            Message::InterruptVectorChanged(vector) => {
+                state.interrupt_vector = vector;
+                Task::none()
+            }
+            Message::IoOverrideAddrChanged(addr) => {
+                state.io_override_addr = addr;
+                Task::none()
+            }
+            Message::IoOverrideToggleChanged(toggle) => {
+                state.io_override_toggle = toggle;
+                Task::none()
+            }
+            Message::IoOverrideValueChanged(value) => {
+                state.io_override_value = value;
+                Task::none()
+            }
+            Message::SetIoOverride { addr, value } => {
+                state.status_message = match state.cpu.set_io_override(addr, value) {
+                    Ok(()) if value.is_some() => Some(format!("Pinned I/O {:#06X}", addr)),
+                    Ok(()) => Some(format!("Unpinned I/O {:#06X}", addr)),
+                    Err(e) => Some(format!("Error: {}", e)),
+                };
+                Task::none()
+            }
+            Message::TriggerInterrupt(vector) => {
+                state.run_active = false;
+                state.prev_sreg = state.cpu.sreg();
+                state.last_instruction = None;
+                state.status_message = match state.cpu.trigger_interrupt(vector) {
+                    Ok(()) => Some(format!(
+                        "Interrupt vector {} fired, PC {:#06X}",
+                        vector,
+                        u32::from(state.cpu.pc()) * 2
+                    )),
+                    Err(e) => Some(format!("Error: {}", e)),
+                };
+                state.follow_pc()
+            }
+            Message::TriggerReset => {
+                state.run_active = false;
+                state.prev_sreg = state.cpu.sreg();
+                state.last_instruction = None;
+                state.cpu.trigger_reset();
+                state.status_message = Some("Jumped to the reset vector".to_string());
+                state.follow_pc()
+            }
+            Message::BreakAddrChanged(addr) => {
+                state.break_addr = addr;
+                Task::none()
+            }
+            Message::BreakHitsChanged(hits) => {
+                state.break_hits = hits;
+                Task::none()
+            }
+            Message::SetBreakpoint { word_addr, target } => {
+                state.cpu.set_breakpoint(word_addr, target);
+                state.status_message = Some(format!(
+                    "Breakpoint at {:#06X} stops on pass {}",
+                    u32::from(word_addr) * 2,
+                    target.max(1)
+                ));
+                Task::none()
+            }
+            Message::RemoveBreakpoint(word_addr) => {
+                let byte_addr = u32::from(word_addr) * 2;
+                state.status_message = match state.cpu.remove_breakpoint(word_addr) {
+                    true => Some(format!("Removed breakpoint {:#06X}", byte_addr)),
+                    false => Some(format!("No breakpoint at {:#06X}", byte_addr)),
+                };
+                Task::none()
+            }
+            Message::PokeTargetChanged(target) => {
+                state.poke_target = target;
+                Task::none()
+            }
+            Message::PokeValueChanged(value) => {
+                state.poke_value = value;
+                Task::none()
+            }
+            Message::Poke { target, value } => {
+                state.run_active = false;
+                let result = match target {
+                    PokeTarget::Register(idx) => state.cpu.set_register(idx, value as u8),
+                    PokeTarget::Pc => state.cpu.set_pc(value),
+                    PokeTarget::Sp => state.cpu.set_sp(value),
+                };
+                state.status_message = match result {
+                    Ok(_) => Some(format!("Set {} to {:#06X}", target, value)),
+                    Err(e) => Some(format!("Error: {}", e)),
+                };
+                state.follow_pc()
+            }
+            Message::FillSpaceChanged(space) => {
+                state.fill_space = space;
+                Task::none()
+            }
+            Message::FillStartChanged(start) => {
+                state.fill_start = start;
+                Task::none()
+            }
+            Message::FillEndChanged(end) => {
+                state.fill_end = end;
+                Task::none()
+            }
+            Message::DecodeWord(text) => {
+                state.decode_text = text;
                 Task::none()
             }
-            Message::LoadHexToFlash => {
-                state.run_active = false;
-                state.cpu = ATmemory::init();
-                state.flash_file = None;
-                let file = FileDialog::new()
-                    .add_filter("Hex file", &["hex"])
-                    .set_directory(std::env::current_dir().unwrap_or(std::env::home_dir().unwrap()))
-                    .set_title("Open hex file")
-                    .pick_file();
-
-                if let Some(path) = file.clone() {
-                    if let Some(path_str) = path.to_str() {
-                        let _ = state.cpu.load_hex(path_str);
-                    } else {
-                        state.status_message = Some("Error: Path is not valid UTF-8.".to_string());
-                        return Task::none();
-                    }
-                } else {
-                    state.status_message = Some("Error: No file selected.".to_string());
-                    return Task::none();
-                }
-
-                state.flash_file = file.clone();
-                state.cpu.connect_to_hw(&state.bridge_address).ok();
-                state.status_message = Some(format!(
-                    "Loaded {}",
-                    state.flash_file.clone().unwrap().as_os_str().display()
+            Message::DisasmStartChanged(start) => {
+                state.disasm_start = start;
+                Task::none()
+            }
+            Message::DisasmLenChanged(len) => {
+                state.disasm_len = len;
+                Task::none()
+            }
+            Message::DisassembleRange { start, len } => {
+                state.disassembly = Some(Self::disassembly_lines(
+                    &state.cpu,
+                    start,
+                    len,
+                    &state.annotations,
+                    state.address_base,
                 ));
                 Task::none()
             }
+            Message::CloseDisassembly => {
+                state.disassembly = None;
+                Task::none()
+            }
+            Message::FillValueChanged(value) => {
+                state.fill_value = value;
+                Task::none()
+            }
+            Message::QuickLoadTextChanged(text) => {
+                state.quick_load_text = text;
+                Task::none()
+            }
             Message::Reset => {
                 state.run_active = false;
+                state.batch = None;
+                state.pause_snapshot = None;
+                state.error_banner = None;
                 state.cpu.reset();
+                state.prev_sreg = state.cpu.sreg();
+                state.last_instruction = None;
+                Task::none()
+            }
+            Message::ResetCounters => {
+                state.cpu.reset_counters();
                 Task::none()
             }
             Message::Restart => {
+                state.pause_snapshot = None;
+                state.error_banner = None;
+                state.replace_cpu();
+                state.cpu.connect_to_hw(&state.bridge_address).ok();
+                Task::none()
+            }
+            Message::RestartKeepingProgram => {
+                let Some(program) = state.last_program.clone() else {
+                    return Task::none();
+                };
                 state.run_active = false;
-                state.cpu = ATmemory::init();
-                state.flash_file = None;
+                state.batch = None;
+                state.pause_snapshot = None;
+                state.error_banner = None;
+                state.last_instruction = None;
+                state.scenario = None;
+                match Self::restart_with_program(state.new_cpu(), &program) {
+                    Ok(cpu) => {
+                        state.cpu = cpu;
+                        state.program_loaded = true;
+                        state.status_message = Some("Restarted with the same program".to_string());
+                    }
+                    Err(e) => {
+                        state.cpu = state.new_cpu();
+                        state.program_loaded = false;
+                        state.status_message = Some(format!("Error: {}", e));
+                    }
+                }
+                state.prev_sreg = state.cpu.sreg();
                 state.cpu.connect_to_hw(&state.bridge_address).ok();
                 Task::none()
             }
             Message::CPUstep => {
+                // Shortcuts still arrive while a batched run is stepping
+                if state.batch.is_some() {
+                    return Task::none();
+                }
+                state.run_active = false;
+                state.prev_sreg = state.cpu.sreg();
+                if let Err(e) = Self::step_recording(&mut state.cpu, &mut state.last_instruction) {
+                    state.status_message = Some(format!("Execution error: {}", e));
+                };
+                state.report_warnings();
+                state.check_watch_hit();
+                state.follow_pc()
+            }
+            Message::StepOver => {
+                if state.batch.is_some() {
+                    return Task::none();
+                }
                 state.run_active = false;
+                state.prev_sreg = state.cpu.sreg();
+                state.last_instruction = None;
+                if let Some(batch) = StepBatch::over(&state.cpu) {
+                    state.batch = Some(batch);
+                    return Task::done(Message::StepBatch);
+                }
                 if let Err(e) = state.cpu.step() {
                     state.status_message = Some(format!("Execution error: {}", e));
                 };
+                state.check_watch_hit();
+                state.follow_pc()
+            }
+            Message::StepOut => {
+                if state.batch.is_some() {
+                    return Task::none();
+                }
+                state.run_active = false;
+                state.prev_sreg = state.cpu.sreg();
+                state.last_instruction = None;
+                match StepBatch::out(&state.cpu) {
+                    Ok(batch) => {
+                        state.batch = Some(batch);
+                        Task::done(Message::StepBatch)
+                    }
+                    Err(e) => {
+                        state.status_message = Some(format!("Execution error: {}", e));
+                        Task::none()
+                    }
+                }
+            }
+            Message::StepCountChanged(text) => {
+                state.step_count_text = text;
+                Task::none()
+            }
+            Message::StepN(count) => {
+                if state.batch.is_some() {
+                    return Task::none();
+                }
+                state.run_active = false;
+                state.prev_sreg = state.cpu.sreg();
+                state.last_instruction = None;
+                state.batch = Some(StepBatch::new(count));
+                Task::done(Message::StepBatch)
+            }
+            Message::StepBatch => {
+                let Some(mut batch) = state.batch.take() else {
+                    return Task::none();
+                };
+                match batch.advance(&mut state.cpu, STEP_BATCH) {
+                    Ok(true) => {
+                        state.batch = Some(batch);
+                        return Task::done(Message::StepBatch);
+                    }
+                    Ok(false) => state.status_message = Some(batch.summary(&state.cpu)),
+                    Err(e) => state.status_message = Some(format!("Execution error: {}", e)),
+                }
+                state.report_warnings();
+                state.check_watch_hit();
+                state.follow_pc()
+            }
+            Message::StopBatch => {
+                if let Some(batch) = state.batch.take() {
+                    state.status_message = Some(batch.summary(&state.cpu));
+                }
+                state.follow_pc()
+            }
+            Message::SaveReference => {
+                state.reference_snapshot = Some(state.cpu.snapshot());
+                state.status_message = Some("Saved reference state".to_string());
+                Task::none()
+            }
+            Message::ClearReference => {
+                state.reference_snapshot = None;
                 Task::none()
             }
+            Message::CopyFlashAsRust => {
+                state.status_message = Some("Copied program as Rust literal".to_string());
+                iced::clipboard::write(state.cpu.flash_as_rust_literal())
+            }
+            Message::CopyValue(value) => {
+                state.status_message = Some(format!("Copied {}", value));
+                iced::clipboard::write(value)
+            }
             Message::OpenSettings => {
                 state.run_active = false;
                 state.temp_memory_bytes_per_column = state.memory_bytes_per_column;
                 state.temp_memory_bytes_per_row = state.memory_bytes_per_row;
+                state.temp_error_policy = state.error_policy;
+                state.temp_lint_zero_register = state.lint_zero_register;
+                state.temp_hex_font_size = state.hex_font_size;
+                state.temp_highlight_duration = state.highlight_duration;
+                state.temp_address_base = state.address_base;
+                state.temp_word_addresses = state.word_addresses;
+                state.temp_registers_grid = state.registers_grid;
                 state.show_settings = true;
                 Task::none()
             }
@@ -393,6 +2325,10 @@ impl GUInterface {
                 state.cpu.connect_to_hw(&state.bridge_address).ok();
                 Task::none()
             }
+            Message::SettingsHexFontSizeChanged(val) => {
+                state.temp_hex_font_size = val;
+                Task::none()
+            }
             Message::SettingsRowChanged(val) => {
                 state.temp_memory_bytes_per_row = val;
                 Task::none()
@@ -405,14 +2341,28 @@ impl GUInterface {
                 state.temp_show_ascii_in_flash = val;
                 Task::none()
             }
+            Message::SettingsHighlightDurationChanged(val) => {
+                state.temp_highlight_duration = val;
+                Task::none()
+            }
+            Message::SettingsRegistersGridChanged(val) => {
+                state.temp_registers_grid = val;
+                Task::none()
+            }
+            Message::SettingsWordAddressesChanged(val) => {
+                state.temp_word_addresses = val;
+                Task::none()
+            }
+            Message::SettingsErrorPolicyChanged(policy) => {
+                state.temp_error_policy = policy;
+                Task::none()
+            }
+            Message::SettingsLintZeroRegisterChanged(val) => {
+                state.temp_lint_zero_register = val;
+                Task::none()
+            }
             Message::SaveSettings => {
-                state.memory_bytes_per_column = state.temp_memory_bytes_per_column;
-                state.memory_bytes_per_row = state.temp_memory_bytes_per_row;
-                state.show_ascii_in_flash = state.temp_show_ascii_in_flash;
-                state.instructions_per_second = state.temp_instructions_per_second;
-                state.display_base_registers = state.temp_display_base_registers;
-                state.display_base_stack = state.temp_display_base_stack;
-                state.bridge_address = state.temp_bridge_address.trim().to_string();
+                state.apply_settings();
                 state.show_settings = false;
                 state.cpu.connect_to_hw(&state.bridge_address).ok();
                 let _ = state.save_config();
@@ -426,20 +2376,139 @@ impl GUInterface {
                 state.temp_display_base_registers = display_base;
                 Task::none()
             }
+            Message::SettingsDisplaySignedChanged(val) => {
+                state.temp_display_signed_registers = val;
+                Task::none()
+            }
+            Message::SettingsPairOrderChanged(order) => {
+                state.temp_pair_order = order;
+                Task::none()
+            }
+            Message::SettingsAddressBaseChanged(address_base) => {
+                state.temp_address_base = address_base;
+                Task::none()
+            }
             Message::SettingsDisplayBaseStackChanged(display_base) => {
                 state.temp_display_base_stack = display_base;
                 Task::none()
             }
             Message::RunTick => {
-                if let Err(e) = state.cpu.step() {
+                state.prev_sreg = state.cpu.sreg();
+                let written = match state.cpu.instruction_at(state.cpu.pc()) {
+                    Ok(instruction) => instruction.writes(),
+                    Err(_) => Vec::new(),
+                };
+                if let Err(e) = Self::step_recording(&mut state.cpu, &mut state.last_instruction) {
                     state.run_active = false;
                     state.status_message = Some(format!("Execution error: {}", e));
-                    return Task::none();
+                    state.error_banner = Self::error_banner(
+                        &state.cpu,
+                        &e,
+                        state.error_policy,
+                        state.word_addresses,
+                        state.address_base,
+                    );
+                    return match state.error_banner {
+                        Some(_) => state.follow_pc(),
+                        None => Task::none(),
+                    };
+                }
+                if state.cpu.pc_beyond_program() {
+                    state.run_active = false;
+                    state.status_message = Some("Auto Run paused: PC beyond loaded program".into());
+                }
+                let pc_addr = u32::from(state.cpu.pc()) * 2;
+                let scenario_breakpoint = state.scenario.as_ref().is_some_and(|scenario| {
+                    scenario
+                        .breakpoints
+                        .iter()
+                        .any(|&addr| u32::from(addr) == pc_addr)
+                });
+                if state.cpu.check_breakpoint() || scenario_breakpoint {
+                    state.run_active = false;
+                    state.pause_snapshot = Some(state.cpu.snapshot());
+                    state.status_message =
+                        Some(format!("Auto Run paused: breakpoint at {:#06X}", pc_addr));
                 }
+                state.highlights.tick(
+                    &written,
+                    state.prev_sreg ^ state.cpu.sreg(),
+                    state.highlight_duration,
+                );
+                state.report_warnings();
+                state.check_watch_hit();
+                state.follow_pc()
+            }
+            Message::WindowResized(size) => {
+                state.window_width = size.width;
+                Task::none()
+            }
+            Message::InspectInstruction(word_addr) => {
+                state.inspected_instruction = Some(word_addr);
+                Task::none()
+            }
+            Message::FlashScrolled(viewport) => {
+                state.flash_viewport = Some(viewport);
                 Task::none()
             }
             Message::RunToggle => {
+                // F5 still arrives while a batched run is stepping, Auto Run would interleave
+                if !state.run_active && state.batch.is_some() {
+                    return Task::none();
+                }
                 state.run_active = !state.run_active;
+                state.highlights = Highlights::default();
+                Task::none()
+            }
+            Message::RunUntil(word_addr) => {
+                if state.batch.is_some() {
+                    return Task::none();
+                }
+                state.run_active = false;
+                state.prev_sreg = state.cpu.sreg();
+                state.last_instruction = None;
+                state.batch = Some(StepBatch::until(word_addr));
+                Task::done(Message::StepBatch)
+            }
+            Message::ToggleWatchpoint(addr) => {
+                state.status_message = match state.cpu.toggle_watchpoint(addr) {
+                    true => Some(format!("Watching writes to {:#06X}", addr)),
+                    false => Some(format!("Removed watchpoint {:#06X}", addr)),
+                };
+                Task::none()
+            }
+            Message::Exit => {
+                let config = state.current_config();
+                if !state.exit_save_failed
+                    && config.is_unsaved()
+                    && let Err(e) = config.save()
+                {
+                    // Stay open so the failure is seen, exiting again discards the settings
+                    state.exit_save_failed = true;
+                    state.status_message = Some(format!(
+                        "Error: Failed to save settings: {}. Exit again to quit without saving",
+                        e
+                    ));
+                    return Task::none();
+                }
+                iced::window::latest().and_then(iced::window::close)
+            }
+            Message::ToggleFlashMode => {
+                state.flash_as_words = !state.flash_as_words;
+                let _ = state.save_config();
+                Task::none()
+            }
+            Message::SetSreg(value) => {
+                if state.run_active {
+                    state.status_message = Some("Pause execution to edit SREG".to_string());
+                } else {
+                    state.cpu.set_sreg(value);
+                    state.prev_sreg = value;
+                }
+                Task::none()
+            }
+            Message::SettingsPcHighlightChanged(color) => {
+                state.temp_pc_highlight = color;
                 Task::none()
             }
             Message::SettingsBridgeChanged(addr) => {
@@ -463,6 +2532,19 @@ impl GUInterface {
                     ..
                 }) => Task::done(Message::CPUstep),
 
+                // Step over trigger
+                Event::Keyboard(keyboard::Event::KeyPressed {
+                    key: keyboard::Key::Named(key::Named::F10),
+                    ..
+                }) => Task::done(Message::StepOver),
+
+                // Step out trigger
+                Event::Keyboard(keyboard::Event::KeyPressed {
+                    key: keyboard::Key::Named(key::Named::F11),
+                    modifiers,
+                    ..
+                }) if modifiers.shift() => Task::done(Message::StepOut),
+
                 // Config open
                 Event::Keyboard(keyboard::Event::KeyPressed {
                     key: keyboard::Key::Named(key::Named::F12),
@@ -503,11 +2585,14 @@ impl GUInterface {
                         state.temp_show_ascii_in_flash = state.show_ascii_in_flash;
                         Task::none()
                     } else if c.as_str() == "q" && modifiers.control() {
-                        iced::window::latest().and_then(iced::window::close)
+                        Task::done(Message::Exit)
                     } else {
                         Task::none()
                     }
                 }
+
+                // Closing through the window manager saves like the Exit button
+                Event::Window(window::Event::CloseRequested) => Task::done(Message::Exit),
                 _ => Task::none(),
             },
         }
@@ -516,12 +2601,23 @@ impl GUInterface {
     pub fn view(&self) -> Element<'_, Message> {
         if self.show_settings {
             self.view_settings()
+        } else if let Some(lines) = &self.disassembly {
+            stack![self.view_main(), self.render_disassembly_popup(lines)].into()
         } else {
             self.view_main()
         }
     }
 
+    /// Pane arrangement for given window width
+    pub(crate) fn layout_for_width(width: f32) -> Layout {
+        match width < COMPACT_WIDTH {
+            true => Layout::Compact,
+            false => Layout::Wide,
+        }
+    }
+
     fn view_main(&self) -> Element<'_, Message> {
+        let layout = Self::layout_for_width(self.window_width);
         let mut content = column![].spacing(2).padding(4);
 
         let header = row![
@@ -533,101 +2629,223 @@ impl GUInterface {
                     false => "Disconnected",
                 }
             ),
-            button(text("Config")).on_press(Message::OpenSettings)
+            button(text("Config")).on_press(Message::OpenSettings),
+            button(text("Exit")).on_press(Message::Exit)
         ]
         .spacing(8);
         content = content.push(header);
 
         content = content.push(rule::horizontal(2));
 
+        let next_instruction = text(Self::next_instruction_label(&self.cpu, self.program_loaded));
+        let next_instruction = match self.program_loaded {
+            true => next_instruction,
+            false => next_instruction.style(text::secondary),
+        };
+
+        // Single steps and Auto Run would interleave with a batched run
+        let can_step = self.program_loaded && self.batch.is_none();
         let toolbar = row![
             button(text("Load .bin")).on_press(Message::LoadBinToFlash),
             button(text("Load .hex")).on_press(Message::LoadHexToFlash),
-            if self.flash_file.is_some() {
+            button(text("Append .hex")).on_press(Message::AppendHexToFlash),
+            button(text("Reload"))
+                .on_press_maybe((!self.flash_files.is_empty()).then_some(Message::ReloadFlash)),
+            button(text("Load .eep")).on_press(Message::LoadEepToEeprom),
+            button(text("Export .eep")).on_press(Message::ExportEep),
+            button(text("Load scenario")).on_press(Message::LoadScenario),
+            button(text("Load listing")).on_press(Message::LoadListing),
+            button(text("Check"))
+                .on_press_maybe(self.scenario.is_some().then_some(Message::CheckScenario)),
+            text_input("Paste hex bytes or Intel HEX", &self.quick_load_text)
+                .on_input(Message::QuickLoadTextChanged)
+                .on_submit(Message::LoadFromText(self.quick_load_text.clone()))
+                .width(240.0),
+            button(text("Load text")).on_press(Message::LoadFromText(self.quick_load_text.clone())),
+            pick_list(ExampleProgram::ALL, None, Message::LoadExample).placeholder("Examples"),
+            if self.program_loaded {
                 button(text("Restart"))
                     .style(button::danger)
                     .on_press(Message::Restart)
             } else {
                 button(text("Restart")).style(button::danger)
             },
-            if self.flash_file.is_some() {
-                button(text("Step")).on_press(Message::CPUstep)
-            } else {
-                button(text("Step"))
+            button(text("Rerun")).on_press_maybe(
+                self.last_program
+                    .as_ref()
+                    .map(|_| Message::RestartKeepingProgram)
+            ),
+            button(text("Step")).on_press_maybe(can_step.then_some(Message::CPUstep)),
+            next_instruction,
+            button(text("Step Over")).on_press_maybe(can_step.then_some(Message::StepOver)),
+            button(text("Step Out")).on_press_maybe(can_step.then_some(Message::StepOut)),
+            text_input("N", &self.step_count_text)
+                .on_input(Message::StepCountChanged)
+                .width(72.0),
+            match self.batch {
+                Some(batch) if batch.goal == BatchGoal::Count => {
+                    button(text!("Stop {}/{}", batch.done, batch.count))
+                        .style(button::secondary)
+                        .on_press(Message::StopBatch)
+                }
+                Some(batch) => button(text!("Stop ({})", batch.done))
+                    .style(button::secondary)
+                    .on_press(Message::StopBatch),
+                None => button(text("Step N")).on_press_maybe(
+                    self.step_count_text
+                        .trim()
+                        .parse()
+                        .ok()
+                        .filter(|_| self.program_loaded)
+                        .map(Message::StepN)
+                ),
             },
-            if self.flash_file.is_some() {
+            if self.program_loaded {
                 match self.run_active {
                     true => button(text("Disable Auto Run"))
                         .style(button::secondary)
                         .on_press(Message::RunToggle),
-                    false => button(text("Enable Auto Run")).on_press(Message::RunToggle),
+                    false => button(text("Enable Auto Run"))
+                        .on_press_maybe(can_step.then_some(Message::RunToggle)),
                 }
             } else {
                 button(text("Auto Run"))
             },
-            if self.flash_file.is_some() {
+            if self.program_loaded {
                 button(text("Reset")).on_press(Message::Reset)
             } else {
                 button(text("Reset"))
+            },
+            if self.program_loaded {
+                button(text("Export .hex")).on_press(Message::ExportHex)
+            } else {
+                button(text("Export .hex"))
+            },
+            if self.program_loaded {
+                button(text("Copy as Rust")).on_press(Message::CopyFlashAsRust)
+            } else {
+                button(text("Copy as Rust"))
+            },
+            if self.program_loaded {
+                button(text("Set Reference")).on_press(Message::SaveReference)
+            } else {
+                button(text("Set Reference"))
+            },
+            if self.reference_snapshot.is_some() {
+                button(text("Clear Reference")).on_press(Message::ClearReference)
+            } else {
+                button(text("Clear Reference"))
             }
         ]
         .spacing(8)
         .padding(4);
         content = content.push(toolbar);
         content = content.push(rule::horizontal(2));
+        if let Some(banner) = self.error_banner.as_ref() {
+            content = content.push(
+                row![
+                    text(banner).style(text::danger).width(Fill),
+                    button(text("Dismiss"))
+                        .style(button::secondary)
+                        .on_press(Message::DismissError)
+                ]
+                .spacing(8)
+                .padding(4),
+            );
+        }
 
+        let order = self.pair_order;
         let left_sidebar = column![
             scrollable(
                 column![
-                    text!("Program Counter | {:#08X}", self.cpu.pc()),
-                    text!("Stack Pointer | {:#06X}", self.cpu.sp()),
-                    text!("X Pointer | {:#06X}", self.cpu.xp()),
-                    text!("Y Pointer | {:#06X}", self.cpu.yp()),
-                    text!("Z Pointer | {:#06X}", self.cpu.zp()),
-                    text!("Cycle Counter | {:06}", self.cpu.cycle_cnt()),
+                    text!(
+                        "Program Counter | {}",
+                        self.address_base.format(Self::shown_flash_addr(
+                            usize::from(self.cpu.pc()) * 2,
+                            self.word_addresses
+                        ))
+                    ),
+                    text!(
+                        "Stack Pointer | {}",
+                        Self::sram_address(self.cpu.sp().into(), self.address_base)
+                    ),
+                    text!("X Pointer | {}", order.format_pair(26, self.cpu.xp())),
+                    text!("Y Pointer | {}", order.format_pair(28, self.cpu.yp())),
+                    text!("Z Pointer | {}", order.format_pair(30, self.cpu.zp())),
+                    row![
+                        text!("Cycle Counter | {:06}", self.cpu.cycle_cnt()),
+                        button(text("Zero").size(12))
+                            .padding([0, 4])
+                            .style(button::secondary)
+                            .on_press(Message::ResetCounters)
+                    ]
+                    .spacing(4),
+                    Self::render_interrupt_state(self),
                     text!("Frequency | {:02} Hz", self.instructions_per_second),
+                    Self::render_poke_form(self),
+                    Self::render_eeprom(self),
+                    Self::render_breakpoint_form(self),
+                    Self::render_interrupt_form(self),
+                    Self::render_io_override_form(self),
+                    Self::render_fill_form(self),
+                    Self::render_disassemble_form(self),
+                    Self::render_decoder(self),
+                    Self::render_diff(
+                        self,
+                        "Diff from reference",
+                        self.reference_snapshot.as_ref()
+                    ),
+                    Self::render_diff(self, "Since breakpoint", self.pause_snapshot.as_ref()),
+                    Self::render_instruction_mix(self),
                     Self::render_sreg(self),
                 ]
                 .padding(4)
             )
             .width(Fill),
             rule::horizontal(2),
-            row![
-                Self::render_registers(self),
-                rule::vertical(2),
-                Self::render_sram(self)
-            ]
+            match layout {
+                Layout::Wide => Element::from(row![
+                    Self::render_registers(self),
+                    rule::vertical(2),
+                    Self::render_sram(self)
+                ]),
+                Layout::Compact => column![
+                    Self::render_registers(self),
+                    rule::horizontal(2),
+                    Self::render_sram(self)
+                ]
+                .into(),
+            }
         ];
 
         let right_sidebar = column![
-            Self::render_bits("PortA", self.cpu.memory()[0x3B]),
-            Self::render_bits("DDRA", self.cpu.memory()[0x3A]),
-            Self::render_bits("PinA", self.cpu.memory()[0x39]),
-            rule::horizontal(2),
-            Self::render_bits("PortB", self.cpu.memory()[0x38]),
-            Self::render_bits("DDRB", self.cpu.memory()[0x37]),
-            Self::render_bits("PinB", self.cpu.memory()[0x36]),
-            rule::horizontal(2),
-            Self::render_bits("PortC", self.cpu.memory()[0x35]),
-            Self::render_bits("DDRC", self.cpu.memory()[0x34]),
-            Self::render_bits("PinC", self.cpu.memory()[0x33]),
-            rule::horizontal(2),
-            Self::render_bits("PortD", self.cpu.memory()[0x32]),
-            Self::render_bits("DDRD", self.cpu.memory()[0x31]),
-            Self::render_bits("PinD", self.cpu.memory()[0x30]),
+            Self::render_ports(self),
             // text("Timer0"),
             // text("Timer1"),
             // text("Timer2"),
         ]
         .padding(2);
 
-        let main_view = row![
-            left_sidebar,
-            rule::vertical(2),
-            Self::render_flash_memory(self),
-            rule::vertical(2),
-            right_sidebar,
-        ];
+        let main_view: Element<'_, Message> = match layout {
+            Layout::Wide => row![
+                left_sidebar,
+                rule::vertical(2),
+                Self::render_flash_memory(self),
+                rule::vertical(2),
+                right_sidebar,
+            ]
+            .into(),
+            Layout::Compact => row![
+                left_sidebar,
+                rule::vertical(2),
+                column![
+                    Self::render_flash_memory(self),
+                    rule::horizontal(2),
+                    right_sidebar
+                ]
+            ]
+            .into(),
+        };
 
         content = content.push(main_view);
         content = content.push(rule::horizontal(2));
@@ -638,8 +2856,21 @@ impl GUInterface {
         } else {
             status_bar = status_bar.push(text("").width(Fill));
         }
-        status_bar = status_bar.push(text!("Current instruction: {}", self.cpu.get_instruction()));
+        if self.program_loaded && self.cpu.pc_beyond_program() {
+            status_bar = status_bar.push(text("PC beyond loaded program | ").style(text::danger));
+        }
+        if self.program_loaded {
+            status_bar = status_bar.push(text!("crc32: {:08X} | ", self.cpu.flash_fingerprint()));
+        }
+        status_bar = status_bar.push(text!("depth: {} | ", self.cpu.call_depth()));
+        status_bar = status_bar.push(text!(
+            "{} | {}",
+            Self::last_instruction_label(self.last_instruction.as_deref()),
+            Self::next_instruction_label(&self.cpu, self.program_loaded)
+        ));
         content = content.push(status_bar);
+        let footer = Self::state_footer(&self.cpu, self.word_addresses, self.address_base);
+        content = content.push(text(footer).font(Font::MONOSPACE));
 
         container(content).into()
     }
@@ -684,6 +2915,32 @@ impl GUInterface {
             .padding(4),
         );
 
+        content = content.push(
+            row![
+                text("Hex dump text size:"),
+                slider(HEX_FONT_SIZES, self.temp_hex_font_size as f64, |val| {
+                    Message::SettingsHexFontSizeChanged(val as u16)
+                }),
+                text!("{} px", Self::hex_text_size(self.temp_hex_font_size))
+            ]
+            .spacing(4)
+            .padding(4),
+        );
+
+        content = content.push(
+            row![
+                text("Auto Run highlight:"),
+                slider(
+                    HIGHLIGHT_DURATIONS,
+                    self.temp_highlight_duration as f64,
+                    |val| Message::SettingsHighlightDurationChanged(val as u32)
+                ),
+                text!("{} ticks", self.temp_highlight_duration)
+            ]
+            .spacing(4)
+            .padding(4),
+        );
+
         content = content.push(
             row![checkbox(self.temp_show_ascii_in_flash)
                 .label("Display ASCII characters next to the flash hex dump?")
@@ -692,6 +2949,49 @@ impl GUInterface {
             .padding(4),
         );
 
+        content = content.push(
+            row![
+                checkbox(self.temp_registers_grid)
+                    .label("Show registers as a 4-column grid?")
+                    .on_toggle(Message::SettingsRegistersGridChanged)
+            ]
+            .spacing(4)
+            .padding(4),
+        );
+
+        content = content.push(
+            row![
+                checkbox(self.temp_word_addresses)
+                    .label("Show flash addresses and PC as word addresses?")
+                    .on_toggle(Message::SettingsWordAddressesChanged)
+            ]
+            .spacing(4)
+            .padding(4),
+        );
+
+        content = content.push(
+            row![
+                text("When a step fails:"),
+                pick_list(
+                    ErrorPolicy::ALL,
+                    Some(self.temp_error_policy),
+                    Message::SettingsErrorPolicyChanged
+                )
+            ]
+            .spacing(4)
+            .padding(4),
+        );
+
+        content = content.push(
+            row![
+                checkbox(self.temp_lint_zero_register)
+                    .label("Warn when r1 is left nonzero across calls (avr-gcc zero register)?")
+                    .on_toggle(Message::SettingsLintZeroRegisterChanged)
+            ]
+            .spacing(4)
+            .padding(4),
+        );
+
         content = content.push(
             row![
                 text("CPU frequency:"),
@@ -713,7 +3013,10 @@ impl GUInterface {
                     DisplayBase::ALL,
                     Some(self.temp_display_base_registers),
                     Message::SettingsDisplayBaseRegistersChanged
-                )
+                ),
+                checkbox(self.temp_display_signed_registers)
+                    .label("Signed decimal")
+                    .on_toggle(Message::SettingsDisplaySignedChanged)
             ]
             .spacing(4)
             .padding(4),
@@ -732,6 +3035,42 @@ impl GUInterface {
             .padding(4),
         );
 
+        content = content.push(
+            row![
+                text("Display addresses in:"),
+                pick_list(
+                    AddressBase::ALL,
+                    Some(self.temp_address_base),
+                    Message::SettingsAddressBaseChanged
+                )
+            ]
+            .spacing(4)
+            .padding(4),
+        );
+
+        content = content.push(
+            row![
+                text("Show register pairs as:"),
+                pick_list(
+                    PairOrder::ALL,
+                    Some(self.temp_pair_order),
+                    Message::SettingsPairOrderChanged
+                )
+            ]
+            .spacing(4)
+            .padding(4),
+        );
+
+        content = content.push(
+            row![
+                text("PC highlight color:"),
+                text_input("#RRGGBB, empty for theme color", &self.temp_pc_highlight)
+                    .on_input(Message::SettingsPcHighlightChanged)
+            ]
+            .spacing(4)
+            .padding(4),
+        );
+
         content = content.push(
             row![
                 text("Hardware bridge address:"),
@@ -746,10 +3085,28 @@ impl GUInterface {
     pub fn window_settings() -> window::Settings {
         window::Settings {
             icon: window::icon::from_file_data(include_bytes!("../assets/icon.png"), None).ok(),
+            exit_on_close_request: false, // Message::Exit saves the config, then closes
             ..Default::default()
         }
     }
 
+    /// Formats register value, decimal base optionally interpreted as `i8`
+    pub(crate) fn format_register(value: u8, base: DisplayBase, signed: bool) -> String {
+        match (base, signed) {
+            (DisplayBase::Decimal, true) => format!("{}", value as i8),
+            _ => Self::format_value(value, base),
+        }
+    }
+
+    /// Space separated values in given base, as copied to the clipboard
+    pub(crate) fn clipboard_text(values: &[u8], base: DisplayBase) -> String {
+        values
+            .iter()
+            .map(|value| Self::format_value(*value, base))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
     fn format_value(value: u8, base: DisplayBase) -> String {
         match base {
             DisplayBase::Binary => format!("{:#010b}", value),
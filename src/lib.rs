@@ -0,0 +1,46 @@
+//! Core of the Breadboard ATmega16 emulator, usable without any of the frontends.
+//!
+//! [`ATmemory`] holds the whole CPU state: load a program into flash, then
+//! [`step`](ATmemory::step) through it and inspect registers, SRAM or flags.
+//!
+//! ```
+//! use breadboard::ATmemory;
+//!
+//! let mut cpu = ATmemory::init();
+//! // ldi r16, 0x2A
+//! cpu.load_flash_from_vec(vec![0x0A, 0xE2]).unwrap();
+//! cpu.step().unwrap();
+//! assert_eq!(cpu.memory()[16], 0x2A);
+//! assert_eq!(cpu.pc(), 1);
+//! ```
+
+/// Single-line assembler used for patching flash
+pub mod asm;
+/// Headless `decode` and `exec` subcommands
+pub mod cli;
+/// User settings persisted to disk
+pub mod config;
+/// Supported AVR parts
+pub mod device;
+/// Errors returned by loading and execution
+pub mod error;
+/// Bundled demo programs
+pub mod examples;
+/// avr-gdb remote serial protocol stub
+pub mod gdb;
+/// avr-objdump listings mapped onto flash addresses
+pub mod listing;
+/// CPU state, instruction decoding and execution
+pub mod memory;
+/// Lab exercise manifests with initial and expected state
+pub mod scenario;
+/// CPU state captures and their differences
+pub mod snapshot;
+
+mod port;
+
+pub use device::Device;
+pub use error::EmuError;
+pub use memory::{ATmemory, BreakpointCond, Instruction, IoOverride, MemSpace};
+pub use scenario::Scenario;
+pub use snapshot::{Snapshot, StateDelta};
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+/// Labels, source lines and comments of an avr-objdump listing, keyed by word address
+///
+/// Labels (`00000000 <main>:`) and source lines interleaved by `avr-objdump -S` are attached to
+/// the instruction that follows them, the `;` comment of an instruction line to the line itself.
+///
+/// ```
+/// use breadboard::listing::parse_objdump_lst;
+///
+/// let annotations = parse_objdump_lst("00000000 <main>:\n   0:\t0f ef\tldi\tr16, 0xFF\t; 255\n");
+/// assert_eq!(annotations[&0], "main: ; 255");
+/// ```
+pub fn parse_objdump_lst(text: &str) -> HashMap<u16, String> {
+    let mut annotations = HashMap::new();
+    let mut pending: Vec<String> = Vec::new();
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty()
+            || trimmed.contains("file format")
+            || trimmed.starts_with("Disassembly of section")
+        {
+            continue;
+        }
+
+        if let Some(label) = parse_label(trimmed) {
+            pending.push(format!("{}:", label));
+        } else if let Some((byte_addr, comment)) = parse_instruction(line) {
+            pending.extend(comment.map(|comment| format!("; {}", comment)));
+            if !pending.is_empty() {
+                annotations.insert((byte_addr / 2) as u16, pending.join(" "));
+            }
+            pending.clear();
+        } else {
+            pending.push(trimmed.to_string());
+        }
+    }
+    annotations
+}
+
+/// Name of a symbol header like `00000000 <main>:`
+fn parse_label(line: &str) -> Option<&str> {
+    let (addr, rest) = line.split_once(' ')?;
+    u32::from_str_radix(addr, 16).ok()?;
+    rest.strip_prefix('<')?.strip_suffix(">:")
+}
+
+/// Byte address and `;` comment of an instruction line like `   2:\t0f ef\tldi\tr16, 0xFF`
+fn parse_instruction(line: &str) -> Option<(u32, Option<&str>)> {
+    let (addr, rest) = line.split_once(':')?;
+    if !line.starts_with(' ') || !rest.starts_with('\t') {
+        return None;
+    }
+    let addr = u32::from_str_radix(addr.trim(), 16).ok()?;
+    let comment = rest.split_once(';').map(|(_, comment)| comment.trim());
+    Some((addr, comment.filter(|comment| !comment.is_empty())))
+}
@@ -1,6 +1,3 @@
-mod config;
-mod memory;
-mod port;
 mod tests;
 
 #[cfg(feature = "gui")]
@@ -13,6 +10,27 @@ fn main() {
     #[cfg(not(any(feature = "gui", feature = "tui")))]
     compile_error!("Mut enable either 'gui' or 'tui' feature");
 
+    // Arguments after the program name, which may be missing, paths need not be UTF-8
+    let args: Vec<std::ffi::OsString> = std::env::args_os().skip(1).collect();
+    match breadboard::cli::run(&args, &mut std::io::stdout()) {
+        Ok(true) => return,
+        Ok(false) => {}
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+    if let [flag, addr, program] = args.as_slice()
+        && flag == "--gdb"
+    {
+        let Some(addr) = addr.to_str() else {
+            eprintln!("Invalid address: {}", addr.to_string_lossy());
+            std::process::exit(1);
+        };
+        run_gdb(addr, std::path::Path::new(program));
+        return;
+    }
+
     #[cfg(feature = "gui")]
     {
         use crate::gui::GUInterface;
@@ -53,6 +71,28 @@ fn main() {
     }
 }
 
+/// Serves avr-gdb on `addr` with `program` loaded, no frontend is started
+fn run_gdb(addr: &str, program: &std::path::Path) {
+    use breadboard::ATmemory;
+    use breadboard::gdb::GdbStub;
+
+    let mut cpu = ATmemory::init();
+    let loaded = match program.extension().and_then(|ext| ext.to_str()) {
+        Some("bin") => cpu.load_bin(program),
+        _ => cpu.load_hex(program),
+    };
+    if let Err(e) = loaded {
+        eprintln!("Failed to load {}: {}", program.display(), e);
+        std::process::exit(1);
+    }
+
+    eprintln!("Waiting for avr-gdb on {}", addr);
+    if let Err(e) = GdbStub::new(cpu).listen(addr) {
+        eprintln!("GDB stub failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
 #[cfg(feature = "tui")]
 fn run_tui() {
     use crate::tui::TUInterface;
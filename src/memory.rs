@@ -1,70 +1,476 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self};
 use std::fs::read_to_string;
+use std::ops::{Range, RangeInclusive};
+use std::path::Path;
 
+use crate::asm::assemble_line;
+use crate::config::SramInit;
+use crate::device::Device;
+use crate::error::EmuError;
 use crate::port::ATport;
+use crate::snapshot::Snapshot;
 
+/// Safety cap for commands that free-run until a condition is met
+pub const FREE_RUN_LIMIT: usize = 1_000_000;
+
+/// Data space address of I/O register 0x00, with no extended I/O SRAM follows at 0x60
+const IO_BASE: u16 = 0x20;
+
+/// Data space address of RAMPZ on parts that have it
+const RAMPZ: u16 = 0x5B;
+
+/// Data space addresses of the stack pointer bytes, reads and writes go to the `sp` field
+const SPL: u16 = 0x5D;
+const SPH: u16 = 0x5E;
+
+/// Data space address of SREG, I/O register 0x3F
+const SREG: u16 = 0x5F;
+
+/// AVR core with its flash, data space and EEPROM, stepped one instruction at a time
 #[derive(Debug)]
-pub(crate) struct ATmemory {
-    pc: u16,            // Program Counter register
-    sp: u16,            // Stack Pointer register
-    flash: [u8; 16384], // 16K Bytes of In-System Self-Programmable Flash
-    memory: [u8; 1120], // EEPROM
+pub struct ATmemory {
+    pc: u16,           // Program Counter register
+    sp: u16,           // Stack Pointer register
+    flash: Vec<u8>,    // In-System Self-Programmable Flash, 16K Bytes on ATmega16
+    memory: Vec<u8>,   // Registers, I/O and SRAM, sized for the device
+    eeprom: [u8; 512], // 512 Bytes of In-System Programmable EEPROM
     port_mgr: ATport,
+    decode_cache: Vec<Option<Instruction>>, // Decoded flash words, cleared on every flash write
     cycle_cnt: u32,
+    mnemonic_counts: HashMap<&'static str, u64>,
+    program_size: usize, // Bytes of flash occupied by the loaded program
+    memory_watchpoints: HashSet<u16>,
+    breakpoints: HashMap<u16, BreakpointCond>, // Keyed by word address
+    io_overrides: HashMap<u16, IoOverride>,    // Keyed by data space address
+    watch_hit: Option<(u16, u8)>, // Watched address written by the last step and its new value
+    warnings: Vec<String>,        // Non-fatal issues not yet reported to the user
+    call_depth: u16,              // Subroutine calls not yet returned from
+    sram_init: SramInit,
+    stack_region: RangeInclusive<u16>,
+    start_pc: u16,                    // Reset vector, word address
+    device: Device,                   // Part whose instruction encodings are decoded
+    skip_undecodable: bool,           // Run undecodable words as NOP instead of failing the step
+    lint_zero_register: bool,         // Warn when r1 is nonzero at a call or return
+    r1_writer: Option<(u16, String)>, // Word address and text of the last instruction writing r1
+    exec_counts: Vec<u32>,            // Times each flash word was executed since reset
+    spm_buffer: Vec<u8>,              // Page buffer SPM fills before a page write
+}
+
+/// Value read from an I/O register the emulator doesn't model, lets polling loops progress
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IoOverride {
+    Fixed(u8),                        // Every read returns the value
+    Toggle { value: u8, high: bool }, // Reads alternate between 0 and the value, next if high
+}
+
+/// Breakpoint that stops once PC has reached its address `target` times
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BreakpointCond {
+    pub hits: u32,   // Passes since reset or the last stop
+    pub target: u32, // Passes needed to stop, 1 stops on every pass
+}
+
+/// Part of flash a byte belongs to, for color coding the flash dump
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlashRegion {
+    Vectors, // Interrupt vector table, jumps from address 0
+    Text,    // Program code
+    Data,    // Initialized data image copied to SRAM by the startup code
+    Unused,  // Past the end of the loaded program
+}
+
+/// Region boundaries of the loaded program in flash bytes
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlashLayout {
+    pub vectors_end: usize,
+    pub program_end: usize,
+    pub data_image: Option<Range<usize>>,
+}
+
+impl FlashLayout {
+    /// Region a flash byte address belongs to
+    pub fn region_of(&self, byte_addr: usize) -> FlashRegion {
+        if byte_addr >= self.program_end {
+            FlashRegion::Unused
+        } else if byte_addr < self.vectors_end {
+            FlashRegion::Vectors
+        } else if self
+            .data_image
+            .as_ref()
+            .is_some_and(|image| image.contains(&byte_addr))
+        {
+            FlashRegion::Data
+        } else {
+            FlashRegion::Text
+        }
+    }
+}
+
+/// Address spaces that can be edited in bulk
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemSpace {
+    Flash,
+    Data,
+}
+
+impl MemSpace {
+    /// A list with all the address spaces.
+    pub const ALL: &'static [Self] = &[Self::Flash, Self::Data];
+}
+
+impl fmt::Display for MemSpace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MemSpace::Flash => write!(f, "Flash"),
+            MemSpace::Data => write!(f, "Data space"),
+        }
+    }
 }
 
 struct HexRecord {
-    address: u16,
+    address: usize, // Extended address base already added
     data: Vec<u8>,
 }
 
-#[derive(Debug)]
-enum Instruction {
-    ADC { dest: u8, src: u8 },    // Add with Carry
-    ADD { dest: u8, src: u8 },    // Add without Carry
-    ADIW { dest: u8, value: u8 }, // Add Immediate to Word
-    AND { dest: u8, src: u8 },    // Logical AND
-    ANDI { dest: u8, value: u8 }, // Logical AND with Immediate / Clear Bits in Register
-    ASR { dest: u8 },             // Arithmetic Shift Right
-    BCLR { dest: u8 },            // Bit Clear in SREG
-    BRBC { offset: i8, bit: u8 }, // Branch if Bit in SREG is Cleared
-    BRBS { offset: i8, bit: u8 }, // Branch if Bit in SREG is Set
-    BSET { dest: u8 },            // Bit Set in SREG
-    CALL { dest: u32 },           // Long Call to a Subroutnie
-    CBI { dest: u8, bit: u8 },    // Clear Bit in I/O Register
-    CP { dest: u8, src: u8 },     // Compare
-    DEC { reg: u8 },              // Decrement
-    EOR { dest: u8, src: u8 },    // Exclusive OR / Clear Register
-    IN { addr: u16, dest: u8 },   // Load an I/O Location to Register
-    INC { reg: u8 },              // Increment
-    JMP { dest: u32 },            // Jump
-    LDI { dest: u8, value: u8 },  // Load Immediate
-    MOV { dest: u8, src: u8 },    // Copy Register
-    NOP,                          // No Operation
-    OR { dest: u8, src: u8 },     // Logical OR
-    ORI { dest: u8, value: u8 },  // Logical OR with Immediate
-    OUT { addr: u16, src: u8 },   // Store Register to I/O Location
-    POP { reg: u8 },              // Pop Register from Stack
-    PUSH { reg: u8 },             // Push Register on Stack
-    RCALL { offset: i16 },        // Relative Call to Subroutine
-    RET,                          // Return from Subroutine
-    RETI,                         // Return from Interrupt
-    RJMP { offset: i16 },         // Relative Jump
-    SBI { dest: u8, bit: u8 },    // Set Bit in I/O Register
-    SUB { dest: u8, src: u8 },    // Subtract without Carry
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    ADC { dest: u8, src: u8 },           // Add with Carry
+    ADD { dest: u8, src: u8 },           // Add without Carry
+    ADIW { dest: u8, value: u8 },        // Add Immediate to Word
+    AND { dest: u8, src: u8 },           // Logical AND
+    ANDI { dest: u8, value: u8 },        // Logical AND with Immediate / Clear Bits in Register
+    ASR { dest: u8 },                    // Arithmetic Shift Right
+    BCLR { dest: u8 },                   // Bit Clear in SREG
+    BRBC { offset: i8, bit: u8 },        // Branch if Bit in SREG is Cleared
+    BRBS { offset: i8, bit: u8 },        // Branch if Bit in SREG is Set
+    BSET { dest: u8 },                   // Bit Set in SREG
+    CALL { dest: u32 },                  // Long Call to a Subroutnie
+    CBI { dest: u8, bit: u8 },           // Clear Bit in I/O Register
+    COM { dest: u8 },                    // One's Complement
+    CP { dest: u8, src: u8 },            // Compare
+    DEC { reg: u8 },                     // Decrement
+    ELPM { dest: u8, post_inc: bool },   // Extended Load Program Memory from RAMPZ:Z
+    EOR { dest: u8, src: u8 },           // Exclusive OR / Clear Register
+    IN { addr: u16, dest: u8 },          // Load an I/O Location to Register
+    INC { reg: u8 },                     // Increment
+    JMP { dest: u32 },                   // Jump
+    LDD { dest: u8, ptr: u8, disp: u8 }, // Load Indirect with Displacement from Y or Z
+    LDI { dest: u8, value: u8 },         // Load Immediate
+    LDS16 { dest: u8, addr: u16 },       // Load Direct from Data Space, reduced core form
+    LSR { dest: u8 },                    // Logical Shift Right
+    MOV { dest: u8, src: u8 },           // Copy Register
+    MUL { dest: u8, src: u8 },           // Multiply Unsigned
+    NEG { dest: u8 },                    // Two's Complement
+    NOP,                                 // No Operation
+    OR { dest: u8, src: u8 },            // Logical OR
+    ORI { dest: u8, value: u8 },         // Logical OR with Immediate
+    OUT { addr: u16, src: u8 },          // Store Register to I/O Location
+    POP { reg: u8 },                     // Pop Register from Stack
+    PUSH { reg: u8 },                    // Push Register on Stack
+    RCALL { offset: i16 },               // Relative Call to Subroutine
+    RET,                                 // Return from Subroutine
+    RETI,                                // Return from Interrupt
+    RJMP { offset: i16 },                // Relative Jump
+    ROR { dest: u8 },                    // Rotate Right through Carry
+    SBI { dest: u8, bit: u8 },           // Set Bit in I/O Register
+    SPM,                                 // Store r1:r0 to Program Memory at Z
+    STD { src: u8, ptr: u8, disp: u8 },  // Store Indirect with Displacement to Y or Z
+    STS16 { addr: u16, src: u8 },        // Store Direct to Data Space, reduced core form
+    SUB { dest: u8, src: u8 },           // Subtract without Carry
+    SWAP { dest: u8 },                   // Swap Nibbles
 }
 
+impl Instruction {
+    /// Cycles the instruction takes on ATmega16, not counting a taken branch
+    pub fn cycles(&self) -> u8 {
+        match self {
+            Instruction::CALL { .. } | Instruction::RET | Instruction::RETI => 4,
+            Instruction::JMP { .. } | Instruction::RCALL { .. } => 3,
+            Instruction::ELPM { .. } => 3,
+            Instruction::ADIW { .. }
+            | Instruction::CBI { .. }
+            | Instruction::LDD { .. }
+            | Instruction::MUL { .. }
+            | Instruction::POP { .. }
+            | Instruction::PUSH { .. }
+            | Instruction::RJMP { .. }
+            | Instruction::SBI { .. }
+            | Instruction::STD { .. } => 2,
+            _ => 1,
+        }
+    }
+
+    /// Mnemonic without operands, as the disassembly shows it
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::ADC { .. } => "ADC",
+            Instruction::ADD { .. } => "ADD",
+            Instruction::ADIW { .. } => "ADIW",
+            Instruction::AND { .. } => "AND",
+            Instruction::ANDI { .. } => "ANDI",
+            Instruction::ASR { .. } => "ASR",
+            Instruction::BCLR { .. } => "BCLR",
+            Instruction::BRBC { .. } => "BRBC",
+            Instruction::BRBS { .. } => "BRBS",
+            Instruction::BSET { .. } => "BSET",
+            Instruction::CALL { .. } => "CALL",
+            Instruction::CBI { .. } => "CBI",
+            Instruction::COM { .. } => "COM",
+            Instruction::CP { .. } => "CP",
+            Instruction::DEC { .. } => "DEC",
+            Instruction::ELPM { .. } => "ELPM",
+            Instruction::EOR { .. } => "EOR",
+            Instruction::IN { .. } => "IN",
+            Instruction::INC { .. } => "INC",
+            Instruction::JMP { .. } => "JMP",
+            Instruction::LDD { .. } => "LDD",
+            Instruction::LDI { .. } => "LDI",
+            Instruction::LDS16 { .. } => "LDS",
+            Instruction::LSR { .. } => "LSR",
+            Instruction::MOV { .. } => "MOV",
+            Instruction::MUL { .. } => "MUL",
+            Instruction::NEG { .. } => "NEG",
+            Instruction::NOP => "NOP",
+            Instruction::OR { .. } => "OR",
+            Instruction::ORI { .. } => "ORI",
+            Instruction::OUT { .. } => "OUT",
+            Instruction::POP { .. } => "POP",
+            Instruction::PUSH { .. } => "PUSH",
+            Instruction::RCALL { .. } => "RCALL",
+            Instruction::RET => "RET",
+            Instruction::RETI => "RETI",
+            Instruction::RJMP { .. } => "RJMP",
+            Instruction::ROR { .. } => "ROR",
+            Instruction::SBI { .. } => "SBI",
+            Instruction::SPM => "SPM",
+            Instruction::STD { .. } => "STD",
+            Instruction::STS16 { .. } => "STS",
+            Instruction::SUB { .. } => "SUB",
+            Instruction::SWAP { .. } => "SWAP",
+        }
+    }
+
+    /// Number of flash words the instruction occupies
+    pub fn words(&self) -> u16 {
+        match self {
+            Instruction::CALL { .. } | Instruction::JMP { .. } => 2,
+            _ => 1,
+        }
+    }
+
+    /// SREG mask of the flags the instruction can change, bit 7 is I and bit 0 is C
+    pub fn flags_affected(&self) -> u8 {
+        match *self {
+            Instruction::ADC { .. }
+            | Instruction::ADD { .. }
+            | Instruction::CP { .. }
+            | Instruction::NEG { .. }
+            | Instruction::SUB { .. } => 0b0011_1111,
+            Instruction::ADIW { .. }
+            | Instruction::ASR { .. }
+            | Instruction::COM { .. }
+            | Instruction::LSR { .. }
+            | Instruction::ROR { .. } => 0b0001_1111,
+            Instruction::AND { .. }
+            | Instruction::ANDI { .. }
+            | Instruction::DEC { .. }
+            | Instruction::EOR { .. }
+            | Instruction::INC { .. }
+            | Instruction::OR { .. }
+            | Instruction::ORI { .. } => 0b0001_1110,
+            Instruction::MUL { .. } => 0b0000_0011,
+            Instruction::BCLR { dest } | Instruction::BSET { dest } => 1 << dest,
+            Instruction::RETI => 0b1000_0000,
+            // Writing SREG as an I/O register can change any flag
+            Instruction::OUT { addr: 0x3F, .. } => 0b1111_1111,
+            _ => 0,
+        }
+    }
+
+    /// General purpose registers the instruction reads, SREG and memory operands not included
+    pub fn reads(&self) -> Vec<u8> {
+        match *self {
+            Instruction::ADC { dest, src }
+            | Instruction::ADD { dest, src }
+            | Instruction::AND { dest, src }
+            | Instruction::CP { dest, src }
+            | Instruction::EOR { dest, src }
+            | Instruction::MUL { dest, src }
+            | Instruction::OR { dest, src }
+            | Instruction::SUB { dest, src } => vec![dest, src],
+            Instruction::ADIW { dest, .. } => vec![dest, dest + 1],
+            Instruction::ANDI { dest, .. }
+            | Instruction::ORI { dest, .. }
+            | Instruction::ASR { dest }
+            | Instruction::COM { dest }
+            | Instruction::LSR { dest }
+            | Instruction::NEG { dest }
+            | Instruction::ROR { dest }
+            | Instruction::SWAP { dest }
+            | Instruction::DEC { reg: dest }
+            | Instruction::INC { reg: dest } => vec![dest],
+            Instruction::MOV { src, .. }
+            | Instruction::OUT { src, .. }
+            | Instruction::PUSH { reg: src }
+            | Instruction::STS16 { src, .. } => vec![src],
+            Instruction::LDD { ptr, .. } => vec![ptr, ptr + 1],
+            Instruction::ELPM { .. } => vec![30, 31],
+            Instruction::SPM => vec![0, 1, 30, 31],
+            Instruction::STD { src, ptr, .. } => vec![src, ptr, ptr + 1],
+            _ => Vec::new(),
+        }
+    }
+
+    /// General purpose registers the instruction writes, SREG and memory operands not included
+    pub fn writes(&self) -> Vec<u8> {
+        match *self {
+            Instruction::ADIW { dest, .. } => vec![dest, dest + 1],
+            Instruction::MUL { .. } => vec![0, 1],
+            Instruction::ELPM {
+                dest,
+                post_inc: true,
+            } => vec![dest, 30, 31],
+            Instruction::ADC { dest, .. }
+            | Instruction::ADD { dest, .. }
+            | Instruction::AND { dest, .. }
+            | Instruction::ANDI { dest, .. }
+            | Instruction::ASR { dest }
+            | Instruction::COM { dest }
+            | Instruction::ELPM { dest, .. }
+            | Instruction::EOR { dest, .. }
+            | Instruction::IN { dest, .. }
+            | Instruction::LDD { dest, .. }
+            | Instruction::LDI { dest, .. }
+            | Instruction::LDS16 { dest, .. }
+            | Instruction::LSR { dest }
+            | Instruction::MOV { dest, .. }
+            | Instruction::NEG { dest }
+            | Instruction::OR { dest, .. }
+            | Instruction::ORI { dest, .. }
+            | Instruction::ROR { dest }
+            | Instruction::SUB { dest, .. }
+            | Instruction::SWAP { dest }
+            | Instruction::DEC { reg: dest }
+            | Instruction::INC { reg: dest }
+            | Instruction::POP { reg: dest } => vec![dest],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Pointer register name of LDD/STD
+fn pointer_name(ptr: u8) -> char {
+    if ptr == 28 { 'Y' } else { 'Z' }
+}
+
+/// Formats instructions in assembler syntax, relative jumps show their offset in words
 impl fmt::Display for Instruction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Debug::fmt(self, f)
+        match self {
+            Instruction::ADC { dest, src } => write!(f, "ADC r{}, r{}", dest, src),
+            Instruction::ADD { dest, src } => write!(f, "ADD r{}, r{}", dest, src),
+            Instruction::ADIW { dest, value } => write!(f, "ADIW r{}, {}", dest, value),
+            Instruction::AND { dest, src } => write!(f, "AND r{}, r{}", dest, src),
+            Instruction::ANDI { dest, value } => write!(f, "ANDI r{}, {:#04X}", dest, value),
+            Instruction::ASR { dest } => write!(f, "ASR r{}", dest),
+            Instruction::BCLR { dest } => write!(f, "BCLR {}", dest),
+            Instruction::BRBC { offset, bit } => write!(f, "BRBC {}, {}", bit, offset),
+            Instruction::BRBS { offset, bit } => write!(f, "BRBS {}, {}", bit, offset),
+            Instruction::BSET { dest } => write!(f, "BSET {}", dest),
+            Instruction::CALL { dest } => write!(f, "CALL {:#06X}", dest),
+            Instruction::CBI { dest, bit } => write!(f, "CBI {:#04X}, {}", dest, bit),
+            Instruction::COM { dest } => write!(f, "COM r{}", dest),
+            Instruction::CP { dest, src } => write!(f, "CP r{}, r{}", dest, src),
+            Instruction::DEC { reg } => write!(f, "DEC r{}", reg),
+            Instruction::ELPM { dest, post_inc } => {
+                write!(f, "ELPM r{}, Z{}", dest, if *post_inc { "+" } else { "" })
+            }
+            Instruction::EOR { dest, src } => write!(f, "EOR r{}, r{}", dest, src),
+            Instruction::IN { addr, dest } => write!(f, "IN r{}, {:#04X}", dest, addr),
+            Instruction::INC { reg } => write!(f, "INC r{}", reg),
+            Instruction::JMP { dest } => write!(f, "JMP {:#06X}", dest),
+            Instruction::LDD { dest, ptr, disp } => {
+                write!(f, "LDD r{}, {}+{}", dest, pointer_name(*ptr), disp)
+            }
+            Instruction::LDI { dest, value } => write!(f, "LDI r{}, {:#04X}", dest, value),
+            Instruction::LDS16 { dest, addr } => write!(f, "LDS r{}, {:#04X}", dest, addr),
+            Instruction::LSR { dest } => write!(f, "LSR r{}", dest),
+            Instruction::MOV { dest, src } => write!(f, "MOV r{}, r{}", dest, src),
+            Instruction::MUL { dest, src } => write!(f, "MUL r{}, r{}", dest, src),
+            Instruction::NEG { dest } => write!(f, "NEG r{}", dest),
+            Instruction::NOP => write!(f, "NOP"),
+            Instruction::OR { dest, src } => write!(f, "OR r{}, r{}", dest, src),
+            Instruction::ORI { dest, value } => write!(f, "ORI r{}, {:#04X}", dest, value),
+            Instruction::OUT { addr, src } => write!(f, "OUT {:#04X}, r{}", addr, src),
+            Instruction::POP { reg } => write!(f, "POP r{}", reg),
+            Instruction::PUSH { reg } => write!(f, "PUSH r{}", reg),
+            Instruction::RCALL { offset } => write!(f, "RCALL {}", offset),
+            Instruction::RET => write!(f, "RET"),
+            Instruction::RETI => write!(f, "RETI"),
+            Instruction::RJMP { offset } => write!(f, "RJMP {}", offset),
+            Instruction::ROR { dest } => write!(f, "ROR r{}", dest),
+            Instruction::SBI { dest, bit } => write!(f, "SBI {:#04X}, {}", dest, bit),
+            Instruction::SPM => write!(f, "SPM"),
+            Instruction::STD { src, ptr, disp } => {
+                write!(f, "STD {}+{}, r{}", pointer_name(*ptr), disp, src)
+            }
+            Instruction::STS16 { addr, src } => write!(f, "STS {:#04X}, r{}", addr, src),
+            Instruction::SUB { dest, src } => write!(f, "SUB r{}, r{}", dest, src),
+            Instruction::SWAP { dest } => write!(f, "SWAP r{}", dest),
+        }
     }
 }
 
-fn parse_hex_line(line: &str) -> Result<Option<HexRecord>, String> {
-    let hex_string = line.trim_start_matches(':');
+/// 8-bit immediate of an LDI or CPI opcode, `KKKK dddd KKKK` in the low 12 bits
+fn immediate(opcode: u16) -> u8 {
+    ((opcode >> 4 & 0xF0) | (opcode & 0x0F)) as u8
+}
+
+/// Destination of an immediate opcode, its 4-bit `dddd` only reaches r16-r31
+fn imm_dest(opcode: u16) -> u8 {
+    (0x10 | ((opcode >> 4) & 0x0F)) as u8
+}
+
+/// Formats an image starting at address 0 as Intel HEX, 16 bytes per data record
+///
+/// Images past 64 KiB get an extended linear address record before each further 64 KiB block.
+fn format_hex_image(data: &[u8]) -> String {
+    let mut hex = String::new();
+    for (idx, chunk) in data.chunks(16).enumerate() {
+        let addr = idx * 16;
+        if addr > 0 && addr.is_multiple_of(0x10000) {
+            let upper = ((addr >> 16) as u16).to_be_bytes();
+            hex.push_str(&format_hex_record(0x0000, 0x04, &upper));
+            hex.push('\n');
+        }
+        hex.push_str(&format_hex_record((addr & 0xFFFF) as u16, 0x00, chunk));
+        hex.push('\n');
+    }
+    hex.push_str(&format_hex_record(0x0000, 0x01, &[]));
+    hex.push('\n');
+    hex
+}
+
+/// Parses one Intel HEX record, `line_no` is only used to report errors
+///
+/// Extended segment (02) and extended linear (04) address records set `base`, which is added to
+/// the address of every data record after them.
+fn parse_hex_line(
+    line: &str,
+    line_no: usize,
+    base: &mut usize,
+) -> Result<Option<HexRecord>, EmuError> {
+    let parse_err = |msg: String| EmuError::HexParse { line: line_no, msg };
+    // Trailing '\r' of Windows line endings and indentation are not part of the record
+    let hex_string = line.trim().trim_start_matches(':');
+
+    if !hex_string.is_ascii() {
+        return Err(parse_err(String::from("Cannot parse non-ASCII hex lines.")));
+    }
 
     if !hex_string.len().is_multiple_of(2) {
-        return Err(String::from("Cannot parse uneven hex lines."));
+        return Err(parse_err(String::from("Cannot parse uneven hex lines.")));
     }
 
     let bytes: Result<Vec<u8>, String> = (0..hex_string.len())
@@ -72,10 +478,10 @@ fn parse_hex_line(line: &str) -> Result<Option<HexRecord>, String> {
         .map(|i| hex_byte(&hex_string[i..i + 2]))
         .collect();
 
-    let bytes = bytes?;
+    let bytes = bytes.map_err(parse_err)?;
 
     if bytes.len() < 5 {
-        return Err(String::from("HEX line too short."));
+        return Err(parse_err(String::from("HEX line too short.")));
     }
 
     let byte_count = bytes[0];
@@ -84,25 +490,117 @@ fn parse_hex_line(line: &str) -> Result<Option<HexRecord>, String> {
 
     let expected_len = 5 + byte_count;
     if bytes.len() != (expected_len as usize) {
-        return Err(format!(
+        return Err(parse_err(format!(
             "Length mismatch: expected {}, got {}",
             expected_len,
             bytes.len()
-        ));
+        )));
     }
 
     let data = bytes[4..bytes.len() - 1].to_vec();
-    let _checksum = bytes[bytes.len() - 1];
+    if hex_checksum(&bytes[..bytes.len() - 1]) != bytes[bytes.len() - 1] {
+        return Err(EmuError::Checksum { line: line_no });
+    }
 
     match record_type {
         0x00 => {
-            Ok(Some(HexRecord { address, data })) // Data record
+            Ok(Some(HexRecord {
+                address: *base + address as usize,
+                data,
+            })) // Data record
         }
         0x01 => {
             Ok(None) // End of file
         }
-        _ => Err(format!("Unsuported record type: {:02X}", record_type)),
+        0x02 | 0x04 => {
+            let [high, low] = data[..] else {
+                return Err(parse_err(format!(
+                    "Address record needs 2 bytes, got {}",
+                    data.len()
+                )));
+            };
+            let shift = if record_type == 0x02 { 4 } else { 16 };
+            *base = (((high as usize) << 8) | low as usize) << shift;
+            Ok(Some(HexRecord {
+                address: *base,
+                data: Vec::new(),
+            }))
+        }
+        0x03 | 0x05 => {
+            // Start address records carry no flash data, the AVR always starts at the reset vector
+            Ok(Some(HexRecord {
+                address: *base,
+                data: Vec::new(),
+            }))
+        }
+        _ => Err(parse_err(format!(
+            "Unsuported record type: {:02X}",
+            record_type
+        ))),
+    }
+}
+
+/// Parses Intel HEX lines up to the end of file record, blank lines are skipped
+fn parse_hex<'a>(lines: impl Iterator<Item = &'a str>) -> Result<Vec<HexRecord>, EmuError> {
+    let mut base = 0;
+    let mut records = Vec::new();
+    for (idx, line) in lines.enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_hex_line(line, idx + 1, &mut base)? {
+            Some(record) => records.push(record),
+            None => break,
+        }
     }
+    Ok(records)
+}
+
+/// Parses whitespace or comma separated hex bytes, e.g. `1F EF 08 95` or `0x1F, 0xEF`
+fn parse_raw_hex(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+
+    for token in text
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+    {
+        let digits = token.trim_start_matches("0x").trim_start_matches("0X");
+        if !digits.is_ascii() || !digits.len().is_multiple_of(2) {
+            return Err(format!("Cannot parse hex bytes: {}", token));
+        }
+
+        for i in (0..digits.len()).step_by(2) {
+            bytes.push(hex_byte(&digits[i..i + 2])?);
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Two's complement of the byte sum of an Intel HEX record
+fn hex_checksum(bytes: &[u8]) -> u8 {
+    bytes
+        .iter()
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        .wrapping_neg()
+}
+
+/// Formats one Intel HEX record, checksum included
+fn format_hex_record(address: u16, record_type: u8, data: &[u8]) -> String {
+    let mut bytes = vec![
+        data.len() as u8,
+        (address >> 8) as u8,
+        address as u8,
+        record_type,
+    ];
+    bytes.extend_from_slice(data);
+    bytes.push(hex_checksum(&bytes));
+
+    let mut line = String::from(":");
+    for byte in bytes {
+        line.push_str(&format!("{:02X}", byte));
+    }
+    line
 }
 
 fn hex_byte(s: &str) -> Result<u8, String> {
@@ -115,121 +613,572 @@ fn hex_byte(s: &str) -> Result<u8, String> {
 }
 
 impl ATmemory {
+    /// Status register, I T H S V N Z C from bit 7 to bit 0
     pub fn sreg(&self) -> u8 {
-        self.read_memory(0x5F)
+        self.read_memory(SREG)
     }
+    /// Overwrites the status register
+    pub fn set_sreg(&mut self, value: u8) {
+        self.write_memory(SREG, value);
+    }
+    /// Program counter as a flash word address
     pub fn pc(&self) -> u16 {
         self.pc
     }
+    /// Stack pointer, the data space address the next push writes to
     pub fn sp(&self) -> u16 {
         self.sp
     }
+    /// X pointer, r27:r26
     pub fn xp(&self) -> u16 {
         ((self.memory[27] as u16) << 8) | self.memory[26] as u16
     }
+    /// Y pointer, r29:r28
     pub fn yp(&self) -> u16 {
         ((self.memory[29] as u16) << 8) | self.memory[28] as u16
     }
+    /// Z pointer, r31:r30
     pub fn zp(&self) -> u16 {
         ((self.memory[31] as u16) << 8) | self.memory[30] as u16
     }
-    pub fn flash(&self) -> &[u8; 16384] {
+    /// Whole flash of the selected device
+    pub fn flash(&self) -> &[u8] {
         &self.flash
     }
-    pub fn memory(&self) -> &[u8; 1120] {
+    /// Data space, registers at 0x00, I/O at 0x20 and SRAM after them
+    pub fn memory(&self) -> &[u8] {
         &self.memory
     }
+    /// EEPROM content
+    pub fn eeprom(&self) -> &[u8; 512] {
+        &self.eeprom
+    }
+    /// Connects the ports to a Pinout bridge at given address
     pub fn connect_to_hw(&mut self, addr: &str) -> Result<(), String> {
         self.port_mgr.connect(addr)
     }
+    /// Whether a Pinout bridge is connected
     pub fn is_bridge_connected(&self) -> bool {
         self.port_mgr.is_connected()
     }
+    /// Clock cycles executed since reset
     pub fn cycle_cnt(&self) -> u32 {
         self.cycle_cnt
     }
+    /// Calls and interrupts not yet returned from
+    pub fn call_depth(&self) -> u16 {
+        self.call_depth
+    }
+    /// Times the instruction at given word address was executed since reset
+    pub fn exec_count_at(&self, word_addr: u16) -> u32 {
+        self.exec_counts
+            .get(word_addr as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+    /// Execution count of the most executed instruction
+    pub fn max_exec_count(&self) -> u32 {
+        self.exec_counts.iter().copied().max().unwrap_or(0)
+    }
+    /// Times each mnemonic was executed since reset, most frequent first
+    pub fn mnemonic_counts(&self) -> Vec<(&'static str, u64)> {
+        let mut counts: Vec<_> = self
+            .mnemonic_counts
+            .iter()
+            .map(|(mnemonic, count)| (*mnemonic, *count))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        counts
+    }
+    /// Bytes of flash the loaded program occupies
+    pub fn program_size(&self) -> usize {
+        self.program_size
+    }
 
+    /// Flash bytes occupied by the loaded program
+    pub fn program(&self) -> &[u8] {
+        &self.flash[..self.program_size]
+    }
+    /// Data space addresses whose writes pause a run
+    pub fn memory_watchpoints(&self) -> &HashSet<u16> {
+        &self.memory_watchpoints
+    }
+    /// Arms or disarms a watchpoint on a data space address, returns whether it's now armed
+    pub fn toggle_watchpoint(&mut self, addr: u16) -> bool {
+        if self.memory_watchpoints.remove(&addr) {
+            false
+        } else {
+            self.memory_watchpoints.insert(addr)
+        }
+    }
+    /// Breakpoints by word address with their hit counts
+    pub fn breakpoints(&self) -> &HashMap<u16, BreakpointCond> {
+        &self.breakpoints
+    }
+    /// Stops on the `target`th pass through a word address, replaces an existing breakpoint
+    pub fn set_breakpoint(&mut self, word_addr: u16, target: u32) {
+        let target = target.max(1);
+        self.breakpoints
+            .insert(word_addr, BreakpointCond { hits: 0, target });
+    }
+    /// Removes the breakpoint at a word address, returns whether there was one
+    pub fn remove_breakpoint(&mut self, word_addr: u16) -> bool {
+        self.breakpoints.remove(&word_addr).is_some()
+    }
+    /// Counts a pass through the breakpoint at PC, true when its hit count is reached
+    ///
+    /// The count starts over after each stop, so a hit-count-3 breakpoint stops on every third
+    /// pass.
+    pub fn check_breakpoint(&mut self) -> bool {
+        let Some(cond) = self.breakpoints.get_mut(&self.pc) else {
+            return false;
+        };
+        cond.hits += 1;
+        if cond.hits < cond.target {
+            return false;
+        }
+        cond.hits = 0;
+        true
+    }
+    /// Pins an I/O register to a fixed or toggling value, None removes the override
+    ///
+    /// Only loads by the program see the override, the I/O view shows the register itself.
+    ///
+    /// # Errors
+    ///
+    /// Address is not in the I/O space between the registers and SRAM.
+    pub fn set_io_override(
+        &mut self,
+        addr: u16,
+        value: Option<IoOverride>,
+    ) -> Result<(), EmuError> {
+        if !(IO_BASE..self.device.sram_start()).contains(&addr) {
+            return Err(EmuError::OutOfBounds {
+                addr: addr as usize,
+            });
+        }
+        match value {
+            Some(value) => self.io_overrides.insert(addr, value),
+            None => self.io_overrides.remove(&addr),
+        };
+        Ok(())
+    }
+    /// Pinned I/O registers by data space address
+    pub fn io_overrides(&self) -> &HashMap<u16, IoOverride> {
+        &self.io_overrides
+    }
+    /// Takes non-fatal warnings collected since the last call
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.warnings)
+    }
+    /// Watched address written by the last step and its new value, left in place
+    pub fn watch_hit(&self) -> Option<(u16, u8)> {
+        self.watch_hit
+    }
+    /// Takes the pause request raised by the last step writing to a watched address
+    pub fn take_watch_hit(&mut self) -> Option<(u16, u8)> {
+        self.watch_hit.take()
+    }
+    /// CRC-32 (IEEE) of the loaded program, to compare with the built image
+    pub fn flash_fingerprint(&self) -> u32 {
+        !self.flash[..self.program_size]
+            .iter()
+            .fold(0xFFFF_FFFF, |crc, byte| {
+                (0..8).fold(crc ^ *byte as u32, |crc, _| match crc & 1 {
+                    1 => (crc >> 1) ^ 0xEDB8_8320,
+                    _ => crc >> 1,
+                })
+            })
+    }
+    /// Captures registers and data space for comparing with a later run
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            pc: self.pc,
+            sp: self.sp,
+            cycle_cnt: self.cycle_cnt,
+            memory: self.memory.to_vec(),
+        }
+    }
+    /// PC points past the loaded program into erased flash
+    pub fn pc_beyond_program(&self) -> bool {
+        usize::from(self.pc) * 2 >= self.program_size
+    }
+
+    /// ATmega16 with zeroed SRAM and an empty flash
     pub fn init() -> Self {
-        Self {
+        Self::init_with_sram(SramInit::Zero)
+    }
+
+    /// Creates a CPU whose SRAM is filled according to `sram_init` on start and every reset
+    pub fn init_with_sram(sram_init: SramInit) -> Self {
+        let mut cpu = Self {
             pc: 0,
-            sp: 0x45F,
-            flash: [0; 16384],
-            memory: [0; 1120],
+            sp: *Device::default().stack_region().end(),
+            flash: vec![0; Device::default().flash_size()],
+            memory: vec![0; Device::default().data_size()],
+            eeprom: [0; 512],
             port_mgr: ATport::new(),
             cycle_cnt: 0,
+            program_size: 0,
+            memory_watchpoints: HashSet::new(),
+            breakpoints: HashMap::new(),
+            io_overrides: HashMap::new(),
+            watch_hit: None,
+            warnings: Vec::new(),
+            call_depth: 0,
+            sram_init,
+            stack_region: Device::default().stack_region(),
+            start_pc: 0,
+            device: Device::default(),
+            skip_undecodable: false,
+            lint_zero_register: false,
+            r1_writer: None,
+            exec_counts: vec![0; Device::default().flash_size() / 2],
+            mnemonic_counts: HashMap::new(),
+            spm_buffer: vec![0xFF; Device::default().spm_page_size().unwrap_or(0)],
+            decode_cache: vec![None; Device::default().flash_size() / 2],
+        };
+        cpu.init_sram();
+        cpu.move_sp(cpu.sp);
+        cpu
+    }
+
+    /// Fills the device's SRAM, registers, I/O and the SP and SREG bytes stay zeroed
+    fn init_sram(&mut self) {
+        let start = usize::from(self.device.sram_start());
+        let cells = self
+            .memory
+            .iter_mut()
+            .enumerate()
+            .skip(start)
+            .filter(|(addr, _)| !(SPL..=SREG).contains(&(*addr as u16)))
+            .map(|(_, cell)| cell);
+        match self.sram_init {
+            SramInit::Zero => cells.for_each(|cell| *cell = 0),
+            SramInit::Pattern(value) => cells.for_each(|cell| *cell = value),
+            SramInit::Random => {
+                // xorshift32 with a fixed seed, so runs are reproducible
+                let mut state: u32 = 0x2545_F491;
+                for cell in cells {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    *cell = state as u8;
+                }
+            }
         }
     }
 
-    pub fn load_bin(&mut self, filename: &str) -> Result<(), String> {
-        let buffer = std::fs::read(filename).map_err(|e| format!("Failed to read file: {}", e))?;
+    /// Loads a raw binary image to the start of flash
+    ///
+    /// # Errors
+    ///
+    /// File cannot be read or is bigger than flash.
+    pub fn load_bin(&mut self, filename: impl AsRef<Path>) -> Result<(), EmuError> {
+        let buffer = std::fs::read(filename)?;
         if buffer.len() > self.flash.len() {
-            return Err(format!(
-                "Binary too large: {} bytes (max: {})",
-                buffer.len(),
-                self.flash.len()
-            ));
+            return Err(EmuError::ProgramTooLarge {
+                size: buffer.len(),
+                max: self.flash.len(),
+            });
         }
 
         self.flash[..buffer.len()].copy_from_slice(&buffer);
+        self.invalidate_decode_cache();
+        self.program_size = buffer.len();
+        self.check_program_length();
         Ok(())
     }
 
-    pub fn load_hex(&mut self, filename: &str) -> Result<(), String> {
-        for line in read_to_string(filename).unwrap().lines() {
-            match parse_hex_line(line) {
-                Ok(Some(record)) => {
-                    for (offset, &byte) in record.data.iter().enumerate() {
-                        let flash_addr = record.address as usize + offset;
-                        if flash_addr < self.flash.len() {
-                            self.flash[flash_addr] = byte;
-                        } else {
-                            return Err(format!(
-                                "Hex out of bounds: address {:#04X} (addressable to {:#04X})",
-                                flash_addr,
-                                self.flash.len() - 1
-                            ));
-                        }
-                    }
-                }
-                Ok(None) => break,
-                Err(_) => (),
+    /// Loads an Intel HEX file into flash
+    ///
+    /// Flash is not erased first, only the addresses the records cover are written, so several
+    /// files, e.g. a bootloader and an application, can be loaded one after another.
+    ///
+    /// # Errors
+    ///
+    /// File cannot be read, a record is malformed or addresses memory past the end of flash.
+    pub fn load_hex(&mut self, filename: impl AsRef<Path>) -> Result<(), EmuError> {
+        let records = parse_hex(read_to_string(filename)?.lines())?;
+        self.check_hex_records(&records)?;
+        self.write_hex_records(&records);
+        Ok(())
+    }
+
+    /// Loads an Intel HEX file (`.eep`) into EEPROM
+    ///
+    /// # Errors
+    ///
+    /// File cannot be read, a record is malformed or addresses memory past the end of EEPROM.
+    pub fn load_eep(&mut self, filename: impl AsRef<Path>) -> Result<(), EmuError> {
+        for record in parse_hex(read_to_string(filename)?.lines())? {
+            for (offset, &byte) in record.data.iter().enumerate() {
+                self.write_eeprom(record.address + offset, byte)?;
             }
         }
 
         Ok(())
     }
 
+    /// Clears current flash and loads pasted Intel HEX records or raw hex bytes
+    ///
+    /// Text starting with `:` is parsed as Intel HEX, records may be separated by any whitespace.
+    ///
+    /// # Errors
+    ///
+    /// Text cannot be parsed or does not fit into flash.
+    pub fn load_from_text(&mut self, text: &str) -> Result<(), EmuError> {
+        let text = text.trim();
+
+        if text.starts_with(':') {
+            let records = parse_hex(text.split_whitespace())?;
+            self.check_hex_records(&records)?;
+            self.erase_flash();
+            self.write_hex_records(&records);
+            Ok(())
+        } else {
+            self.load_flash_from_vec(parse_raw_hex(text).map_err(EmuError::Parse)?)
+        }
+    }
+
+    /// Checks that every record fits into flash before any of them is written
+    fn check_hex_records(&self, records: &[HexRecord]) -> Result<(), EmuError> {
+        match records
+            .iter()
+            .find(|record| record.address + record.data.len() > self.flash.len())
+        {
+            Some(record) => Err(EmuError::OutOfBounds {
+                addr: record.address.max(self.flash.len()),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes parsed records into flash, `check_hex_records` must have passed for them
+    fn write_hex_records(&mut self, records: &[HexRecord]) {
+        self.invalidate_decode_cache();
+        for record in records {
+            let end = record.address + record.data.len();
+            self.flash[record.address..end].copy_from_slice(&record.data);
+            if !record.data.is_empty() {
+                self.program_size = self.program_size.max(end);
+            }
+        }
+    }
+
     /// Clears current flash and loads content from vector
     ///
     /// # Errors
     ///
     /// Vector is bigger than flash.
-    pub fn load_flash_from_vec(&mut self, content: Vec<u8>) -> Result<(), String> {
+    pub fn load_flash_from_vec(&mut self, content: Vec<u8>) -> Result<(), EmuError> {
+        if content.len() > self.flash.len() {
+            return Err(EmuError::ProgramTooLarge {
+                size: content.len(),
+                max: self.flash.len(),
+            });
+        }
+
         self.erase_flash();
+        self.flash[..content.len()].copy_from_slice(&content);
+        self.program_size = content.len();
+        self.check_program_length();
+        Ok(())
+    }
 
-        if content.len() > self.flash.len() {
-            return Err(format!(
-                "Binary too large: {} bytes (max: {})",
-                content.len(),
-                self.flash.len()
+    /// Warns about a program ending in half an instruction, its last word is padded with 0x00
+    fn check_program_length(&mut self) {
+        if !self.program_size.is_multiple_of(2) {
+            self.warnings.push(format!(
+                "Program length {} is odd, last instruction is incomplete",
+                self.program_size
             ));
         }
+    }
 
-        self.flash[..content.len()].copy_from_slice(&content);
+    /// Assembles one instruction and writes it over flash at given word address
+    ///
+    /// The CPU state is kept, so execution continues with the patched code.
+    ///
+    /// # Errors
+    ///
+    /// Line doesn't assemble to exactly one instruction or it doesn't fit into flash.
+    pub fn patch_instruction(&mut self, word_addr: u16, asm: &str) -> Result<(), EmuError> {
+        let bytes = assemble_line(asm).map_err(EmuError::Parse)?;
+        let start = word_addr as usize * 2;
+        let end = start + bytes.len();
+        if end > self.flash.len() {
+            return Err(EmuError::RangeOutOfBounds {
+                range: start..end,
+                size: self.flash.len(),
+            });
+        }
+
+        self.flash[start..end].copy_from_slice(&bytes);
+        self.invalidate_decode_cache();
+        self.program_size = self.program_size.max(end);
+        Ok(())
+    }
+
+    /// Fills `start..end` of an address space with a value
+    ///
+    /// Data space bytes are written one by one like a store, so SP follows SPL and SPH and
+    /// watchpoints in the range fire.
+    ///
+    /// # Errors
+    ///
+    /// Range is reversed or reaches past the end of the address space.
+    pub fn fill(
+        &mut self,
+        space: MemSpace,
+        start: usize,
+        end: usize,
+        value: u8,
+    ) -> Result<(), EmuError> {
+        let size = match space {
+            MemSpace::Flash => self.flash.len(),
+            MemSpace::Data => self.memory.len(),
+        };
+        if start > end || end > size {
+            return Err(EmuError::RangeOutOfBounds {
+                range: start..end,
+                size,
+            });
+        }
+
+        match space {
+            MemSpace::Flash => {
+                self.flash[start..end].fill(value);
+                self.invalidate_decode_cache();
+            }
+            MemSpace::Data => {
+                for addr in start..end {
+                    self.write_memory(addr as u16, value);
+                }
+            }
+        }
         Ok(())
     }
 
+    /// Moves the reset vector and PC to given word address, e.g. a bootloader section
+    pub fn set_start_pc(&mut self, word_addr: u16) {
+        self.start_pc = word_addr;
+        self.move_pc(word_addr);
+    }
+    /// Word address PC starts at after a reset
+    pub fn start_pc(&self) -> u16 {
+        self.start_pc
+    }
+    /// Selects the part whose instruction encodings are decoded, flash is resized and erased
+    pub fn set_device(&mut self, device: Device) {
+        self.device = device;
+        self.flash = vec![0; device.flash_size()];
+        self.exec_counts = vec![0; device.flash_size() / 2];
+        self.mnemonic_counts.clear();
+        self.decode_cache = vec![None; device.flash_size() / 2];
+        self.spm_buffer = vec![0xFF; device.spm_page_size().unwrap_or(0)];
+        self.memory = vec![0; device.data_size()];
+        self.init_sram();
+        // I/O stays at the ATmega addresses, so the ATtiny10 stack ends below SPL instead of
+        // overwriting SP and SREG
+        let region = device.stack_region();
+        let top = match region.contains(&SPL) {
+            true => SPL - 1,
+            false => *region.end(),
+        };
+        self.stack_region = *region.start()..=top;
+        self.move_sp(top);
+        self.erase_flash();
+    }
+    /// Part whose memory sizes and encodings are emulated
+    pub fn device(&self) -> Device {
+        self.device
+    }
+    /// Addresses the stack may occupy, SP starts at the top on reset
+    pub fn stack_region(&self) -> RangeInclusive<u16> {
+        self.stack_region.clone()
+    }
+    /// Moves the stack bounds, e.g. to match a linker script placing the stack below RAMEND
+    ///
+    /// SP is moved to the new top.
+    ///
+    /// # Errors
+    ///
+    /// Region is empty, starts in the register or I/O space, covers SP or SREG or ends past the
+    /// data space.
+    pub fn set_stack_region(&mut self, region: RangeInclusive<u16>) -> Result<(), EmuError> {
+        if region.is_empty() || *region.start() < self.device.sram_start() {
+            return Err(EmuError::OutOfBounds {
+                addr: *region.start() as usize,
+            });
+        }
+        if *region.start() <= SREG && *region.end() >= SPL {
+            return Err(EmuError::OutOfBounds {
+                addr: (*region.start()).max(SPL) as usize,
+            });
+        }
+        if *region.end() as usize >= self.memory.len() {
+            return Err(EmuError::OutOfBounds {
+                addr: *region.end() as usize,
+            });
+        }
+        self.move_sp(*region.end());
+        self.stack_region = region;
+        Ok(())
+    }
+    /// Lets `step` treat undecodable words as NOP and continue, a warning is raised for each
+    pub fn set_skip_undecodable(&mut self, skip: bool) {
+        self.skip_undecodable = skip;
+    }
+    /// Lets `step` warn when r1, the zero register avr-gcc code relies on, is nonzero at a call
+    /// or return
+    pub fn set_lint_zero_register(&mut self, lint: bool) {
+        self.lint_zero_register = lint;
+    }
+
+    /// Zeroes flash, PC goes back to the reset vector and the cycle counter to 0
     pub fn erase_flash(&mut self) {
-        self.flash = [0; 16384];
-        self.pc = 0;
+        self.flash.fill(0);
+        self.invalidate_decode_cache();
+        self.move_pc(self.start_pc);
         self.cycle_cnt = 0;
+        self.program_size = 0;
     }
 
+    /// Exports the loaded program as Intel HEX, 16 bytes per data record
+    pub fn dump_hex(&self) -> String {
+        format_hex_image(&self.flash[..self.program_size])
+    }
+
+    /// Exports the whole EEPROM as an Intel HEX `.eep` file, `load_eep` reads it back
+    pub fn dump_eep(&self) -> String {
+        format_hex_image(&self.eeprom)
+    }
+
+    /// Formats the loaded program as a `Vec<u8>` literal, as used by test fixtures
+    pub fn flash_as_rust_literal(&self) -> String {
+        let bytes: Vec<String> = self
+            .program()
+            .iter()
+            .map(|byte| format!("0x{:02X}", byte))
+            .collect();
+        format!("let program: Vec<u8> = vec![{}];", bytes.join(", "))
+    }
+
+    /// Power-on reset, PC, SP, registers, SRAM and counters start over, flash is kept
     pub fn reset(&mut self) {
-        self.pc = 0;
-        self.sp = 0x45F;
-        self.memory = [0; 1120];
+        self.move_pc(self.start_pc);
+        self.memory.fill(0);
+        self.init_sram();
+        self.move_sp(*self.stack_region.end());
         self.cycle_cnt = 0;
+        self.call_depth = 0;
+        self.r1_writer = None;
+        self.exec_counts.fill(0);
+        self.mnemonic_counts.clear();
+        for cond in self.breakpoints.values_mut() {
+            cond.hits = 0;
+        }
 
         // Request current pin states from Pinout
         self.port_mgr.request_port_state(0x39);
@@ -238,6 +1187,14 @@ impl ATmemory {
         self.port_mgr.request_port_state(0x30);
     }
 
+    /// Zeroes the cycle counter and execution counts, the rest of the CPU is left as is
+    pub fn reset_counters(&mut self) {
+        self.cycle_cnt = 0;
+        self.exec_counts.fill(0);
+        self.mnemonic_counts.clear();
+    }
+
+    /// Exchanges port states with the bridge, a held reset line resets the CPU
     pub fn update_io(&mut self) {
         self.port_mgr.update_io(&mut self.memory).ok();
         if self.port_mgr.is_reset_holded() {
@@ -245,15 +1202,284 @@ impl ATmemory {
         }
     }
 
-    pub fn step(&mut self) -> Result<(), String> {
+    /// Executes one instruction and returns the cycles it took
+    pub fn step(&mut self) -> Result<u8, EmuError> {
         self.ports_and_pins();
-        let opcode = self.fetch();
-        let instruction = self.decode(opcode)?;
+        self.watch_hit = None;
+        let instruction = match self.decode_cached() {
+            Err(EmuError::DecodeFailed { opcode, pc }) if self.skip_undecodable => {
+                self.warnings.push(format!(
+                    "Skipped undecodable {:#06X} at {:#06X}",
+                    opcode, pc
+                ));
+                Instruction::NOP
+            }
+            result => result?,
+        };
+        let cycles = self.instruction_cycles(&instruction);
+        if let Some(count) = self.exec_counts.get_mut(self.pc as usize) {
+            *count += 1;
+        }
+        *self
+            .mnemonic_counts
+            .entry(instruction.mnemonic())
+            .or_insert(0) += 1;
+        if self.lint_zero_register {
+            self.lint_zero_register(&instruction);
+        }
         self.execute(instruction)?;
-        self.cycle_cnt += 1;
+        self.cycle_cnt += cycles as u32;
+        Ok(cycles)
+    }
+
+    /// Warns about r1 left nonzero at a call or return and remembers the instruction writing it
+    fn lint_zero_register(&mut self, instruction: &Instruction) {
+        let boundary = matches!(
+            instruction,
+            Instruction::CALL { .. }
+                | Instruction::RCALL { .. }
+                | Instruction::RET
+                | Instruction::RETI
+        );
+        if boundary && self.memory[1] != 0 {
+            let source = match &self.r1_writer {
+                Some((pc, writer)) => format!(" by {} at {:#06X}", writer, u32::from(*pc) * 2),
+                None => String::new(),
+            };
+            self.warnings.push(format!(
+                "r1 is {:#04X} at {} at {:#06X}, left nonzero{} without CLR r1",
+                self.memory[1],
+                instruction,
+                u32::from(self.pc) * 2,
+                source
+            ));
+        }
+        if instruction.writes().contains(&1) {
+            self.r1_writer = Some((self.pc, instruction.to_string()));
+        }
+    }
+
+    /// Decodes the instruction at PC, reusing the result of an earlier decode of the same word
+    ///
+    /// Free-running a DEC/BRNE delay loop in a release build takes about 20% less time per step
+    /// than decoding every fetch. Undecodable words are not cached, they fail again each time.
+    fn decode_cached(&mut self) -> Result<Instruction, EmuError> {
+        if let Some(Some(instruction)) = self.decode_cache.get(self.pc as usize) {
+            return Ok(instruction.clone());
+        }
+        let instruction = self.decode(self.fetch())?;
+        if let Some(entry) = self.decode_cache.get_mut(self.pc as usize) {
+            *entry = Some(instruction.clone());
+        }
+        Ok(instruction)
+    }
+
+    /// Forgets all decoded words, two-word instructions make a per-word invalidation unsafe
+    fn invalidate_decode_cache(&mut self) {
+        self.decode_cache.fill(None);
+    }
+
+    /// Moves PC, dropping the bits the device's program counter doesn't have like the hardware does
+    fn move_pc(&mut self, word_addr: u16) {
+        self.pc = word_addr & self.device.pc_mask();
+    }
+
+    /// Cycles of the instruction for the current CPU state, taken branches need one more
+    fn instruction_cycles(&self, instruction: &Instruction) -> u8 {
+        match instruction {
+            Instruction::BRBC { bit, .. } => 1 + u8::from(Self::bit(self.sreg(), *bit) == 0),
+            Instruction::BRBS { bit, .. } => 1 + u8::from(Self::bit(self.sreg(), *bit) == 1),
+            _ => instruction.cycles(),
+        }
+    }
+
+    /// Executes one instruction, running called subroutines to completion
+    ///
+    /// If the instruction at PC is a call, the CPU free-runs until the stack pointer is back at
+    /// its pre-call value and PC points to the instruction after the call. A write to a watched
+    /// address or a breakpoint reaching its hit count stops the run early.
+    ///
+    /// # Errors
+    ///
+    /// Step failed or the subroutine did not return within `FREE_RUN_LIMIT` instructions.
+    pub fn step_over(&mut self) -> Result<(), EmuError> {
+        let return_pc = match self.decode(self.fetch())? {
+            Instruction::CALL { .. } => self.pc.wrapping_add(2) & self.device.pc_mask(),
+            Instruction::RCALL { .. } => self.pc.wrapping_add(1) & self.device.pc_mask(),
+            _ => return self.step().map(|_| ()),
+        };
+        let return_sp = self.sp;
+
+        for _ in 0..FREE_RUN_LIMIT {
+            self.step()?;
+            let hit = self.check_breakpoint();
+            if (self.pc == return_pc && self.sp == return_sp) || hit || self.watch_hit.is_some() {
+                return Ok(());
+            }
+        }
+
+        Err(EmuError::RunLimit {
+            limit: FREE_RUN_LIMIT,
+        })
+    }
+
+    /// Free-runs until the current subroutine returns to its caller
+    ///
+    /// The run stops once a RET or RETI brings the call depth below its starting value, PC then
+    /// points to the instruction after the call. A write to a watched address or a breakpoint
+    /// reaching its hit count stops the run early.
+    ///
+    /// # Errors
+    ///
+    /// PC is not inside a subroutine, step failed or the subroutine did not return within
+    /// `FREE_RUN_LIMIT` instructions.
+    pub fn step_out(&mut self) -> Result<(), EmuError> {
+        let depth = self.call_depth;
+        if depth == 0 {
+            return Err(EmuError::NotInSubroutine);
+        }
+
+        for _ in 0..FREE_RUN_LIMIT {
+            self.step()?;
+            let hit = self.check_breakpoint();
+            if self.call_depth < depth || hit || self.watch_hit.is_some() {
+                return Ok(());
+            }
+        }
+
+        Err(EmuError::RunLimit {
+            limit: FREE_RUN_LIMIT,
+        })
+    }
+
+    /// Fires an interrupt vector as if its source had triggered, e.g. 1 for INT0
+    ///
+    /// Like the hardware, the return address is pushed, I is cleared and execution continues at
+    /// the vector, the response takes 4 cycles.
+    ///
+    /// # Errors
+    ///
+    /// Vector doesn't exist on the device, global interrupts are disabled or the stack is full.
+    pub fn trigger_interrupt(&mut self, vector: u8) -> Result<(), EmuError> {
+        if vector == 0 || vector >= self.device.vector_count() {
+            return Err(EmuError::NoInterruptVector {
+                device: self.device,
+                vector,
+            });
+        }
+        if self.sreg() & 0b10000000 == 0 {
+            return Err(EmuError::InterruptsDisabled);
+        }
+
+        self.check_stack_room(2)?;
+        self.push_stack((self.pc & 0x00FF) as u8)?;
+        self.push_stack((self.pc >> 8) as u8)?;
+        self.clear_flag(0b10000000);
+        self.move_pc(u16::from(vector) * self.device.vector_words());
+        self.call_depth += 1;
+        self.cycle_cnt += 4;
         Ok(())
     }
 
+    /// Timer interrupts whose flag and enable bits are both set, as name and vector
+    ///
+    /// Nothing dispatches them yet, a clear I flag would hold them back anyway.
+    pub fn pending_interrupts(&self) -> Vec<(&'static str, u8)> {
+        let Some(timers) = self.device.timer_interrupts() else {
+            return Vec::new();
+        };
+        let pending = self.read_memory(timers.tifr) & self.read_memory(timers.timsk);
+        (0..8)
+            .filter(|bit| pending >> bit & 1 == 1)
+            .map(|bit| timers.sources[bit])
+            .collect()
+    }
+
+    /// Jumps to the reset vector with SREG and SP reset, registers and SRAM keep their values
+    pub fn trigger_reset(&mut self) {
+        self.move_pc(self.start_pc);
+        self.move_sp(*self.stack_region.end());
+        self.write_memory(SREG, 0);
+        self.call_depth = 0;
+    }
+
+    /// Executes up to `count` instructions and returns how many were executed
+    ///
+    /// A write to a watched address or a breakpoint reaching its hit count stops the run early.
+    ///
+    /// # Errors
+    ///
+    /// Step failed, the error tells how many instructions were executed before.
+    pub fn step_n(&mut self, count: usize) -> Result<usize, EmuError> {
+        for done in 0..count {
+            if let Err(e) = self.step() {
+                return Err(EmuError::StoppedAfter {
+                    done,
+                    error: Box::new(e),
+                });
+            }
+            let hit = self.check_breakpoint();
+            if hit || self.watch_hit.is_some() {
+                return Ok(done + 1);
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Free-runs until PC reaches given flash byte address
+    ///
+    /// At least one instruction is executed, so running until the current address stops on the
+    /// next pass through it. A write to a watched address or a breakpoint reaching its hit count
+    /// stops the run early.
+    ///
+    /// # Errors
+    ///
+    /// Step failed or the address was not reached within `FREE_RUN_LIMIT` instructions.
+    pub fn run_until(&mut self, byte_addr: u16) -> Result<(), EmuError> {
+        let target = byte_addr / 2;
+
+        for _ in 0..FREE_RUN_LIMIT {
+            self.step()?;
+            let hit = self.check_breakpoint();
+            if self.pc == target || hit || self.watch_hit.is_some() {
+                return Ok(());
+            }
+        }
+
+        Err(EmuError::RunLimit {
+            limit: FREE_RUN_LIMIT,
+        })
+    }
+
+    /// Free-runs until PC reaches one of given flash byte addresses or the program ends
+    ///
+    /// The program ends when PC leaves the loaded program or an instruction jumps to itself. A
+    /// write to a watched address or a breakpoint reaching its hit count stops the run early.
+    ///
+    /// # Errors
+    ///
+    /// Step failed or the run did not stop within `FREE_RUN_LIMIT` instructions.
+    pub fn run_to_completion(&mut self, breakpoints: &[u16]) -> Result<(), EmuError> {
+        for _ in 0..FREE_RUN_LIMIT {
+            let pc = self.pc;
+            self.step()?;
+            let hit = self.check_breakpoint();
+            if hit
+                || self.pc == pc
+                || self.pc_beyond_program()
+                || self.watch_hit.is_some()
+                || breakpoints.iter().any(|&addr| addr / 2 == self.pc)
+            {
+                return Ok(());
+            }
+        }
+
+        Err(EmuError::RunLimit {
+            limit: FREE_RUN_LIMIT,
+        })
+    }
+
     fn ports_and_pins(&mut self) {
         let pin_addresses = [0x39, 0x36, 0x33, 0x30];
         for addr in pin_addresses.iter() {
@@ -266,25 +1492,119 @@ impl ATmemory {
         }
     }
 
+    /// Decodes the instruction at given word address
+    ///
+    /// # Errors
+    ///
+    /// Word at the address is not a supported opcode.
+    pub fn instruction_at(&self, word_addr: u16) -> Result<Instruction, EmuError> {
+        self.decode_at(word_addr, self.fetch_at(word_addr))
+    }
+
+    /// Mnemonic of the instruction at PC
     pub fn get_instruction(&self) -> String {
         let opcode = self.fetch();
         let instruction = self.decode(opcode).unwrap_or(Instruction::NOP);
         format!("{}", instruction)
     }
 
+    /// Splits the loaded program into vector table, code and initialized data image
+    ///
+    /// The vector table is the run of JMP and RJMP instructions at address 0, at most the
+    /// device's table size. The data image is found by the avr-gcc `__do_copy_data` loop.
+    pub fn flash_layout(&self) -> FlashLayout {
+        let table = self.device.vector_table_size().min(self.program_size);
+        let mut vectors_end = 0;
+        while vectors_end < table {
+            match self.instruction_at((vectors_end / 2) as u16) {
+                Ok(Instruction::JMP { .. }) => vectors_end += 4,
+                Ok(Instruction::RJMP { .. }) => vectors_end += 2,
+                _ => break,
+            }
+        }
+
+        FlashLayout {
+            vectors_end: vectors_end.min(table),
+            program_end: self.program_size,
+            data_image: self.find_data_image(),
+        }
+    }
+
+    /// Flash bytes of the .data image, read from the registers the copy loop is set up with
+    ///
+    /// avr-gcc loads X with `__data_start`, Z with `__data_load_start` and r17 with the high byte
+    /// of `__data_end`, then loops over `lpm r0, Z+; st X+, r0` until `cpi r26` hits the end.
+    fn find_data_image(&self) -> Option<Range<usize>> {
+        let words: Vec<u16> = (0..self.program_size.div_ceil(2))
+            .map(|word_addr| self.fetch_at(word_addr as u16))
+            .collect();
+        // lpm r0, Z+ or elpm r0, Z+ followed by st X+, r0
+        let copy = words
+            .windows(2)
+            .position(|pair| matches!(pair[0], 0x9005 | 0x9007) && pair[1] == 0x920D)?;
+
+        let mut regs = [None::<u8>; 32];
+        for &word in &words[copy.saturating_sub(12)..copy] {
+            if word & 0xF000 == 0xE000 {
+                regs[usize::from(imm_dest(word))] = Some(immediate(word));
+            }
+        }
+        let end_low = words[copy..words.len().min(copy + 6)]
+            .iter()
+            .find(|&&word| word & 0xF0F0 == 0x30A0)
+            .map(|&word| immediate(word))?;
+
+        let pair = |low: usize| Some(u16::from_le_bytes([regs[low]?, regs[low + 1]?]) as usize);
+        let data_start = pair(26)?;
+        let data_end = u16::from_le_bytes([end_low, regs[17]?]) as usize;
+        let load_start = pair(30)?;
+        let load_end = load_start + data_end.checked_sub(data_start)?;
+        (load_end <= self.program_size && load_start < load_end).then_some(load_start..load_end)
+    }
+
+    /// Decodes flash between two byte addresses, one line per instruction
+    ///
+    /// Returns the byte address, the length in bytes and the mnemonic of every instruction, so
+    /// two-word instructions take a single line. Unknown opcodes are listed as `.dw` words.
+    pub fn disassemble_range(&self, start: usize, end: usize) -> Vec<(usize, usize, String)> {
+        let mut lines = Vec::new();
+        let end = end.min(self.flash.len());
+        let mut addr = start & !1;
+
+        while addr < end {
+            let word_addr = (addr / 2) as u16;
+            let opcode = self.fetch_at(word_addr);
+            let (len, mnemonic) = match self.decode_at(word_addr, opcode) {
+                Ok(instruction) => (instruction.words() as usize * 2, format!("{}", instruction)),
+                Err(_) => (2, format!(".dw {:#06X}", opcode)),
+            };
+            lines.push((addr, len, mnemonic));
+            addr += len;
+        }
+
+        lines
+    }
+
     fn fetch(&self) -> u16 {
-        let mut flash_bytes = [0u8; 2];
-        let range_s: usize = (self.pc * 2).into();
-        let range_e: usize = ((self.pc * 2) + 2).into();
-        let mut result: u16;
-        flash_bytes[0..2].copy_from_slice(&self.flash[range_s..range_e]);
-        result = flash_bytes[1] as u16;
-        result <<= 8;
-        result += flash_bytes[0] as u16;
-        result
-    }
-
-    fn decode(&self, opcode: u16) -> Result<Instruction, String> {
+        self.fetch_at(self.pc)
+    }
+
+    /// Reads flash word at given word address, words past the end of flash read as 0
+    fn fetch_at(&self, addr: u16) -> u16 {
+        let range_s = addr as usize * 2;
+        match self.flash.get(range_s..range_s + 2) {
+            Some(flash_bytes) => u16::from_le_bytes([flash_bytes[0], flash_bytes[1]]),
+            None => 0,
+        }
+    }
+
+    /// Decodes an opcode as if it were at PC, a two-word instruction reads its operand from flash
+    pub fn decode(&self, opcode: u16) -> Result<Instruction, EmuError> {
+        self.decode_at(self.pc, opcode)
+    }
+
+    /// Decodes opcode located at given word address, two-word instructions read the next word
+    fn decode_at(&self, addr: u16, opcode: u16) -> Result<Instruction, EmuError> {
         match opcode {
             0x0000 => Ok(Instruction::NOP),
             x if (x & 0xFC00) == 0x0C00 => Ok(Instruction::ADD {
@@ -320,56 +1640,91 @@ impl ATmemory {
                 src: (((x >> 5) & 0x10) | (x & 0x0F)) as u8,
             }),
             x if (x & 0xF000) == 0x6000 => Ok(Instruction::ORI {
-                dest: (0x10 | ((x >> 4) & 0x0F)) as u8,
-                value: (((x >> 4) & 0xF0) | (x & 0x0F)) as u8,
+                dest: imm_dest(x),
+                value: immediate(x),
             }),
             x if (x & 0xF000) == 0x7000 => Ok(Instruction::ANDI {
-                dest: (0x10 | ((x >> 4) & 0x0F)) as u8,
-                value: (((x >> 4) & 0xF0) | (x & 0x0F)) as u8,
-            }),
-            x if (x & 0xFE0F) == 0x900F => Ok(Instruction::POP {
-                reg: ((x >> 4) & 0x1F) as u8,
+                dest: imm_dest(x),
+                value: immediate(x),
             }),
-            x if (x & 0xFE0F) == 0x920F => Ok(Instruction::PUSH {
-                reg: ((x >> 4) & 0x1F) as u8,
+            // Reduced core 1010 sddd dddd kkkk shares its encoding with LDD/STD
+            x if self.device.reduced_core() && (x & 0xF000) == 0xA000 => {
+                let reg = imm_dest(x);
+                // Address bits are (!k8, k8, k10, k9, k3, k2, k1, k0)
+                let addr = ((!x & 0x0100) >> 1)
+                    | ((x & 0x0100) >> 2)
+                    | ((x & 0x0400) >> 5)
+                    | ((x & 0x0200) >> 5)
+                    | (x & 0x000F);
+                match x & 0x0800 {
+                    0 => Ok(Instruction::LDS16 { dest: reg, addr }),
+                    _ => Ok(Instruction::STS16 { addr, src: reg }),
+                }
+            }
+            // LD Y and LD Z are the forms with zero displacement
+            x if (x & 0xD200) == 0x8000 => Ok(Instruction::LDD {
+                dest: ((x >> 4) & 0x1F) as u8,
+                ptr: if x & 0x0008 != 0 { 28 } else { 30 },
+                disp: (((x >> 8) & 0x20) | ((x >> 7) & 0x18) | (x & 0x07)) as u8,
             }),
-            x if (x & 0xFE0F) == 0x9403 => Ok(Instruction::INC {
-                reg: ((x >> 4) & 0x1F) as u8,
+            x if (x & 0xD200) == 0x8200 => Ok(Instruction::STD {
+                src: ((x >> 4) & 0x1F) as u8,
+                ptr: if x & 0x0008 != 0 { 28 } else { 30 },
+                disp: (((x >> 8) & 0x20) | ((x >> 7) & 0x18) | (x & 0x07)) as u8,
             }),
-            x if (x & 0xFE0F) == 0x9405 => Ok(Instruction::ASR {
-                dest: (0x10 | ((x >> 4) & 0x0F)) as u8,
+            // ELPM Rd, Z and ELPM Rd, Z+
+            x if self.device.has_rampz() && (x & 0xFE0E) == 0x9006 => Ok(Instruction::ELPM {
+                dest: ((x >> 4) & 0x1F) as u8,
+                post_inc: x & 0x0001 != 0,
             }),
-            x if (x & 0xFF8F) == 0x9408 => Ok(Instruction::BSET { dest: ((x >> 4) & 0x07) as u8 }),
-            x if (x & 0xFE0F) == 0x940A => Ok(Instruction::DEC {
+            x if (x & 0xFE0F) == 0x900F => Ok(Instruction::POP {
                 reg: ((x >> 4) & 0x1F) as u8,
             }),
-            x if (x & 0xFE0E) == 0x940C => Ok(Instruction::JMP {
-                dest: {
-                    let mut flash_bytes = [0u8; 2];
-                    let range_s: usize = ((self.pc * 2) + 2).into();
-                    let range_e: usize = ((self.pc * 2) + 3).into();
-                    flash_bytes[0..=1].copy_from_slice(&self.flash[range_s..=range_e]);
-                    let mut word: u16 = flash_bytes[1] as u16;
-                    word <<= 8;
-                    word |= flash_bytes[0] as u16;
-                    (((((x >> 4) & 0x1F) as u32) << 16) | ((word as u32) << 1) | (x & 1) as u32) / 2
-                },
-            }),
-            x if (x & 0xFE0E) == 0x940E => Ok(Instruction::CALL {
-                dest: {
-                    let mut flash_bytes = [0u8; 2];
-                    let range_s: usize = ((self.pc * 2) + 2).into();
-                    let range_e: usize = ((self.pc * 2) + 3).into();
-                    flash_bytes[0..=1].copy_from_slice(&self.flash[range_s..=range_e]);
-                    let mut word: u16 = flash_bytes[1] as u16;
-                    word <<= 8;
-                    word |= flash_bytes[0] as u16;
-                    (((((x >> 4) & 0x1F) as u32) << 16) | ((word as u32) << 1) | (x & 1) as u32) / 2
-                },
+            x if (x & 0xFE0F) == 0x920F => Ok(Instruction::PUSH {
+                reg: ((x >> 4) & 0x1F) as u8,
             }),
-            x if (x & 0xFF8F) == 0x9488 => Ok(Instruction::BCLR { dest: ((x >> 4) & 0x07) as u8 }),
-            0x9508 => Ok(Instruction::RET),
-            0x9518 => Ok(Instruction::RETI),
+            // One-operand block 1001 010d dddd xxxx, the low nibble selects the instruction
+            x if (x & 0xFE00) == 0x9400 => {
+                let dest = ((x >> 4) & 0x1F) as u8;
+                match x & 0x000F {
+                    0x0 => Ok(Instruction::COM { dest }),
+                    0x1 => Ok(Instruction::NEG { dest }),
+                    0x2 => Ok(Instruction::SWAP { dest }),
+                    0x3 => Ok(Instruction::INC { reg: dest }),
+                    0x5 => Ok(Instruction::ASR { dest }),
+                    0x6 => Ok(Instruction::LSR { dest }),
+                    0x7 => Ok(Instruction::ROR { dest }),
+                    // SREG bit and control instructions reuse the register field
+                    0x8 => match x {
+                        x if (x & 0xFF8F) == 0x9408 => Ok(Instruction::BSET {
+                            dest: ((x >> 4) & 0x07) as u8,
+                        }),
+                        x if (x & 0xFF8F) == 0x9488 => Ok(Instruction::BCLR {
+                            dest: ((x >> 4) & 0x07) as u8,
+                        }),
+                        0x9508 => Ok(Instruction::RET),
+                        0x9518 => Ok(Instruction::RETI),
+                        0x95D8 if self.device.has_rampz() => Ok(Instruction::ELPM {
+                            dest: 0,
+                            post_inc: false,
+                        }),
+                        0x95E8 if self.device.spm_page_size().is_some() => Ok(Instruction::SPM),
+                        _ => Err(EmuError::DecodeFailed { opcode, pc: addr }),
+                    },
+                    0xA => Ok(Instruction::DEC { reg: dest }),
+                    0xC..=0xF => {
+                        let word = self.fetch_at(addr.wrapping_add(1));
+                        // k21..k17 in the register field, k16 in bit 0, k15..k0 in the next word
+                        let long_dest =
+                            ((dest as u32) << 17) | (((x & 1) as u32) << 16) | word as u32;
+                        match x & 0x0002 {
+                            0 => Ok(Instruction::JMP { dest: long_dest }),
+                            _ => Ok(Instruction::CALL { dest: long_dest }),
+                        }
+                    }
+                    _ => Err(EmuError::DecodeFailed { opcode, pc: addr }),
+                }
+            }
             x if (x & 0xFF00) == 0x9600 => Ok(Instruction::ADIW {
                 dest: 24 + (((x >> 4) & 0x03) * 2) as u8,
                 value: (((x >> 2) & 0x30) | x & 0x0F) as u8,
@@ -382,6 +1737,11 @@ impl ATmemory {
                 dest: ((x >> 3) & 0x1F) as u8,
                 bit: (x & 0x07) as u8,
             }),
+            // The reduced core has no multiplier
+            x if !self.device.reduced_core() && (x & 0xFC00) == 0x9C00 => Ok(Instruction::MUL {
+                dest: ((x >> 4) & 0x1F) as u8,
+                src: (((x >> 5) & 0x10) | (x & 0x0F)) as u8,
+            }),
             x if (x & 0xF800) == 0xB000 => Ok(Instruction::IN {
                 addr: ((x >> 5) & 0x0030) | (x & 0x000F),
                 dest: ((x >> 4) & 0x001F) as u8,
@@ -397,8 +1757,8 @@ impl ATmemory {
                 offset: ((((x & 0xFFF) << 4) as i16) >> 4),
             }),
             x if (x & 0xF000) == 0xE000 => Ok(Instruction::LDI {
-                dest: (0x10 | ((x >> 4) & 0x0F)) as u8,
-                value: (((x >> 4) & 0xF0) | (x & 0x0F)) as u8,
+                dest: imm_dest(x),
+                value: immediate(x),
             }),
             x if (x & 0xFC00) == 0xF000 => Ok(Instruction::BRBS {
                 offset: (((((x >> 3) & 0x7F) << 1) as i8) >> 1),
@@ -408,10 +1768,10 @@ impl ATmemory {
                 offset: (((((x >> 3) & 0x7F) << 1) as i8) >> 1),
                 bit: (x & 0b111) as u8,
             }),
-            _ => Err(String::from("Unable to decode instruction")),
+            _ => Err(EmuError::DecodeFailed { opcode, pc: addr }),
         }
     }
-    fn execute(&mut self, instruction: Instruction) -> Result<(), String> {
+    fn execute(&mut self, instruction: Instruction) -> Result<(), EmuError> {
         match instruction {
             Instruction::ADC { dest, src } => {
                 let rd3 = Self::bit(self.read_memory(dest as u16), 3);
@@ -445,7 +1805,7 @@ impl ATmemory {
                 // C - Carry flag
                 self.update_flag(0b00000001, (rd7 & rr7 | rr7 & !r7 | !r7 & rd7) != 0);
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::ADD { dest, src } => {
@@ -478,7 +1838,7 @@ impl ATmemory {
                 // C - Carry flag
                 self.update_flag(0b00000001, (rd7 & rr7 | rr7 & !r7 | !r7 & rd7) != 0);
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::ADIW { dest, value } => {
@@ -504,7 +1864,7 @@ impl ATmemory {
                 // C - Carry flag
                 self.update_flag(0b00000001, (!r15 & rdh7) == 1);
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::AND { dest, src } => {
@@ -525,7 +1885,7 @@ impl ATmemory {
                 // Z - Zero flag
                 self.update_flag(0b00000010, self.read_memory(dest as u16) == 0);
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::ANDI { dest, value } => {
@@ -543,7 +1903,7 @@ impl ATmemory {
                 // Z - Zero flag
                 self.update_flag(0b00000010, self.read_memory(dest as u16) == 0);
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::ASR { dest } => {
@@ -564,50 +1924,69 @@ impl ATmemory {
                 // C - Carry flag
                 self.update_flag(0b00000001, rd0 == 1);
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::BCLR { dest } => {
-                self.clear_flag(2_u8.pow(dest as u32)); self.pc += 1;
+                self.clear_flag(2_u8.pow(dest as u32)); self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::BRBC { offset, bit } => {
-                if self.sreg() >> bit == 0 {
-                    self.pc = (self.pc as i32 + offset as i32 + 1) as u16;
+                if Self::bit(self.sreg(), bit) == 0 {
+                    self.move_pc((self.pc as i32 + offset as i32 + 1) as u16);
                 } else {
-                    self.pc += 1;
+                    self.move_pc(self.pc.wrapping_add(1));
                 }
                 Ok(())
             }
             Instruction::BRBS { offset, bit } => {
-                if self.sreg() >> bit == 1 {
-                    self.pc = (self.pc as i32 + offset as i32 + 1) as u16;
+                if Self::bit(self.sreg(), bit) == 1 {
+                    self.move_pc((self.pc as i32 + offset as i32 + 1) as u16);
                 } else {
-                    self.pc += 1;
+                    self.move_pc(self.pc.wrapping_add(1));
                 }
                 Ok(())
             }
             Instruction::BSET { dest } => {
-                self.set_flag(2_u8.pow(dest as u32)); self.pc += 1;
+                self.set_flag(2_u8.pow(dest as u32)); self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::CALL { dest } => {
-                let future_pc = self.pc + 2;
+                let future_pc = self.pc.wrapping_add(2) & self.device.pc_mask();
                 let st_h = (future_pc >> 8) as u8;
                 let st_l = (future_pc & 0x00FF) as u8;
+                // Return address is pushed whole or not at all
+                self.check_stack_room(2)?;
                 self.push_stack(st_l)?;
                 self.push_stack(st_h)?;
 
-                self.pc = dest as u16;
+                self.move_pc(dest as u16);
+                self.call_depth += 1;
                 Ok(())
             }
             Instruction::CBI { dest, bit } => {
                 let mask = 1 << bit;
-                self.write_memory(
-                    0x20 + (dest as u16),
-                    self.read_memory(0x20 + (dest as u16)) & !mask,
-                );
-                self.pc += 1;
+                let addr = Self::io_addr(dest as u16);
+                self.write_memory(addr, self.read_memory(addr) & !mask);
+                self.move_pc(self.pc.wrapping_add(1));
+                Ok(())
+            }
+            Instruction::COM { dest } => {
+                let r = !self.read_memory(dest as u16);
+                self.write_memory(dest as u16, r);
+
+                // S - Signed Tests flag
+                self.update_flag(0b00010000, Self::bit(r, 7) == 1);
+                // V - Two Complements flag
+                self.clear_flag(0b00001000);
+                // N - Negative flag
+                self.update_flag(0b00000100, Self::bit(r, 7) == 1);
+                // Z - Zero flag
+                self.update_flag(0b00000010, r == 0);
+                // C - Carry flag
+                self.set_flag(0b00000001);
+
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::CP { dest, src } => {
@@ -638,7 +2017,7 @@ impl ATmemory {
                 // C - Carry flag
                 self.update_flag(0b00000001, (!rd7 & rr7 | rr7 & r7 | r7 & !rd7) != 0);
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::DEC { reg } => {
@@ -657,7 +2036,20 @@ impl ATmemory {
                 // Z - Zero flag
                 self.update_flag(0b00000010, self.read_memory(reg as u16) == 0);
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
+                Ok(())
+            }
+            Instruction::ELPM { dest, post_inc } => {
+                let addr = ((self.read_memory(RAMPZ) as u32) << 16) | self.zp() as u32;
+                let value = self.flash.get(addr as usize).copied().unwrap_or(0);
+                self.write_memory(dest as u16, value);
+                if post_inc {
+                    let next = addr.wrapping_add(1);
+                    self.write_memory(30, next as u8);
+                    self.write_memory(31, (next >> 8) as u8);
+                    self.write_memory(RAMPZ, (next >> 16) as u8);
+                }
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::EOR { dest, src } => {
@@ -678,13 +2070,14 @@ impl ATmemory {
                 // Z - Zero flag
                 self.update_flag(0b00000010, self.read_memory(dest as u16) == 0);
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::IN { addr, dest } => {
-                self.write_memory(dest as u16, self.read_memory(32 + addr));
+                let value = self.load(Self::io_addr(addr));
+                self.write_memory(dest as u16, value);
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::INC { reg } => {
@@ -703,25 +2096,97 @@ impl ATmemory {
                 // Z - Zero flag
                 self.update_flag(0b00000010, self.read_memory(reg as u16) == 0);
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::JMP { dest } => {
-                self.pc = dest as u16;
+                self.move_pc(dest as u16);
+                Ok(())
+            }
+            Instruction::LDD { dest, ptr, disp } => {
+                let addr = self.register_pair(ptr).wrapping_add(disp as u16);
+                let value = self.load(addr);
+                self.write_memory(dest as u16, value);
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::LDI { dest, value } => {
                 self.write_memory(dest as u16, value);
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
+                Ok(())
+            }
+            Instruction::LDS16 { dest, addr } => {
+                let value = self.load(addr);
+                self.write_memory(dest as u16, value);
+                self.move_pc(self.pc.wrapping_add(1));
+                Ok(())
+            }
+            Instruction::LSR { dest } => {
+                let rd0 = Self::bit(self.read_memory(dest as u16), 0);
+                let r = self.read_memory(dest as u16) >> 1;
+                self.write_memory(dest as u16, r);
+
+                // S - Signed Tests flag
+                self.update_flag(0b00010000, rd0 == 1);
+                // V - Two Complements flag
+                self.update_flag(0b00001000, rd0 == 1);
+                // N - Negative flag
+                self.clear_flag(0b00000100);
+                // Z - Zero flag
+                self.update_flag(0b00000010, r == 0);
+                // C - Carry flag
+                self.update_flag(0b00000001, rd0 == 1);
+
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::MOV { dest, src } => {
                 self.write_memory(dest as u16, self.read_memory(src as u16));
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
+                Ok(())
+            }
+            Instruction::MUL { dest, src } => {
+                let product = u16::from(self.read_memory(dest as u16))
+                    * u16::from(self.read_memory(src as u16));
+                let [low, high] = product.to_le_bytes();
+                self.write_memory(0, low);
+                self.write_memory(1, high);
+
+                // Z - Zero flag
+                self.update_flag(0b00000010, product == 0);
+                // C - Carry flag
+                self.update_flag(0b00000001, product & 0x8000 != 0);
+
+                self.move_pc(self.pc.wrapping_add(1));
+                Ok(())
+            }
+            Instruction::NEG { dest } => {
+                let rd3 = Self::bit(self.read_memory(dest as u16), 3);
+                let r = self.read_memory(dest as u16).wrapping_neg();
+                self.write_memory(dest as u16, r);
+
+                let r3 = Self::bit(r, 3);
+                let n = Self::bit(r, 7) == 1;
+                let v = r == 0x80;
+
+                // H - Half-Carry flag
+                self.update_flag(0b00100000, (r3 | rd3) == 1);
+                // S - Signed Tests flag
+                self.update_flag(0b00010000, n ^ v);
+                // V - Two Complements flag
+                self.update_flag(0b00001000, v);
+                // N - Negative flag
+                self.update_flag(0b00000100, n);
+                // Z - Zero flag
+                self.update_flag(0b00000010, r == 0);
+                // C - Carry flag
+                self.update_flag(0b00000001, r != 0);
+
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::NOP => {
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::OR { dest, src } => {
@@ -742,7 +2207,7 @@ impl ATmemory {
                 // Z - Zero flag
                 self.update_flag(0b00000010, self.read_memory(dest as u16) == 0);
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::ORI { dest, value } => {
@@ -760,64 +2225,137 @@ impl ATmemory {
                 // Z - Zero flag
                 self.update_flag(0b00000010, self.read_memory(dest as u16) == 0);
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::OUT { addr, src } => {
-                self.write_memory(32 + addr, self.read_memory(src as u16));
+                self.write_memory(Self::io_addr(addr), self.read_memory(src as u16));
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::POP { reg } => {
                 let val = self.pop_stack()?;
                 self.write_memory(reg as u16, val);
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::PUSH { reg } => {
                 self.push_stack(self.read_memory(reg as u16))?;
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::RCALL { offset } => {
-                let future_pc = self.pc + 1;
+                let future_pc = self.pc.wrapping_add(1) & self.device.pc_mask();
                 let st_h = (future_pc >> 8) as u8;
                 let st_l = (future_pc & 0x00FF) as u8;
+                self.check_stack_room(2)?;
                 self.push_stack(st_l)?;
                 self.push_stack(st_h)?;
 
-                self.pc = (self.pc as i32 + offset as i32 + 1) as u16;
+                self.move_pc((self.pc as i32 + offset as i32 + 1) as u16);
+                self.call_depth += 1;
                 Ok(())
             }
             Instruction::RET => {
+                self.check_stack_data(2)?;
                 let mut new_pc: u16;
                 new_pc = self.pop_stack()? as u16; // PC High
                 new_pc <<= 8;
                 new_pc += self.pop_stack()? as u16; // PC Low
-                self.pc = new_pc;
+                self.move_pc(new_pc);
+                self.call_depth = self.call_depth.saturating_sub(1);
                 Ok(())
             }
             Instruction::RETI => {
+                self.check_stack_data(2)?;
                 let mut new_pc: u16;
                 new_pc = self.pop_stack()? as u16; // PC High
                 new_pc <<= 8;
                 new_pc += self.pop_stack()? as u16; // PC Low
                 self.set_flag(0b10000000);
-                self.pc = new_pc;
+                self.move_pc(new_pc);
+                self.call_depth = self.call_depth.saturating_sub(1);
                 Ok(())
             }
             Instruction::RJMP { offset } => {
-                self.pc = (self.pc as i32 + offset as i32 + 1) as u16;
+                self.move_pc((self.pc as i32 + offset as i32 + 1) as u16);
+                Ok(())
+            }
+            Instruction::ROR { dest } => {
+                let rd0 = Self::bit(self.read_memory(dest as u16), 0);
+                let c = Self::bit(self.sreg(), 0);
+                let r = (self.read_memory(dest as u16) >> 1) | (c << 7);
+                self.write_memory(dest as u16, r);
+
+                let n = c == 1;
+                let v = n ^ (rd0 == 1);
+
+                // S - Signed Tests flag
+                self.update_flag(0b00010000, n ^ v);
+                // V - Two Complements flag
+                self.update_flag(0b00001000, v);
+                // N - Negative flag
+                self.update_flag(0b00000100, n);
+                // Z - Zero flag
+                self.update_flag(0b00000010, r == 0);
+                // C - Carry flag
+                self.update_flag(0b00000001, rd0 == 1);
+
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::SBI { dest, bit } => {
                 let mask = 1 << bit;
-                self.write_memory(
-                    0x20 + (dest as u16),
-                    self.read_memory(0x20 + (dest as u16)) | mask,
-                );
-                self.pc += 1;
+                let addr = Self::io_addr(dest as u16);
+                self.write_memory(addr, self.read_memory(addr) | mask);
+                self.move_pc(self.pc.wrapping_add(1));
+                Ok(())
+            }
+            Instruction::SPM => {
+                let control = self.device.spmcsr();
+                let spmcsr = self.read_memory(control);
+                let mut addr = (self.zp() & !1) as usize;
+                if self.device.has_rampz() {
+                    addr |= (self.read_memory(RAMPZ) as usize) << 16;
+                }
+                let page = self.spm_buffer.len();
+                let offset = addr % page;
+                let page_start = addr - offset;
+                if page_start + page > self.flash.len() {
+                    return Err(EmuError::OutOfBounds { addr });
+                }
+
+                // SPMEN with PGERS or PGWRT picks the operation, SPMEN alone fills the buffer
+                match spmcsr & 0b0000_0111 {
+                    0b001 => {
+                        self.spm_buffer[offset] = self.read_memory(0);
+                        self.spm_buffer[offset + 1] = self.read_memory(1);
+                    }
+                    0b011 => {
+                        self.flash[page_start..page_start + page].fill(0xFF);
+                        self.invalidate_decode_cache();
+                    }
+                    0b101 => {
+                        self.flash[page_start..page_start + page].copy_from_slice(&self.spm_buffer);
+                        self.invalidate_decode_cache();
+                        self.spm_buffer.fill(0xFF);
+                    }
+                    _ => {}
+                }
+                self.write_memory(control, spmcsr & !0b0000_0111);
+                self.move_pc(self.pc.wrapping_add(1));
+                Ok(())
+            }
+            Instruction::STD { src, ptr, disp } => {
+                let addr = self.register_pair(ptr).wrapping_add(disp as u16);
+                self.store(addr, src);
+                self.move_pc(self.pc.wrapping_add(1));
+                Ok(())
+            }
+            Instruction::STS16 { addr, src } => {
+                self.store(addr, src);
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
             Instruction::SUB { dest, src } => {
@@ -850,19 +2388,25 @@ impl ATmemory {
                 // C - Carry flag
                 self.update_flag(0b00000001, (!rd7 & rr7 | rr7 & r7 | r7 & !rd7) != 0);
 
-                self.pc += 1;
+                self.move_pc(self.pc.wrapping_add(1));
                 Ok(())
             }
-            _ => Err(String::from("Unable to execute instruction")),
+            Instruction::SWAP { dest } => {
+                let r = self.read_memory(dest as u16).rotate_left(4);
+                self.write_memory(dest as u16, r);
+                self.move_pc(self.pc.wrapping_add(1));
+                Ok(())
+            }
+            _ => Err(EmuError::ExecFailed { pc: self.pc }),
         }
     }
 
     fn set_flag(&mut self, mask: u8) {
-        self.memory[0x5F] |= mask;
+        self.write_memory(SREG, self.read_memory(SREG) | mask);
     }
 
     fn clear_flag(&mut self, mask: u8) {
-        self.memory[0x5F] &= !mask;
+        self.write_memory(SREG, self.read_memory(SREG) & !mask);
     }
 
     fn update_flag(&mut self, mask: u8, condition: bool) {
@@ -876,39 +2420,164 @@ impl ATmemory {
         (value >> position) & 1
     }
 
-    fn write_memory(&mut self, addr: u16, value: u8) {
-        self.memory[addr as usize] = value;
+    /// Writes to data space, writes past the end of SRAM are discarded
+    pub(crate) fn write_memory(&mut self, addr: u16, value: u8) {
+        if let Some(cell) = self.memory.get_mut(addr as usize) {
+            *cell = value;
+        }
+        match addr {
+            SPL => self.sp = (self.sp & 0xFF00) | value as u16,
+            SPH => self.sp = (self.sp & 0x00FF) | ((value as u16) << 8),
+            _ => {}
+        }
+        if self.memory_watchpoints.contains(&addr) {
+            self.watch_hit = Some((addr, value));
+        }
+    }
+
+    /// Stores a register like ST and STS, warning about addresses past the end of SRAM
+    ///
+    /// Flash is a separate address space, so such a store is lost. It usually comes from an
+    /// uninitialized pointer.
+    fn store(&mut self, addr: u16, src: u8) {
+        if addr > self.device.ram_end() {
+            self.warnings.push(format!(
+                "Store to {:#06X} at {:#06X} past SRAM end {:#06X}, pointer likely uninitialized",
+                addr,
+                u32::from(self.pc) * 2,
+                self.device.ram_end()
+            ));
+        }
+        self.write_memory(addr, self.read_memory(src as u16));
     }
 
-    fn read_memory(&self, addr: u16) -> u8 {
-        self.memory[addr as usize]
+    /// Loads a byte like IN, LD and LDS, pinned I/O registers return their override instead
+    fn load(&mut self, addr: u16) -> u8 {
+        match self.io_overrides.get_mut(&addr) {
+            Some(IoOverride::Fixed(value)) => *value,
+            Some(IoOverride::Toggle { value, high }) => {
+                let read = if *high { *value } else { 0 };
+                *high = !*high;
+                read
+            }
+            None => self.read_memory(addr),
+        }
     }
 
-    fn push_stack(&mut self, value: u8) -> Result<(), String> {
-        self.sp = self.sp.wrapping_sub(1);
+    /// Data space address of an I/O register, IN/OUT only reach the 64 registers at 0x00-0x3F
+    fn io_addr(addr: u16) -> u16 {
+        IO_BASE + (addr & 0x3F)
+    }
+
+    /// Pointer held in a register pair, `low` is the register with the low byte
+    fn register_pair(&self, low: u8) -> u16 {
+        ((self.read_memory(low as u16 + 1) as u16) << 8) | self.read_memory(low as u16) as u16
+    }
 
-        if self.sp < 0x0060 || self.sp > 0x045F {
-            return Err(format!("Stack overflow! SP={:#04X}", self.sp));
+    /// Reads from data space, reads past the end of SRAM return 0
+    pub(crate) fn read_memory(&self, addr: u16) -> u8 {
+        match addr {
+            SPL => self.sp as u8,
+            SPH => (self.sp >> 8) as u8,
+            _ => self.memory.get(addr as usize).copied().unwrap_or(0),
         }
+    }
+
+    /// Checks that `bytes` can be pushed without the stack growing below its region
+    fn check_stack_room(&self, bytes: u16) -> Result<(), EmuError> {
+        if self.sp < self.stack_region.start() + bytes || self.sp > *self.stack_region.end() {
+            return Err(EmuError::StackOverflow {
+                pc: self.pc,
+                sp: self.sp,
+            });
+        }
+        Ok(())
+    }
+
+    /// Moves the stack pointer, the SPL and SPH bytes of the data space follow it
+    fn move_sp(&mut self, sp: u16) {
+        self.sp = sp;
+        let [low, high] = sp.to_le_bytes();
+        self.memory[SPL as usize] = low;
+        self.memory[SPH as usize] = high;
+    }
 
+    fn push_stack(&mut self, value: u8) -> Result<(), EmuError> {
+        self.check_stack_room(1)?;
+        self.move_sp(self.sp - 1);
         self.write_memory(self.sp, value);
         Ok(())
     }
 
-    fn pop_stack(&mut self) -> Result<u8, String> {
-        if self.sp > 0x045F {
-            return Err(format!("Stack underflow! SP={:#04X}", self.sp));
+    /// Checks that `bytes` pushed bytes sit between SP and the top of the stack region
+    fn check_stack_data(&self, bytes: u16) -> Result<(), EmuError> {
+        if u32::from(self.sp) + u32::from(bytes) > u32::from(*self.stack_region.end()) {
+            return Err(EmuError::StackUnderflow { sp: self.sp });
         }
+        Ok(())
+    }
 
+    fn pop_stack(&mut self) -> Result<u8, EmuError> {
+        self.check_stack_data(1)?;
         let ret = self.read_memory(self.sp);
-        self.sp = self.sp.wrapping_add(1);
+        self.move_sp(self.sp.wrapping_add(1));
         Ok(ret)
     }
 
-    pub fn write_to_register(&mut self, addr: u16, value: u8) {
-        if addr < 32 {
-            self.memory[addr as usize] = value;
+    /// Writes a general purpose register while paused
+    ///
+    /// # Errors
+    ///
+    /// Index is not one of r0-r31.
+    pub fn set_register(&mut self, idx: u8, value: u8) -> Result<(), EmuError> {
+        if idx >= 32 {
+            return Err(EmuError::OutOfBounds { addr: idx as usize });
+        }
+        self.memory[idx as usize] = value;
+        Ok(())
+    }
+
+    /// Moves PC to given word address while paused, the reset vector stays as is
+    ///
+    /// # Errors
+    ///
+    /// Address is past the end of flash.
+    pub fn set_pc(&mut self, word_addr: u16) -> Result<(), EmuError> {
+        if word_addr as usize * 2 >= self.flash.len() {
+            return Err(EmuError::OutOfBounds {
+                addr: word_addr as usize * 2,
+            });
+        }
+        self.move_pc(word_addr);
+        Ok(())
+    }
+
+    /// Moves the stack pointer while paused
+    ///
+    /// # Errors
+    ///
+    /// Address is outside of the stack region.
+    pub fn set_sp(&mut self, addr: u16) -> Result<(), EmuError> {
+        if !self.stack_region.contains(&addr) {
+            return Err(EmuError::OutOfBounds {
+                addr: addr as usize,
+            });
         }
+        self.move_sp(addr);
+        Ok(())
+    }
+
+    /// Writes one EEPROM byte
+    ///
+    /// # Errors
+    ///
+    /// Address is past the end of EEPROM.
+    pub fn write_eeprom(&mut self, addr: usize, value: u8) -> Result<(), EmuError> {
+        match self.eeprom.get_mut(addr) {
+            Some(cell) => *cell = value,
+            None => return Err(EmuError::OutOfBounds { addr }),
+        }
+        Ok(())
     }
 }
 
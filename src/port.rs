@@ -53,7 +53,7 @@ impl ATport {
         self.reset_hold
     }
 
-    pub fn update_io(&mut self, memory: &mut [u8; 1120]) -> Result<(), String> {
+    pub fn update_io(&mut self, memory: &mut [u8]) -> Result<(), String> {
         if let Some(ref mut stream) = self.tcp_connection {
             let mut buf = [0u8; 4];
 
@@ -0,0 +1,111 @@
+use crate::error::EmuError;
+use crate::memory::ATmemory;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Lab exercise described in one TOML file
+///
+/// ```toml
+/// program = "sum.hex"
+/// breakpoints = [0x0A]
+///
+/// [registers]
+/// r17 = 10
+///
+/// [expect]
+/// r16 = 55
+/// ```
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Scenario {
+    pub program: PathBuf, // .hex or .bin, relative paths start next to the manifest
+    #[serde(default)]
+    pub registers: BTreeMap<String, u8>, // Initial register values, keyed "r0".."r31"
+    #[serde(default)]
+    pub eeprom: Vec<u8>, // Initial EEPROM content from address 0
+    #[serde(default)]
+    pub breakpoints: Vec<u16>, // Flash byte addresses the run stops at
+    #[serde(default)]
+    pub expect: BTreeMap<String, u8>, // Register values after the run
+}
+
+impl Scenario {
+    /// Reads a manifest, the program path is resolved against the manifest's directory
+    ///
+    /// # Errors
+    ///
+    /// File cannot be read or is not a valid manifest.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let mut scenario: Scenario =
+            toml::from_str(&content).map_err(|e| format!("Invalid scenario: {}", e))?;
+
+        if scenario.program.is_relative()
+            && let Some(dir) = path.parent()
+        {
+            scenario.program = dir.join(&scenario.program);
+        }
+        Ok(scenario)
+    }
+
+    /// Loads the program and writes the initial registers and EEPROM
+    ///
+    /// # Errors
+    ///
+    /// Program cannot be loaded, a register name is unknown or EEPROM content is too long.
+    pub fn apply(&self, cpu: &mut ATmemory) -> Result<(), String> {
+        let program = &self.program;
+        match program.extension().and_then(|ext| ext.to_str()) {
+            Some("hex") => cpu.load_hex(program).map_err(|e| e.to_string())?,
+            Some("bin") => cpu.load_bin(program).map_err(|e| e.to_string())?,
+            _ => {
+                return Err(format!("Unsupported program file: {}", program.display()));
+            }
+        }
+
+        for (name, &value) in &self.registers {
+            cpu.set_register(register_index(name)?, value)
+                .map_err(|e| e.to_string())?;
+        }
+        for (addr, &value) in self.eeprom.iter().enumerate() {
+            cpu.write_eeprom(addr, value).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Runs the applied program until a breakpoint or its end
+    ///
+    /// # Errors
+    ///
+    /// Execution failed or did not stop within the free-run limit.
+    pub fn run(&self, cpu: &mut ATmemory) -> Result<(), EmuError> {
+        cpu.run_to_completion(&self.breakpoints)
+    }
+
+    /// Registers that differ from the expected values, empty when the exercise passed
+    pub fn check(&self, cpu: &ATmemory) -> Vec<String> {
+        self.expect
+            .iter()
+            .filter_map(|(name, &expected)| match register_index(name) {
+                Ok(idx) if cpu.memory()[idx as usize] == expected => None,
+                Ok(idx) => Some(format!(
+                    "{}: expected {:#04X}, got {:#04X}",
+                    name,
+                    expected,
+                    cpu.memory()[idx as usize]
+                )),
+                Err(e) => Some(e),
+            })
+            .collect()
+    }
+}
+
+/// Index of a register named like "r16"
+fn register_index(name: &str) -> Result<u8, String> {
+    name.strip_prefix('r')
+        .and_then(|idx| idx.parse::<u8>().ok())
+        .filter(|&idx| idx < 32)
+        .ok_or_else(|| format!("Unknown register {}", name))
+}
@@ -0,0 +1,102 @@
+use std::fmt;
+
+/// SREG flag letters from bit 7 to bit 0
+const FLAGS: [char; 8] = ['I', 'T', 'H', 'S', 'V', 'N', 'Z', 'C'];
+
+/// CPU state captured at one point of a run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub pc: u16,
+    pub sp: u16,
+    pub cycle_cnt: u32,
+    pub memory: Vec<u8>, // Whole data space, registers and SREG included
+}
+
+/// Single difference between two snapshots, `old` is the reference value
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateDelta {
+    Pc { old: u16, new: u16 },
+    Sp { old: u16, new: u16 },
+    Register { reg: u8, old: u8, new: u8 },
+    Flag { flag: char, old: bool, new: bool },
+    Memory { addr: u16, old: u8, new: u8 },
+}
+
+impl fmt::Display for StateDelta {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StateDelta::Pc { old, new } => write!(f, "PC: {:#06X} -> {:#06X}", old, new),
+            StateDelta::Sp { old, new } => write!(f, "SP: {:#06X} -> {:#06X}", old, new),
+            StateDelta::Register { reg, old, new } => {
+                write!(f, "r{}: {:#04X} -> {:#04X}", reg, old, new)
+            }
+            StateDelta::Flag { flag, old, new } => {
+                write!(f, "{}: {} -> {}", flag, u8::from(*old), u8::from(*new))
+            }
+            StateDelta::Memory { addr, old, new } => {
+                write!(f, "{:#06X}: {:#04X} -> {:#04X}", addr, old, new)
+            }
+        }
+    }
+}
+
+/// Byte addresses whose value differs between two images, e.g. flash before and after a reload
+pub fn changed_addresses(old: &[u8], new: &[u8]) -> Vec<usize> {
+    let len = old.len().max(new.len());
+    (0..len)
+        .filter(|&addr| old.get(addr) != new.get(addr))
+        .collect()
+}
+
+impl Snapshot {
+    /// Lists what changed from this reference to `current`, SREG is compared flag by flag
+    pub fn diff(&self, current: &Snapshot) -> Vec<StateDelta> {
+        let mut deltas = Vec::new();
+
+        if self.pc != current.pc {
+            deltas.push(StateDelta::Pc {
+                old: self.pc,
+                new: current.pc,
+            });
+        }
+        if self.sp != current.sp {
+            deltas.push(StateDelta::Sp {
+                old: self.sp,
+                new: current.sp,
+            });
+        }
+
+        let (old_sreg, new_sreg) = (self.memory[0x5F], current.memory[0x5F]);
+        for (idx, flag) in FLAGS.iter().enumerate() {
+            let bit = 7 - idx;
+            let (old, new) = ((old_sreg >> bit) & 1 == 1, (new_sreg >> bit) & 1 == 1);
+            if old != new {
+                deltas.push(StateDelta::Flag {
+                    flag: *flag,
+                    old,
+                    new,
+                });
+            }
+        }
+
+        for (addr, (old, new)) in self.memory.iter().zip(current.memory.iter()).enumerate() {
+            if old == new || addr == 0x5F {
+                continue;
+            }
+            deltas.push(match addr {
+                0..32 => StateDelta::Register {
+                    reg: addr as u8,
+                    old: *old,
+                    new: *new,
+                },
+                _ => StateDelta::Memory {
+                    addr: addr as u16,
+                    old: *old,
+                    new: *new,
+                },
+            });
+        }
+
+        deltas
+    }
+}
@@ -1,7 +1,12 @@
 #![cfg(test)]
 
-use crate::memory::ATmemory;
+use breadboard::config::{Config, ErrorPolicy, SramInit};
+use breadboard::device::Device;
+use breadboard::error::EmuError;
+use breadboard::memory::{ATmemory, Instruction, IoOverride, MemSpace};
+use breadboard::snapshot::StateDelta;
 use rand::Rng;
+use std::ffi::OsString;
 
 #[test]
 /// Load 255 to r17
@@ -20,8 +25,8 @@ fn tst_add() {
     let mut rng = rand::rng();
     let value_r16: u8 = rng.random_range(0..=255);
     let value_r17: u8 = rng.random_range(0..=255);
-    cpu.write_to_register(16, value_r16);
-    cpu.write_to_register(17, value_r17);
+    cpu.set_register(16, value_r16).ok();
+    cpu.set_register(17, value_r17).ok();
     // add r16, r17
     let program: Vec<u8> = vec![0x01, 0x0F];
     cpu.load_flash_from_vec(program.clone()).ok();
@@ -37,8 +42,8 @@ fn tst_sub() {
     let mut rng = rand::rng();
     let value_r16: u8 = rng.random_range(0..=255);
     let value_r17: u8 = rng.random_range(0..=255);
-    cpu.write_to_register(16, value_r16);
-    cpu.write_to_register(17, value_r17);
+    cpu.set_register(16, value_r16).ok();
+    cpu.set_register(17, value_r17).ok();
     // sub r16, r17
     let program: Vec<u8> = vec![0x01, 0x1B];
     cpu.load_flash_from_vec(program.clone()).ok();
@@ -115,8 +120,8 @@ fn tst_and() {
     let mut rng = rand::rng();
     let value_r16: u8 = rng.random_range(0..=255);
     let value_r17: u8 = rng.random_range(0..=255);
-    cpu.write_to_register(16, value_r16);
-    cpu.write_to_register(17, value_r17);
+    cpu.set_register(16, value_r16).ok();
+    cpu.set_register(17, value_r17).ok();
 
     // and r16, r17
     let program: Vec<u8> = vec![0x01, 0x23];
@@ -134,8 +139,8 @@ fn tst_xor() {
     let mut rng = rand::rng();
     let value_r16: u8 = rng.random_range(0..=255);
     let value_r17: u8 = rng.random_range(0..=255);
-    cpu.write_to_register(16, value_r16);
-    cpu.write_to_register(17, value_r17);
+    cpu.set_register(16, value_r16).ok();
+    cpu.set_register(17, value_r17).ok();
 
     // eor r16, r17
     let program: Vec<u8> = vec![0x01, 0x27];
@@ -153,8 +158,8 @@ fn tst_or() {
     let mut rng = rand::rng();
     let value_r16: u8 = rng.random_range(0..=255);
     let value_r17: u8 = rng.random_range(0..=255);
-    cpu.write_to_register(16, value_r16);
-    cpu.write_to_register(17, value_r17);
+    cpu.set_register(16, value_r16).ok();
+    cpu.set_register(17, value_r17).ok();
 
     // or r16, r17
     let program: Vec<u8> = vec![0x01, 0x2B];
@@ -171,7 +176,7 @@ fn tst_andi() {
     let mut cpu = ATmemory::init();
     let mut rng = rand::rng();
     let value_r16: u8 = rng.random_range(0..=255);
-    cpu.write_to_register(16, value_r16);
+    cpu.set_register(16, value_r16).ok();
 
     // andi r16, 29
     let program: Vec<u8> = vec![0x0D, 0x71];
@@ -188,7 +193,7 @@ fn tst_ori() {
     let mut cpu = ATmemory::init();
     let mut rng = rand::rng();
     let value_r16: u8 = rng.random_range(0..=255);
-    cpu.write_to_register(16, value_r16);
+    cpu.set_register(16, value_r16).ok();
 
     // ori r16, 29
     let program: Vec<u8> = vec![0x0D, 0x61];
@@ -239,7 +244,7 @@ fn tst_asr() {
     let mut rng = rand::rng();
     let value_r16: u8 = rng.random_range(0..=255);
     let c_flag = value_r16 & 0x01;
-    cpu.write_to_register(16, value_r16);
+    cpu.set_register(16, value_r16).ok();
 
     // asr r16
     let program: Vec<u8> = vec![0x05, 0x95];
@@ -255,7 +260,7 @@ fn tst_out() {
     let mut cpu = ATmemory::init();
     let mut rng = rand::rng();
     let value_r21: u8 = rng.random_range(0..=255);
-    cpu.write_to_register(21, value_r21);
+    cpu.set_register(21, value_r21).ok();
 
     // out SPL, r21
     let program: Vec<u8> = vec![0x5D, 0xBF];
@@ -265,3 +270,2759 @@ fn tst_out() {
     }
     assert_eq!(cpu.memory()[93], value_r21)
 }
+
+#[test]
+/// Writing SPL and SPH moves the stack pointer used by PUSH, IN reads it back
+fn tst_sp_io_alias() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 0x00; ldi r17, 0x02; out SPL, r16; out SPH, r17
+    // ldi r18, 0x42; push r18; in r19, SPL
+    let program: Vec<u8> = vec![
+        0x00, 0xE0, 0x12, 0xE0, 0x0D, 0xBF, 0x1E, 0xBF, 0x22, 0xE4, 0x2F, 0x93, 0x3D, 0xB7,
+    ];
+    cpu.load_flash_from_vec(program.clone()).ok();
+    for _ in 0..(program.len() / 2) {
+        cpu.step().ok();
+    }
+    assert_eq!(
+        (cpu.sp(), cpu.memory()[0x1FF], cpu.memory()[19]),
+        (0x1FF, 0x42, 0xFF)
+    )
+}
+
+#[test]
+/// SREG is reachable through IN and OUT, saving and restoring flags like an interrupt prologue
+fn tst_sreg_io_alias() {
+    let mut cpu = ATmemory::init();
+    // sec; in r0, SREG; clc; out SREG, r0
+    let program: Vec<u8> = vec![0x08, 0x94, 0x0F, 0xB6, 0x88, 0x94, 0x0F, 0xBE];
+    cpu.load_flash_from_vec(program).ok();
+    cpu.step_n(2).ok();
+    assert_eq!(cpu.memory()[0], 0x01);
+    cpu.step().ok();
+    assert_eq!(cpu.sreg(), 0x00);
+    cpu.step().ok();
+    assert_eq!(cpu.sreg(), 0x01)
+}
+
+#[test]
+/// A single step executes both words of CALL, the return lands on the word after its operand
+fn tst_call_two_words() {
+    let mut cpu = ATmemory::init();
+    // call sub
+    // ldi r16, 0x2A
+    // end: rjmp end
+    // sub: ret
+    let program: Vec<u8> = vec![0x0E, 0x94, 0x04, 0x00, 0x0A, 0xE2, 0xFF, 0xCF, 0x08, 0x95];
+    cpu.load_flash_from_vec(program).ok();
+    cpu.step().ok();
+    assert_eq!(
+        (cpu.pc(), cpu.memory()[0x45D], cpu.memory()[0x45E]),
+        (0x0004, 0x00, 0x02)
+    );
+    cpu.step().ok();
+    assert_eq!(cpu.pc() * 2, 4);
+    assert_eq!(cpu.get_instruction(), "LDI r16, 0x2A");
+
+    cpu.reset();
+    cpu.step_over().ok();
+    assert_eq!(cpu.pc() * 2, 4);
+    cpu.step().ok();
+    assert_eq!(cpu.memory()[16], 0x2A)
+}
+
+#[test]
+/// Step over a subroutine call
+fn tst_step_over() {
+    let mut cpu = ATmemory::init();
+    // Reset:
+    //     ldi r16, 0x12
+    // rjmp Main
+    //
+    // increment_reg
+    //     inc r16
+    // ret
+    //
+    // Main:
+    //     rcall increment_reg
+    // rjmp Main
+    let program: Vec<u8> = vec![0x02, 0xE1, 0x02, 0xC0, 0x03, 0x95, 0x08, 0x95, 0xFD, 0xDF];
+    cpu.load_flash_from_vec(program).ok();
+    cpu.step().ok();
+    cpu.step().ok();
+    cpu.step_over().ok();
+    assert_eq!(
+        (cpu.memory()[16], cpu.pc(), cpu.sp()),
+        (0x13, 0x0005, 0x045F)
+    )
+}
+
+#[test]
+/// A store through a pointer past the end of SRAM warns with the address and PC
+fn tst_store_past_sram() {
+    let mut cpu = ATmemory::init();
+    // ldi r30, 0x00
+    // ldi r31, 0x05
+    // st Z, r16
+    // std Z+0x1F, r16
+    let program: Vec<u8> = vec![0xE0, 0xE0, 0xF5, 0xE0, 0x00, 0x83, 0x07, 0x8F];
+    cpu.load_flash_from_vec(program.clone()).ok();
+    cpu.step_n(3).ok();
+    assert_eq!(
+        cpu.take_warnings(),
+        vec!["Store to 0x0500 at 0x0004 past SRAM end 0x045F, pointer likely uninitialized"]
+    );
+
+    // Top of SRAM is still in range
+    cpu.set_register(30, 0x40).ok();
+    cpu.set_register(31, 0x04).ok();
+    cpu.step().ok();
+    assert_eq!(cpu.memory()[0x045F], 0);
+    assert!(cpu.take_warnings().is_empty());
+
+    // The end follows the device, the ATmega128 has SRAM up to 0x10FF
+    cpu.set_device(Device::ATmega128);
+    cpu.load_flash_from_vec(program).ok();
+    cpu.step_n(3).ok();
+    assert!(cpu.take_warnings().is_empty());
+
+    // ldi r30, 0x60
+    // ldi r31, 0x00
+    // st Z, r16
+    let program: Vec<u8> = vec![0xE0, 0xE6, 0xF0, 0xE0, 0x00, 0x83];
+    cpu.set_device(Device::ATtiny10);
+    cpu.load_flash_from_vec(program).ok();
+    cpu.step_n(3).ok();
+    assert_eq!(
+        cpu.take_warnings(),
+        vec!["Store to 0x0060 at 0x0004 past SRAM end 0x005F, pointer likely uninitialized"]
+    )
+}
+
+#[test]
+/// INT0 with I set pushes PC, clears I and jumps to vector 1, RETI returns and sets I again
+fn tst_trigger_interrupt() {
+    let mut cpu = ATmemory::init();
+    // sei
+    // nop
+    // INT0 vector:
+    //     reti
+    let program: Vec<u8> = vec![0x78, 0x94, 0x00, 0x00, 0x18, 0x95];
+    cpu.load_flash_from_vec(program).ok();
+    assert_eq!(cpu.trigger_interrupt(1), Err(EmuError::InterruptsDisabled));
+
+    cpu.step().ok();
+    assert_eq!(cpu.trigger_interrupt(1), Ok(()));
+    assert_eq!(
+        (cpu.pc(), cpu.sp(), cpu.sreg() & 0x80, cpu.call_depth()),
+        (0x0002, 0x045D, 0, 1)
+    );
+    assert_eq!((cpu.memory()[0x45D], cpu.memory()[0x45E]), (0x00, 0x01));
+    cpu.step().ok();
+    assert_eq!((cpu.pc(), cpu.sreg() & 0x80), (0x0001, 0x80));
+
+    assert_eq!(
+        cpu.trigger_interrupt(21),
+        Err(EmuError::NoInterruptVector {
+            device: Device::ATmega16,
+            vector: 21
+        })
+    );
+    cpu.trigger_reset();
+    assert_eq!((cpu.pc(), cpu.sp(), cpu.sreg()), (0x0000, 0x045F, 0))
+}
+
+#[test]
+/// Step out of a subroutine back to the instruction after the call
+fn tst_step_out() {
+    let mut cpu = ATmemory::init();
+    // Reset:
+    //     ldi r16, 0x12
+    // rjmp Main
+    //
+    // increment_reg
+    //     inc r16
+    //     inc r16
+    // ret
+    //
+    // Main:
+    //     rcall increment_reg
+    // rjmp Main
+    let program: Vec<u8> = vec![
+        0x02, 0xE1, 0x03, 0xC0, 0x03, 0x95, 0x03, 0x95, 0x08, 0x95, 0xFC, 0xDF, 0xFE, 0xCF,
+    ];
+    cpu.load_flash_from_vec(program).ok();
+    cpu.step().ok();
+    cpu.step().ok();
+    cpu.step().ok();
+    cpu.step().ok();
+    assert_eq!(cpu.pc(), 0x0003);
+    cpu.step_out().ok();
+    assert_eq!(
+        (cpu.memory()[16], cpu.pc(), cpu.sp()),
+        (0x14, 0x0006, 0x045F)
+    );
+    assert!(cpu.step_out().is_err())
+}
+
+#[test]
+/// Run to a mid-program address
+fn tst_run_until() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 1
+    // inc r16
+    // inc r16
+    // inc r16
+    // rjmp PC
+    let program: Vec<u8> = vec![0x01, 0xE0, 0x03, 0x95, 0x03, 0x95, 0x03, 0x95, 0xFF, 0xCF];
+    cpu.load_flash_from_vec(program).ok();
+    cpu.run_until(0x0006).ok();
+    assert_eq!((cpu.pc() * 2, cpu.memory()[16]), (0x0006, 3))
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// SREG letters map to bits 7 (I) down to 0 (C)
+fn tst_sreg_flag_mapping() {
+    use crate::gui::GUInterface;
+
+    let flags = ["I", "T", "H", "S", "V", "N", "Z", "C"];
+    let set: Vec<&str> = flags
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| GUInterface::sreg_flag_set(0b10000001, *idx))
+        .map(|(_, flag)| *flag)
+        .collect();
+    assert_eq!(set, vec!["I", "C"])
+}
+
+#[test]
+/// Load pasted Intel HEX records
+fn tst_load_from_text_intel_hex() {
+    let mut cpu = ATmemory::init();
+    let text = ":020000020000FC\n:0E00000004E312E12DEC3BEA020F131FF9CFCF\n:00000001FF";
+    cpu.load_from_text(text).ok();
+    assert_eq!(
+        cpu.flash()[..14],
+        [
+            0x04, 0xE3, 0x12, 0xE1, 0x2D, 0xEC, 0x3B, 0xEA, 0x02, 0x0F, 0x13, 0x1F, 0xF9, 0xCF
+        ]
+    )
+}
+
+#[test]
+/// Load pasted raw hex bytes
+fn tst_load_from_text_raw() {
+    let mut cpu = ATmemory::init();
+    cpu.load_from_text("1F EF, 0x08 9503").ok();
+    assert_eq!(cpu.flash()[..4], [0x1F, 0xEF, 0x08, 0x95]);
+    assert!(cpu.load_from_text("1F E").is_err())
+}
+
+#[test]
+/// Access past the top of SRAM doesn't panic, stores are dropped and loads read 0
+fn tst_memory_out_of_bounds() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 0xAA; ldi r17, 0x55; ldi r18, 0x55
+    // ldi r30, 0x60; ldi r31, 0x04; std Z+0, r16; ldd r17, Z+0
+    // ldi r30, 0xFF; ldi r31, 0xFF; std Z+0, r16; ldd r18, Z+0
+    cpu.load_flash_from_vec(vec![
+        0x0A, 0xEA, 0x15, 0xE5, 0x25, 0xE5, 0xE0, 0xE6, 0xF4, 0xE0, 0x00, 0x83, 0x10, 0x81, 0xEF,
+        0xEF, 0xFF, 0xEF, 0x00, 0x83, 0x20, 0x81,
+    ])
+    .ok();
+    let before = cpu.memory()[0x20..].to_vec();
+    assert_eq!(cpu.step_n(11), Ok(11));
+    assert_eq!(&cpu.memory()[16..19], &[0xAA, 0x00, 0x00]);
+    assert_eq!((cpu.memory()[0], cpu.zp()), (0x00, 0xFFFF));
+    assert_eq!(cpu.memory()[0x20..], before[..])
+}
+
+#[test]
+/// Word view lists one line per instruction, two-word instructions included
+fn tst_disassemble_range() {
+    let mut cpu = ATmemory::init();
+    // LDI r16, 0x01; JMP 0x0000; NOP; INC r16
+    cpu.load_flash_from_vec(vec![
+        0x01, 0xE0, 0x0C, 0x94, 0x00, 0x00, 0x00, 0x00, 0x03, 0x95,
+    ])
+    .ok();
+    let lines = cpu.disassemble_range(0, 10);
+    assert_eq!(
+        lines
+            .iter()
+            .map(|(addr, len, _)| (*addr, *len))
+            .collect::<Vec<_>>(),
+        vec![(0, 2), (2, 4), (6, 2), (8, 2)]
+    )
+}
+
+#[test]
+/// Set SREG directly
+fn tst_set_sreg() {
+    let mut cpu = ATmemory::init();
+    cpu.set_sreg(0b1010_0101);
+    assert_eq!((cpu.sreg(), cpu.memory()[0x5F]), (0b1010_0101, 0b1010_0101))
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Flag letter toggles only its own SREG bit
+fn tst_sreg_flag_toggle() {
+    use crate::gui::GUInterface;
+
+    // C is the last letter, I the first
+    let sreg = GUInterface::toggle_sreg_flag(0b0000_0000, 7);
+    assert_eq!(sreg, 0b0000_0001);
+    let sreg = GUInterface::toggle_sreg_flag(sreg, 0);
+    assert_eq!(sreg, 0b1000_0001);
+    assert_eq!(GUInterface::toggle_sreg_flag(sreg, 7), 0b1000_0000)
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Only flags that flipped between two SREG values are marked changed
+fn tst_sreg_flag_changed() {
+    use crate::gui::GUInterface;
+
+    let flags = ["I", "T", "H", "S", "V", "N", "Z", "C"];
+    let changed: Vec<&str> = flags
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| GUInterface::sreg_flag_changed(0b1000_0011, 0b1000_0110, *idx))
+        .map(|(_, flag)| *flag)
+        .collect();
+    assert_eq!(changed, vec!["N", "C"])
+}
+
+#[test]
+/// Load Intel HEX with CRLF line endings, indentation and blank lines
+fn tst_load_hex_crlf() {
+    let path = std::env::temp_dir().join("breadboard-tst-load-hex-crlf.hex");
+    std::fs::write(&path, "  :0400000001E0039583 \r\r\n\r\n:00000001FF\r\n").unwrap();
+    let mut cpu = ATmemory::init();
+    let result = cpu.load_hex(path.to_str().unwrap());
+    std::fs::remove_file(&path).ok();
+    assert_eq!(
+        (result, &cpu.flash()[..4]),
+        (Ok(()), &[0x01, 0xE0, 0x03, 0x95][..])
+    )
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Row offset driving the flash view auto-scroll
+fn tst_pc_row_fraction() {
+    use crate::gui::GUInterface;
+
+    assert_eq!(GUInterface::pc_row_fraction(0x10, 0x00, 0x40, 8), 0.25);
+    assert_eq!(GUInterface::pc_row_fraction(0x19, 0x08, 0x48, 8), 0.25);
+    // PC outside of the window clamps to the first and last row
+    assert_eq!(GUInterface::pc_row_fraction(0x00, 0x08, 0x48, 8), 0.0);
+    assert_eq!(GUInterface::pc_row_fraction(0x80, 0x00, 0x40, 8), 0.875)
+}
+
+#[test]
+/// Decode canonical opcodes, including the boundary registers of each operand field
+fn tst_decode_table() {
+    let mut cpu = ATmemory::init();
+    // Second word of JMP/CALL at word 1
+    cpu.load_flash_from_vec(vec![0x00, 0x00, 0x34, 0x12]).ok();
+
+    #[rustfmt::skip]
+    let table = [
+        (0x0000, Instruction::NOP),
+        (0x0C01, Instruction::ADD { dest: 0, src: 1 }),
+        (0x0FFF, Instruction::ADD { dest: 31, src: 31 }),
+        (0x160F, Instruction::CP { dest: 0, src: 31 }),
+        (0x19F0, Instruction::SUB { dest: 31, src: 0 }),
+        (0x1F01, Instruction::ADC { dest: 16, src: 17 }),
+        (0x2023, Instruction::AND { dest: 2, src: 3 }),
+        (0x2411, Instruction::EOR { dest: 1, src: 1 }),
+        (0x29EF, Instruction::OR { dest: 30, src: 15 }),
+        (0x2E0F, Instruction::MOV { dest: 0, src: 31 }),
+        (0x6F0F, Instruction::ORI { dest: 16, value: 0xFF }),
+        (0x70F0, Instruction::ANDI { dest: 31, value: 0x00 }),
+        (0x900F, Instruction::POP { reg: 0 }),
+        (0x91FF, Instruction::POP { reg: 31 }),
+        (0x920F, Instruction::PUSH { reg: 0 }),
+        (0x93FF, Instruction::PUSH { reg: 31 }),
+        (0x9403, Instruction::INC { reg: 0 }),
+        (0x95F3, Instruction::INC { reg: 31 }),
+        (0x9405, Instruction::ASR { dest: 0 }),
+        (0x95F5, Instruction::ASR { dest: 31 }),
+        (0x9408, Instruction::BSET { dest: 0 }),
+        (0x9478, Instruction::BSET { dest: 7 }),
+        (0x940A, Instruction::DEC { reg: 0 }),
+        (0x95FA, Instruction::DEC { reg: 31 }),
+        (0x940C, Instruction::JMP { dest: 0x1234 }),
+        (0x940E, Instruction::CALL { dest: 0x1234 }),
+        (0x9488, Instruction::BCLR { dest: 0 }),
+        (0x94F8, Instruction::BCLR { dest: 7 }),
+        (0x9508, Instruction::RET),
+        (0x9518, Instruction::RETI),
+        (0x9601, Instruction::ADIW { dest: 24, value: 1 }),
+        (0x96FF, Instruction::ADIW { dest: 30, value: 63 }),
+        (0x98FF, Instruction::CBI { dest: 31, bit: 7 }),
+        (0x9A00, Instruction::SBI { dest: 0, bit: 0 }),
+        (0xB60F, Instruction::IN { addr: 0x3F, dest: 0 }),
+        (0xB1F0, Instruction::IN { addr: 0x00, dest: 31 }),
+        (0xBFFF, Instruction::OUT { addr: 0x3F, src: 31 }),
+        (0xC002, Instruction::RJMP { offset: 2 }),
+        (0xDFFF, Instruction::RCALL { offset: -1 }),
+        (0xE000, Instruction::LDI { dest: 16, value: 0x00 }),
+        (0xEF1F, Instruction::LDI { dest: 17, value: 0xFF }),
+        (0xEFFF, Instruction::LDI { dest: 31, value: 0xFF }),
+        (0xF3F9, Instruction::BRBS { offset: -1, bit: 1 }),
+        (0xF5FF, Instruction::BRBC { offset: 63, bit: 7 }),
+    ];
+
+    for (opcode, expected) in table {
+        assert_eq!(cpu.decode(opcode), Ok(expected), "opcode {:#06X}", opcode);
+    }
+}
+
+#[test]
+/// Export loaded program the way test fixtures are written
+fn tst_flash_as_rust_literal() {
+    let mut cpu = ATmemory::init();
+    cpu.load_flash_from_vec(vec![0x1F, 0xEF, 0x03, 0x95]).ok();
+    assert_eq!(
+        cpu.flash_as_rust_literal(),
+        "let program: Vec<u8> = vec![0x1F, 0xEF, 0x03, 0x95];"
+    )
+}
+
+#[test]
+/// Stepping past the last loaded instruction is flagged
+fn tst_pc_beyond_program() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 1; inc r16
+    cpu.load_flash_from_vec(vec![0x01, 0xE0, 0x03, 0x95]).ok();
+    cpu.step().ok();
+    assert!(!cpu.pc_beyond_program());
+    cpu.step().ok();
+    assert!(cpu.pc_beyond_program())
+}
+
+#[test]
+/// Flash dumped as Intel HEX loads back identically
+fn tst_dump_hex_round_trip() {
+    let mut cpu = ATmemory::init();
+    let program: Vec<u8> = (0..=40).collect();
+    cpu.load_flash_from_vec(program.clone()).ok();
+    let hex = cpu.dump_hex();
+    assert!(hex.ends_with(":00000001FF\n"));
+
+    let mut reloaded = ATmemory::init();
+    reloaded.load_from_text(&hex).ok();
+    assert_eq!(
+        (reloaded.flash(), reloaded.program_size()),
+        (cpu.flash(), program.len())
+    )
+}
+
+#[test]
+/// Flash past 64 KiB is dumped behind an extended linear address record and loads back
+fn tst_dump_hex_extended_address() {
+    let mut cpu = ATmemory::init();
+    cpu.set_device(Device::ATmega128);
+    let program: Vec<u8> = (0..0x10020).map(|idx| (idx % 251) as u8).collect();
+    cpu.load_flash_from_vec(program).ok();
+    let hex = cpu.dump_hex();
+    assert_eq!(hex.matches(":020000040001F9").count(), 1);
+
+    let mut reloaded = ATmemory::init();
+    reloaded.set_device(Device::ATmega128);
+    assert_eq!(reloaded.load_from_text(&hex), Ok(()));
+    assert_eq!(
+        (reloaded.flash(), reloaded.program_size()),
+        (cpu.flash(), 0x10020)
+    )
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Decimal registers as unsigned and signed bytes, other bases unaffected
+fn tst_format_register_signed() {
+    use crate::gui::GUInterface;
+    use breadboard::config::DisplayBase;
+
+    let values = [0x00, 0x7F, 0x80, 0xFF];
+    let unsigned: Vec<String> = values
+        .iter()
+        .map(|v| GUInterface::format_register(*v, DisplayBase::Decimal, false))
+        .collect();
+    let signed: Vec<String> = values
+        .iter()
+        .map(|v| GUInterface::format_register(*v, DisplayBase::Decimal, true))
+        .collect();
+    assert_eq!(unsigned, vec!["0", "127", "128", "255"]);
+    assert_eq!(signed, vec!["0", "127", "-128", "-1"]);
+    assert_eq!(
+        GUInterface::format_register(0xFF, DisplayBase::Hexadecimal, true),
+        "0xFF"
+    )
+}
+
+#[test]
+/// Taken BRNE takes two cycles, not taken one
+fn tst_branch_cycles() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 1; dec r16; brne -2
+    let program: Vec<u8> = vec![0x01, 0xE0, 0x0A, 0x95, 0xF1, 0xF7];
+    cpu.load_flash_from_vec(program).ok();
+    cpu.step().ok();
+    cpu.step().ok();
+    // Z set, falls through
+    assert_eq!(cpu.step(), Ok(1));
+
+    // ldi r16, 2; dec r16; brne -2
+    let program: Vec<u8> = vec![0x02, 0xE0, 0x0A, 0x95, 0xF1, 0xF7];
+    cpu.load_flash_from_vec(program).ok();
+    cpu.step().ok();
+    cpu.step().ok();
+    // Z clear, jumps back
+    assert_eq!((cpu.step(), cpu.pc(), cpu.cycle_cnt()), (Ok(2), 1, 4))
+}
+
+#[test]
+/// Write to a watched stack address pauses the run and reports address and value
+fn tst_memory_watchpoint() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 0x2A; push r16; nop; nop
+    let program: Vec<u8> = vec![0x0A, 0xE2, 0x0F, 0x93, 0x00, 0x00, 0x00, 0x00];
+    cpu.load_flash_from_vec(program).ok();
+    assert!(cpu.toggle_watchpoint(0x045E));
+    cpu.run_until(8).ok();
+    assert_eq!((cpu.pc(), cpu.take_watch_hit()), (2, Some((0x045E, 0x2A))));
+    assert_eq!(cpu.take_watch_hit(), None)
+}
+
+#[test]
+/// SPL and SPH in the data space follow SP through reset, pushes and calls
+fn tst_sp_alias_bytes() {
+    let mut cpu = ATmemory::init();
+    let sp_bytes = |cpu: &ATmemory| u16::from_le_bytes([cpu.memory()[0x5D], cpu.memory()[0x5E]]);
+    assert_eq!(sp_bytes(&cpu), 0x045F);
+    // push r16; rcall +0; pop r17
+    cpu.load_flash_from_vec(vec![0x0F, 0x93, 0x00, 0xD0, 0x1F, 0x91])
+        .ok();
+    cpu.step().ok();
+    assert_eq!(sp_bytes(&cpu), 0x045E);
+    cpu.step().ok();
+    assert_eq!(sp_bytes(&cpu), 0x045C);
+    cpu.step().ok();
+    assert_eq!((sp_bytes(&cpu), cpu.sp()), (0x045D, 0x045D));
+    cpu.reset();
+    assert_eq!(sp_bytes(&cpu), 0x045F)
+}
+
+#[test]
+/// Flag updates and a reset write SREG like any store, so a watchpoint on 0x5F fires
+fn tst_sreg_watchpoint() {
+    let mut cpu = ATmemory::init();
+    // sec; clc
+    cpu.load_flash_from_vec(vec![0x08, 0x94, 0x88, 0x94]).ok();
+    assert!(cpu.toggle_watchpoint(0x005F));
+    cpu.step().ok();
+    assert_eq!(cpu.take_watch_hit(), Some((0x005F, 0x01)));
+    cpu.step().ok();
+    assert_eq!(cpu.take_watch_hit(), Some((0x005F, 0x00)));
+
+    cpu.set_sreg(0x80);
+    cpu.take_watch_hit();
+    cpu.trigger_reset();
+    assert_eq!(cpu.take_watch_hit(), Some((0x005F, 0x00)))
+}
+
+#[test]
+/// Odd length program loads with a warning, its last word padded with 0x00
+fn tst_load_odd_length() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 1; half of inc
+    let program: Vec<u8> = vec![0x01, 0xE0, 0x03];
+    assert_eq!(cpu.load_flash_from_vec(program), Ok(()));
+    assert_eq!(cpu.take_warnings().len(), 1);
+    assert_eq!(&cpu.flash()[..4], &[0x01, 0xE0, 0x03, 0x00]);
+
+    cpu.load_flash_from_vec(vec![0x01, 0xE0]).ok();
+    assert!(cpu.take_warnings().is_empty())
+}
+
+#[test]
+/// Patch a NOP into an INC without resetting the CPU
+fn tst_patch_instruction() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 1; nop
+    let program: Vec<u8> = vec![0x01, 0xE0, 0x00, 0x00];
+    cpu.load_flash_from_vec(program).ok();
+    cpu.step().ok();
+    assert_eq!(cpu.patch_instruction(1, "inc r16 ; was nop"), Ok(()));
+    let opcode = u16::from_le_bytes([cpu.flash()[2], cpu.flash()[3]]);
+    assert_eq!(cpu.decode(opcode), Ok(Instruction::INC { reg: 16 }));
+    cpu.step().ok();
+    assert_eq!((cpu.pc(), cpu.memory()[16]), (2, 2));
+
+    assert!(cpu.patch_instruction(1, "nop\nnop").is_err());
+    assert!(cpu.patch_instruction(1, "ldi r0, 1").is_err())
+}
+
+#[test]
+/// Flash edits drop decoded instructions, a loop runs the patched code on its next pass
+fn tst_decode_cache_invalidation() {
+    let mut cpu = ATmemory::init();
+    // loop: inc r16; rjmp loop
+    let program: Vec<u8> = vec![0x03, 0x95, 0xFE, 0xCF];
+    cpu.load_flash_from_vec(program).ok();
+    cpu.step_n(4).ok();
+    assert_eq!((cpu.pc(), cpu.memory()[16]), (0, 2));
+
+    cpu.patch_instruction(0, "dec r16").ok();
+    cpu.step_n(4).ok();
+    assert_eq!((cpu.pc(), cpu.memory()[16]), (0, 0));
+
+    cpu.fill(MemSpace::Flash, 0, 2, 0x00).ok();
+    cpu.step_n(2).ok();
+    assert_eq!((cpu.pc(), cpu.memory()[16]), (0, 0));
+
+    cpu.load_flash_from_vec(vec![0x0F, 0xEF]).ok();
+    cpu.step().ok();
+    assert_eq!(cpu.memory()[16], 0xFF)
+}
+
+#[test]
+/// Vector table ends at 0x0053 on the ATmega16, the .data image is found by the copy loop
+fn tst_flash_regions() {
+    use breadboard::memory::FlashRegion;
+
+    let mut cpu = ATmemory::init();
+    // 21 vectors of jmp 0x0054
+    let mut program: Vec<u8> = [0x0C, 0x94, 0x2A, 0x00].repeat(21);
+    // ldi r17, 0x00; ldi r26, 0x60; ldi r27, 0x00; ldi r30, 0x6C; ldi r31, 0x00; rjmp start
+    // loop: lpm r0, Z+; st X+, r0
+    // start: cpi r26, 0x62; cpc r27, r17; brne loop
+    // rjmp PC
+    program.extend([
+        0x10, 0xE0, 0xA0, 0xE6, 0xB0, 0xE0, 0xEC, 0xE6, 0xF0, 0xE0, 0x02, 0xC0, 0x05, 0x90, 0x0D,
+        0x92, 0xA2, 0x36, 0xB1, 0x07, 0xE1, 0xF7, 0xFF, 0xCF,
+    ]);
+    // .data image
+    program.extend([0x12, 0x34]);
+    cpu.load_flash_from_vec(program).ok();
+
+    let layout = cpu.flash_layout();
+    assert_eq!(layout.data_image, Some(0x6C..0x6E));
+    let regions: Vec<FlashRegion> = [0x0000, 0x0053, 0x0054, 0x006B, 0x006C, 0x006D, 0x006E]
+        .iter()
+        .map(|&addr| layout.region_of(addr))
+        .collect();
+    assert_eq!(
+        regions,
+        vec![
+            FlashRegion::Vectors,
+            FlashRegion::Vectors,
+            FlashRegion::Text,
+            FlashRegion::Text,
+            FlashRegion::Data,
+            FlashRegion::Data,
+            FlashRegion::Unused,
+        ]
+    );
+
+    // Programs without a vector table start with code
+    cpu.load_flash_from_vec(vec![0x0F, 0xEF, 0xFF, 0xCF]).ok();
+    let layout = cpu.flash_layout();
+    assert_eq!(
+        (layout.vectors_end, layout.region_of(0)),
+        (0, FlashRegion::Text)
+    );
+    assert_eq!(layout.data_image, None)
+}
+
+/// Reference decodings, `opcode[ operand word],mnemonic` per line, words in hex
+///
+/// New instructions get coverage by appending rows.
+const DECODE_VECTORS: &str = "\
+1F01,ADC r16, r17
+0E0F,ADD r0, r31
+2034,AND r3, r4
+1745,CP r20, r21
+2456,EOR r5, r6
+2D01,MOV r16, r1
+9F01,MUL r16, r17
+2878,OR r7, r8
+1BEF,SUB r30, r31
+700F,ANDI r16, 0x0F
+EFFF,LDI r31, 0xFF
+6810,ORI r17, 0x80
+9505,ASR r16
+9420,COM r2
+950A,DEC r16
+9503,INC r16
+9496,LSR r9
+94A1,NEG r10
+910F,POP r16
+931F,PUSH r17
+9527,ROR r18
+9532,SWAP r19
+96CF,ADIW r24, 63
+94F8,BCLR 7
+9478,BSET 7
+F7F1,BRBC 1, -2
+F028,BRBS 0, 5
+98C3,CBI 0x18, 3
+9AFF,SBI 0x1F, 7
+B70F,IN r16, 0x3F
+BB12,OUT 0x12, r17
+0000,NOP
+9508,RET
+9518,RETI
+95E8,SPM
+C005,RJMP 5
+DFFD,RCALL -3
+CFFF,RJMP -1
+810D,LDD r16, Y+5
+AD17,LDD r17, Z+63
+8329,STD Y+1, r18
+940E 0100,CALL 0x0100
+940C 002A,JMP 0x002A
+940F 0000,CALL 0x10000
+";
+
+#[test]
+/// Every reference opcode decodes and formats to its mnemonic
+fn tst_decode_vectors() {
+    let mut cpu = ATmemory::init();
+    let mut mismatches = Vec::new();
+    for line in DECODE_VECTORS.lines() {
+        let (words, mnemonic) = line.split_once(',').unwrap();
+        let bytes: Vec<u8> = words
+            .split_whitespace()
+            .flat_map(|word| u16::from_str_radix(word, 16).unwrap().to_le_bytes())
+            .collect();
+        cpu.load_flash_from_vec(bytes).unwrap();
+        let decoded = match cpu.instruction_at(0) {
+            Ok(instruction) => instruction.to_string(),
+            Err(e) => e.to_string(),
+        };
+        if decoded != mnemonic {
+            mismatches.push(format!("{}: {} instead of {}", words, decoded, mnemonic));
+        }
+    }
+    assert_eq!(mismatches, Vec::<String>::new())
+}
+
+#[test]
+/// Assembled lines decode back to the same instruction
+fn tst_assemble_line() {
+    use breadboard::asm::assemble_line;
+
+    let cpu = ATmemory::init();
+    #[rustfmt::skip]
+    let table = [
+        ("add r0, r31", Instruction::ADD { dest: 0, src: 31 }),
+        ("CLR R17", Instruction::EOR { dest: 17, src: 17 }),
+        ("ldi r31, 0xFF", Instruction::LDI { dest: 31, value: 0xFF }),
+        ("cbr r16, $0F", Instruction::ANDI { dest: 16, value: 0xF0 }),
+        ("adiw r30, 63", Instruction::ADIW { dest: 30, value: 63 }),
+        ("out 0x3F, r1", Instruction::OUT { addr: 0x3F, src: 1 }),
+        ("in r31, 0b11", Instruction::IN { addr: 3, dest: 31 }),
+        ("sbi 31, 7", Instruction::SBI { dest: 31, bit: 7 }),
+        ("rjmp -1", Instruction::RJMP { offset: -1 }),
+        ("brne -64", Instruction::BRBC { offset: -64, bit: 1 }),
+        ("sec", Instruction::BSET { dest: 0 }),
+        ("cli", Instruction::BCLR { dest: 7 }),
+        ("reti", Instruction::RETI),
+    ];
+
+    for (line, expected) in table {
+        let bytes = assemble_line(line).unwrap();
+        let opcode = u16::from_le_bytes([bytes[0], bytes[1]]);
+        assert_eq!(cpu.decode(opcode), Ok(expected), "{}", line);
+    }
+    assert_eq!(
+        assemble_line("call 0x1234"),
+        Ok(vec![0x0E, 0x94, 0x34, 0x12])
+    );
+    assert!(assemble_line("foo r1").is_err())
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// PC highlight color strings
+fn tst_parse_color() {
+    use crate::gui::GUInterface;
+    use iced::Color;
+
+    assert_eq!(
+        GUInterface::parse_color("#FF8000"),
+        Some(Color::from_rgb8(0xFF, 0x80, 0x00))
+    );
+    assert_eq!(
+        GUInterface::parse_color(" #ff8000 "),
+        Some(Color::from_rgb8(0xFF, 0x80, 0x00))
+    );
+    assert_eq!(GUInterface::parse_color("FF8000"), None);
+    assert_eq!(GUInterface::parse_color("#FF80"), None);
+    assert_eq!(GUInterface::parse_color("#GG0000"), None);
+    assert_eq!(GUInterface::parse_color("#+F0000"), None)
+}
+
+#[test]
+/// Nested RCALLs count call depth, RETs bring it back
+fn tst_call_depth() {
+    let mut cpu = ATmemory::init();
+    // rcall +1; rjmp -1; rcall +0; ret (returns to itself, then to rjmp)
+    let program: Vec<u8> = vec![0x01, 0xD0, 0xFF, 0xCF, 0x00, 0xD0, 0x08, 0x95];
+    cpu.load_flash_from_vec(program).ok();
+    cpu.step().ok();
+    cpu.step().ok();
+    assert_eq!((cpu.call_depth(), cpu.pc()), (2, 3));
+    cpu.step().ok();
+    assert_eq!(cpu.call_depth(), 1);
+    cpu.step().ok();
+    assert_eq!((cpu.call_depth(), cpu.pc()), (0, 1));
+
+    cpu.load_flash_from_vec(vec![0x00, 0xD0]).ok();
+    cpu.step().ok();
+    cpu.reset();
+    assert_eq!(cpu.call_depth(), 0)
+}
+
+#[test]
+/// Fill flash and SRAM regions
+fn tst_fill() {
+    let mut cpu = ATmemory::init();
+    assert_eq!(cpu.fill(MemSpace::Data, 0x60, 0x70, 0xAA), Ok(()));
+    assert_eq!(cpu.fill(MemSpace::Flash, 0x3FFC, 0x4000, 0xFF), Ok(()));
+    assert!(cpu.memory()[0x60..0x70].iter().all(|b| *b == 0xAA));
+    assert_eq!((cpu.memory()[0x5F], cpu.memory()[0x70]), (0x00, 0x00));
+    assert_eq!(&cpu.flash()[0x3FFB..], &[0x00, 0xFF, 0xFF, 0xFF, 0xFF])
+}
+
+#[test]
+/// Data fills behave like stores: SP follows SPL/SPH and watchpoints fire
+fn tst_fill_io() {
+    let mut cpu = ATmemory::init();
+    cpu.toggle_watchpoint(0x5F);
+    assert_eq!(cpu.fill(MemSpace::Data, 0x5D, 0x60, 0x02), Ok(()));
+    assert_eq!(cpu.sp(), 0x0202);
+    assert_eq!(cpu.take_watch_hit(), Some((0x5F, 0x02)))
+}
+
+#[test]
+/// Reversed or out of range fills are rejected without touching memory
+fn tst_fill_out_of_range() {
+    let mut cpu = ATmemory::init();
+    let before = cpu.memory().to_vec();
+    assert!(cpu.fill(MemSpace::Data, 0x70, 0x60, 0xAA).is_err());
+    assert!(cpu.fill(MemSpace::Data, 0x400, 0x461, 0xAA).is_err());
+    assert!(cpu.fill(MemSpace::Flash, 0x4000, 0x4001, 0xFF).is_err());
+    assert_eq!(cpu.memory(), before.as_slice());
+    assert!(cpu.flash().iter().all(|b| *b == 0x00))
+}
+
+#[test]
+/// Loading and stepping report the kind of failure
+fn tst_emu_error_variants() {
+    let mut cpu = ATmemory::init();
+    assert!(matches!(
+        cpu.load_bin("/nonexistent/breadboard.bin"),
+        Err(EmuError::FileRead(_))
+    ));
+
+    let path = std::env::temp_dir().join("breadboard-tst-emu-error-variants.hex");
+    std::fs::write(&path, ":0400000001E0039583\n:04000400FFFF\n").unwrap();
+    let parse = cpu.load_hex(path.to_str().unwrap());
+    std::fs::write(&path, ":0400000001E0039584\n").unwrap();
+    let checksum = cpu.load_hex(path.to_str().unwrap());
+    std::fs::write(&path, ":0240000001E0DD\n").unwrap();
+    let bounds = cpu.load_hex(path.to_str().unwrap());
+    std::fs::remove_file(&path).ok();
+    assert!(matches!(parse, Err(EmuError::HexParse { line: 2, .. })));
+    assert!(matches!(checksum, Err(EmuError::Checksum { line: 1 })));
+    assert!(matches!(
+        bounds,
+        Err(EmuError::OutOfBounds { addr: 0x4000 })
+    ));
+
+    // 0xFFFF is not a valid opcode
+    cpu.load_flash_from_vec(vec![0x00, 0x00, 0xFF, 0xFF]).ok();
+    cpu.step().ok();
+    assert!(matches!(
+        cpu.step(),
+        Err(EmuError::DecodeFailed {
+            opcode: 0xFFFF,
+            pc: 1
+        })
+    ))
+}
+
+#[test]
+/// Decode errors name the opcode and the PC it was fetched from
+fn tst_decode_error_message() {
+    let mut cpu = ATmemory::init();
+    let mut program = vec![0x00; 0x0123 * 2];
+    // 0x9404 is a reserved opcode
+    program.extend([0x04, 0x94]);
+    cpu.load_flash_from_vec(program).ok();
+    cpu.run_until(0x0123 * 2).ok();
+    let err = cpu.step().unwrap_err().to_string();
+    assert_eq!(err, "Cannot decode 0x9404 at 0x0123")
+}
+
+#[test]
+/// Carry, half carry, overflow and borrow edge cases of ADD and SUB set the whole SREG
+fn tst_add_sub_flags() {
+    // (opcode of add/sub r16, r17, r16, r17, result, SREG)
+    #[rustfmt::skip]
+    let table = [
+        (0x0F01, 0xFF, 0x01, 0x00, 0b0010_0011), // Carry out, H, Z
+        (0x0F01, 0x0F, 0x01, 0x10, 0b0010_0000), // Half carry only
+        (0x0F01, 0x7F, 0x01, 0x80, 0b0010_1100), // Signed overflow, H, V, N
+        (0x0F01, 0x80, 0x80, 0x00, 0b0001_1011), // Carry and overflow, S, V, Z, C
+        (0x1B01, 0x00, 0x01, 0xFF, 0b0011_0101), // Borrow, H, S, N, C
+        (0x1B01, 0x80, 0x01, 0x7F, 0b0011_1000), // Signed overflow, H, S, V
+        (0x1B01, 0x10, 0x01, 0x0F, 0b0010_0000), // Half borrow only
+        (0x1B01, 0x10, 0x10, 0x00, 0b0000_0010), // Zero
+    ];
+
+    // ldi r16 + reg, value
+    let ldi = |reg: u16, value: u16| 0xE000 | ((value & 0xF0) << 4) | (reg << 4) | (value & 0x0F);
+    for (opcode, rd, rr, result, sreg) in table {
+        let mut cpu = ATmemory::init();
+        let mut program = Vec::new();
+        for word in [ldi(0, rd), ldi(1, rr), opcode] {
+            program.extend(word.to_le_bytes());
+        }
+        cpu.load_flash_from_vec(program).ok();
+        for _ in 0..3 {
+            cpu.step().ok();
+        }
+        assert_eq!(
+            (cpu.memory()[16], cpu.sreg()),
+            (result, sreg),
+            "{:#06X} with {:#04X}, {:#04X}",
+            opcode,
+            rd,
+            rr
+        );
+    }
+}
+
+#[test]
+/// MUL puts the product in r1:r0, C is its top bit and Z is set for a zero product
+fn tst_mul() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 0xFF; ldi r17, 0xFF; mul r16, r17
+    cpu.load_flash_from_vec(vec![0x0F, 0xEF, 0x1F, 0xEF, 0x01, 0x9F])
+        .ok();
+    for _ in 0..3 {
+        cpu.step().ok();
+    }
+    assert_eq!((cpu.memory()[0], cpu.memory()[1]), (0x01, 0xFE));
+    assert_eq!(cpu.sreg() & 0b11, 0b01);
+    assert_eq!(cpu.cycle_cnt(), 4)
+}
+
+#[test]
+/// r1 left nonzero by MUL is reported at the return, clearing it first or disabling the lint
+/// keeps the warnings empty
+fn tst_zero_register_lint() {
+    // ldi r16, 0x20; ldi r17, 0x10; rcall sub; end: rjmp end; sub: mul r16, r17; ret
+    let program = vec![
+        0x00, 0xE2, 0x10, 0xE1, 0x01, 0xD0, 0xFF, 0xCF, 0x01, 0x9F, 0x08, 0x95,
+    ];
+    let run = |program: Vec<u8>, lint: bool| {
+        let mut cpu = ATmemory::init();
+        cpu.set_lint_zero_register(lint);
+        cpu.load_flash_from_vec(program).ok();
+        for _ in 0..6 {
+            cpu.step().ok();
+        }
+        cpu.take_warnings()
+    };
+
+    assert_eq!(
+        run(program.clone(), true),
+        vec!["r1 is 0x02 at RET at 0x000A, left nonzero by MUL r16, r17 at 0x0008 without CLR r1"]
+    );
+    assert!(run(program.clone(), false).is_empty());
+
+    // sub: mul r16, r17; clr r1; ret
+    let mut cleared = program[..10].to_vec();
+    cleared.extend([0x11, 0x24, 0x08, 0x95]);
+    assert!(run(cleared, true).is_empty())
+}
+
+#[test]
+/// Zeroed counters only count the segment stepped after, registers and PC are kept
+fn tst_reset_counters() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 1; push r16; push r16
+    cpu.load_flash_from_vec(vec![0x01, 0xE0, 0x0F, 0x93, 0x0F, 0x93])
+        .ok();
+    cpu.step().ok();
+    cpu.reset_counters();
+    assert_eq!((cpu.cycle_cnt(), cpu.exec_count_at(0), cpu.pc()), (0, 0, 1));
+    cpu.step().ok();
+    cpu.step().ok();
+    assert_eq!(cpu.cycle_cnt(), 4);
+    assert_eq!((cpu.memory()[16], cpu.exec_count_at(1)), (1, 1))
+}
+
+#[test]
+/// Hit-count breakpoint lets the first passes through and stops on the third
+fn tst_breakpoint_hit_count() {
+    let mut cpu = ATmemory::init();
+    // inc r16; rjmp -2
+    cpu.load_flash_from_vec(vec![0x03, 0x95, 0xFE, 0xCF]).ok();
+    cpu.set_breakpoint(0, 3);
+
+    cpu.run_to_completion(&[]).unwrap();
+    assert_eq!((cpu.pc(), cpu.memory()[16]), (0, 3));
+    cpu.run_to_completion(&[]).unwrap();
+    assert_eq!(cpu.memory()[16], 6);
+
+    assert!(!cpu.check_breakpoint());
+    assert_eq!(cpu.breakpoints()[&0].hits, 1);
+    cpu.reset();
+    assert_eq!(cpu.breakpoints()[&0].hits, 0);
+    assert!(cpu.remove_breakpoint(0));
+    assert!(!cpu.remove_breakpoint(0));
+
+    // Other run loops count passes too
+    cpu.set_breakpoint(0, 2);
+    assert_eq!(cpu.step_n(10), Ok(4));
+    assert!(cpu.remove_breakpoint(0));
+    cpu.set_breakpoint(0, 1);
+    cpu.run_until(0x0010).unwrap();
+    assert_eq!(cpu.pc(), 0)
+}
+
+#[test]
+/// Malformed config is backed up next to the original, defaults and a warning are returned
+fn tst_corrupt_config_backup() {
+    let dir = std::env::temp_dir().join(format!("breadboard-config-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.toml");
+    std::fs::write(&path, "bridge_address = [").unwrap();
+
+    let (config, warning) = Config::load_from(&path).unwrap();
+    assert_eq!(config.bridge_address, Config::default().bridge_address);
+    assert!(warning.unwrap().contains("config.toml.bak"));
+    assert!(!path.exists());
+    assert_eq!(
+        std::fs::read_to_string(dir.join("config.toml.bak")).unwrap(),
+        "bridge_address = ["
+    );
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+/// Config matching the file on disk is clean, any changed setting makes it unsaved
+fn tst_config_unsaved() {
+    let dir = std::env::temp_dir().join(format!("breadboard-unsaved-{}", std::process::id()));
+    let path = dir.join("config.toml");
+    let mut config = Config::default();
+    assert!(!config.is_unsaved_at(&path));
+
+    config.save_to(&path).unwrap();
+    assert!(!config.is_unsaved_at(&path));
+    config.display.hex_font_size = 20;
+    assert!(config.is_unsaved_at(&path));
+    config.save_to(&path).unwrap();
+    assert!(!config.is_unsaved_at(&path));
+
+    // Checking a corrupt file leaves it where it is
+    std::fs::write(&path, "not toml [").unwrap();
+    assert!(config.is_unsaved_at(&path));
+    assert!(path.exists() && !path.with_extension("toml.bak").exists());
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+/// Labels and source lines attach to the next instruction, comments to their own line
+fn tst_objdump_listing() {
+    use breadboard::listing::parse_objdump_lst;
+    let lst = "
+blink.elf:     file format elf32-avr
+
+
+Disassembly of section .text:
+
+00000000 <main>:
+main.c:4
+  DDRB = 0xFF;
+   0:\t8f ef       \tldi\tr24, 0xFF\t; 255
+   2:\t87 bb       \tout\t0x17, r24\t; 23
+   4:\t00 00       \tnop
+
+00000006 <loop>:
+   6:\tff cf       \trjmp\t.-2      \t; 0x6 <loop>
+";
+    let annotations = parse_objdump_lst(lst);
+    assert_eq!(annotations.len(), 3);
+    assert_eq!(annotations[&0], "main: main.c:4 DDRB = 0xFF; ; 255");
+    assert_eq!(annotations[&1], "; 23");
+    assert!(!annotations.contains_key(&2));
+    assert_eq!(annotations[&3], "loop: ; 0x6 <loop>")
+}
+
+#[test]
+/// avr-gdb stub answers `g` over TCP with registers, SREG, SP and the PC byte address, NAKs bad
+/// checksums and rejects memory reads past the end
+fn tst_gdb_registers() {
+    use breadboard::gdb::{self, GdbStub};
+    use std::io::{Read, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let mut cpu = ATmemory::init();
+        // ldi r16, 0x2A
+        cpu.load_flash_from_vec(vec![0x0A, 0xE2]).unwrap();
+        cpu.step().unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        GdbStub::new(cpu).serve(stream).unwrap();
+    });
+
+    let mut client = std::net::TcpStream::connect(addr).unwrap();
+    // Corrupted packet is NAKed and skipped
+    client.write_all(b"$g#00").unwrap();
+    client.write_all(gdb::frame("g").as_bytes()).unwrap();
+    client.write_all(gdb::frame("D").as_bytes()).unwrap();
+    let mut reply = String::new();
+    client.read_to_string(&mut reply).unwrap();
+    server.join().unwrap();
+
+    let (registers, _) = reply.strip_prefix("-+$").unwrap().split_once('#').unwrap();
+    let bytes: Vec<u8> = (0..registers.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(&registers[idx..idx + 2], 16).unwrap())
+        .collect();
+    assert_eq!(bytes.len(), 39);
+    assert_eq!(bytes[16], 0x2A);
+    assert_eq!(u16::from_le_bytes([bytes[33], bytes[34]]), 0x45F);
+    assert_eq!(
+        u32::from_le_bytes([bytes[35], bytes[36], bytes[37], bytes[38]]),
+        2
+    );
+    assert!(reply.ends_with(&gdb::frame("OK")));
+
+    // Address plus length past usize::MAX is an error, not an overflow
+    let mut stub = GdbStub::new(ATmemory::init());
+    assert_eq!(stub.handle("mffffffffffffffff,2"), "E01");
+    assert_eq!(stub.handle("m800000,2"), "0000")
+}
+
+#[test]
+/// Scenario sets up registers and EEPROM, runs the program to its end and checks the result
+fn tst_scenario() {
+    use breadboard::scenario::Scenario;
+    let dir = std::env::temp_dir().join(format!("breadboard-scenario-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    // ldi r16, 0; loop: add r16, r17; dec r17; brne loop; end: rjmp end
+    std::fs::write(
+        dir.join("sum.bin"),
+        [0x00, 0xE0, 0x01, 0x0F, 0x1A, 0x95, 0xE9, 0xF7, 0xFF, 0xCF],
+    )
+    .unwrap();
+    let path = dir.join("sum.toml");
+    std::fs::write(
+        &path,
+        "program = \"sum.bin\"\neeprom = [0xAA, 0x55]\n[registers]\nr17 = 10\n[expect]\nr16 = 55\nr17 = 0\n",
+    )
+    .unwrap();
+
+    let scenario = Scenario::load(&path).unwrap();
+    let mut cpu = ATmemory::init();
+    assert_eq!(scenario.apply(&mut cpu), Ok(()));
+    assert_eq!(
+        (cpu.memory()[17], cpu.eeprom()[0], cpu.eeprom()[1]),
+        (10, 0xAA, 0x55)
+    );
+    assert_eq!(scenario.run(&mut cpu), Ok(()));
+    assert_eq!(cpu.pc(), 4);
+    assert_eq!(scenario.check(&cpu), Vec::<String>::new());
+
+    cpu.set_register(16, 54).ok();
+    assert_eq!(scenario.check(&cpu), vec!["r16: expected 0x37, got 0x36"]);
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+/// Register pairs read high:low by default, low:high swaps the names and the bytes
+fn tst_pair_order() {
+    use breadboard::config::PairOrder;
+    assert_eq!(PairOrder::default(), PairOrder::HighLow);
+    assert_eq!(
+        PairOrder::HighLow.format_pair(30, 0x1234),
+        "r31:r30 = 0x1234"
+    );
+    assert_eq!(
+        PairOrder::LowHigh.format_pair(30, 0x1234),
+        "r30:r31 = 0x3412"
+    );
+    assert_eq!(
+        PairOrder::LowHigh.format_pair(26, 0x00FF),
+        "r26:r27 = 0xFF00"
+    )
+}
+
+#[test]
+/// SPM fills the page buffer from r1:r0, a page write copies the buffer into flash
+fn tst_spm_page_write() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 0x01; out SPMCR, r16; spm
+    // ldi r16, 0x05; out SPMCR, r16; spm
+    let program: Vec<u8> = vec![
+        0x01, 0xE0, 0x07, 0xBF, 0xE8, 0x95, 0x05, 0xE0, 0x07, 0xBF, 0xE8, 0x95,
+    ];
+    cpu.load_flash_from_vec(program).ok();
+    cpu.set_register(0, 0x34).ok();
+    cpu.set_register(1, 0x12).ok();
+    cpu.set_register(30, 0x82).ok();
+    cpu.step_n(3).ok();
+    assert_eq!(&cpu.flash()[0x80..0x84], &[0x00; 4]);
+    assert_eq!(cpu.memory()[0x57], 0x00);
+    cpu.step_n(3).ok();
+    assert_eq!(&cpu.flash()[0x80..0x84], &[0xFF, 0xFF, 0x34, 0x12]);
+    assert!(cpu.flash()[0x84..0x100].iter().all(|b| *b == 0xFF));
+    assert_eq!(&cpu.flash()[..2], &[0x01, 0xE0]);
+
+    cpu.set_device(Device::ATtiny10);
+    assert!(cpu.decode(0x95E8).is_err())
+}
+
+#[test]
+/// Registers, PC and SP can be changed while paused, invalid targets leave the CPU untouched
+fn tst_state_setters() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 1 at 0x0000, inc r16 at word 0x10
+    let mut program = vec![0x01, 0xE0];
+    program.resize(0x20, 0x00);
+    program.extend([0x03, 0x95]);
+    cpu.load_flash_from_vec(program).ok();
+
+    assert_eq!(cpu.set_register(16, 0x41), Ok(()));
+    assert_eq!(cpu.set_pc(0x10), Ok(()));
+    cpu.step().ok();
+    assert_eq!((cpu.memory()[16], cpu.pc()), (0x42, 0x11));
+
+    assert_eq!(cpu.set_sp(0x300), Ok(()));
+    assert_eq!(cpu.sp(), 0x300);
+
+    assert_eq!(
+        cpu.set_register(32, 0xFF),
+        Err(EmuError::OutOfBounds { addr: 32 })
+    );
+    assert_eq!(
+        cpu.set_pc(0x2000),
+        Err(EmuError::OutOfBounds { addr: 0x4000 })
+    );
+    assert_eq!(cpu.set_sp(0x5F), Err(EmuError::OutOfBounds { addr: 0x5F }));
+    assert_eq!(
+        cpu.set_sp(0x460),
+        Err(EmuError::OutOfBounds { addr: 0x460 })
+    );
+    assert_eq!(
+        (cpu.pc(), cpu.sp(), cpu.memory()[0x20]),
+        (0x11, 0x300, 0x00)
+    )
+}
+
+#[test]
+/// Hex files loaded one after another are merged, each only writes its own records
+fn tst_load_hex_segments() {
+    let dir = std::env::temp_dir().join(format!("breadboard-segments-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    // Application: ldi r16, 0xFF at 0x0000, bootloader: inc r16 at 0x0100
+    std::fs::write(dir.join("app.hex"), ":020000000FEF00\n:00000001FF\n").unwrap();
+    std::fs::write(dir.join("boot.hex"), ":02010000039565\n:00000001FF\n").unwrap();
+
+    let mut cpu = ATmemory::init();
+    for file in ["app.hex", "boot.hex"] {
+        let path = dir.join(file);
+        assert_eq!(cpu.load_hex(path.to_str().unwrap()), Ok(()));
+    }
+    assert_eq!(&cpu.flash()[..2], &[0x0F, 0xEF]);
+    assert_eq!(&cpu.flash()[0x100..0x102], &[0x03, 0x95]);
+    assert!(cpu.flash()[2..0x100].iter().all(|b| *b == 0x00));
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+/// Extended segment and linear address records move the data records after them
+fn tst_load_hex_extended_address() {
+    let mut cpu = ATmemory::init();
+    // Segment 0x0010 moves inc r16 to 0x0100, the start address record is skipped
+    let text = ":020000020010EC :02000000039566 :0400000500000000F7 :00000001FF";
+    assert_eq!(cpu.load_from_text(text), Ok(()));
+    assert_eq!(&cpu.flash()[0x100..0x102], &[0x03, 0x95]);
+    assert_eq!(&cpu.flash()[..2], &[0x00, 0x00]);
+
+    // Linear 0x0001 is 64 KiB up, past the end of flash, nothing is erased or written
+    let text = ":020000040001F9 :02000000039566 :00000001FF";
+    assert!(cpu.load_from_text(text).is_err());
+    assert_eq!(&cpu.flash()[0x100..0x102], &[0x03, 0x95]);
+    assert!(cpu.load_from_text(":0100000210ED").is_err())
+}
+
+#[test]
+/// Reloading a rebuilt image reports exactly the flash bytes the rebuild changed
+fn tst_reload_changes() {
+    use breadboard::snapshot::changed_addresses;
+    let dir = std::env::temp_dir().join(format!("breadboard-reload-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("main.bin");
+    // ldi r16, 0xFF; inc r16
+    std::fs::write(&path, [0x0F, 0xEF, 0x03, 0x95]).unwrap();
+    let mut cpu = ATmemory::init();
+    cpu.load_bin(path.to_str().unwrap()).ok();
+    let previous = cpu.flash().to_vec();
+
+    // ldi r16, 0x10; inc r16; nop
+    std::fs::write(&path, [0x00, 0xE1, 0x03, 0x95, 0x00, 0x00]).unwrap();
+    let mut cpu = ATmemory::init();
+    cpu.load_bin(path.to_str().unwrap()).ok();
+    assert_eq!(changed_addresses(&previous, cpu.flash()), vec![0, 1]);
+    assert_eq!(changed_addresses(&[0x01], &[0x01, 0x02]), vec![1]);
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+/// Targets past flash wrap around the 13-bit program counter instead of leaving flash
+fn tst_pc_wraps() {
+    let mut cpu = ATmemory::init();
+    // jmp 0x2005 (word address)
+    cpu.load_flash_from_vec(vec![0x0C, 0x94, 0x05, 0x20]).ok();
+    assert_eq!(cpu.step(), Ok(3));
+    assert_eq!(cpu.pc(), 0x0005);
+
+    // rjmp -2048 from word 0
+    cpu.load_flash_from_vec(vec![0x00, 0xC8]).ok();
+    cpu.reset();
+    assert_eq!(cpu.step(), Ok(2));
+    assert_eq!(cpu.pc(), 0x1801);
+    assert!(cpu.step().is_ok());
+
+    // Resets wrap the same way
+    cpu.set_start_pc(0x2001);
+    cpu.reset();
+    assert_eq!(cpu.pc(), 0x0001);
+    cpu.set_start_pc(0x0000);
+
+    cpu.set_device(Device::ATmega128);
+    cpu.load_flash_from_vec(vec![0x0C, 0x94, 0x05, 0x20]).ok();
+    cpu.step().ok();
+    assert_eq!(cpu.pc(), 0x2005)
+}
+
+#[test]
+/// A bad word pauses by default and runs as NOP when skipping is enabled
+fn tst_skip_undecodable() {
+    // ldi r16, 1; reserved 0x9404; ldi r17, 2
+    let program = vec![0x01, 0xE0, 0x04, 0x94, 0x12, 0xE0];
+
+    let mut cpu = ATmemory::init();
+    cpu.load_flash_from_vec(program.clone()).ok();
+    cpu.step().ok();
+    assert_eq!(
+        cpu.step(),
+        Err(EmuError::DecodeFailed {
+            opcode: 0x9404,
+            pc: 1
+        })
+    );
+    assert_eq!(cpu.pc(), 1);
+
+    let mut cpu = ATmemory::init();
+    cpu.set_skip_undecodable(true);
+    cpu.load_flash_from_vec(program).ok();
+    for _ in 0..3 {
+        cpu.step().unwrap();
+    }
+    assert_eq!((cpu.pc(), cpu.memory()[17]), (3, 2));
+    assert_eq!(cpu.take_warnings().len(), 1)
+}
+
+#[test]
+/// Fingerprint is the CRC-32 of the loaded program and follows edits
+fn tst_flash_fingerprint() {
+    let mut cpu = ATmemory::init();
+    cpu.load_flash_from_vec(b"123456789".to_vec()).ok();
+    assert_eq!(cpu.flash_fingerprint(), 0xCBF43926);
+    cpu.fill(MemSpace::Flash, 4, 5, b'0').ok();
+    assert_ne!(cpu.flash_fingerprint(), 0xCBF43926)
+}
+
+#[test]
+/// SRAM starts and resets filled with the configured pattern, registers and I/O below SP stay
+/// zeroed
+fn tst_sram_init_pattern() {
+    let mut cpu = ATmemory::init_with_sram(SramInit::Pattern(0xAA));
+    assert!(cpu.memory()[0x60..].iter().all(|b| *b == 0xAA));
+    assert!(cpu.memory()[..0x5D].iter().all(|b| *b == 0x00));
+    cpu.fill(MemSpace::Data, 0x100, 0x101, 0x00).ok();
+    cpu.reset();
+    assert_eq!(cpu.memory()[0x100], 0xAA);
+    cpu.set_device(Device::ATmega128);
+    assert_eq!((cpu.memory()[0xFF], cpu.memory()[0x100]), (0x00, 0xAA));
+    cpu.set_device(Device::ATtiny10);
+    assert_eq!((cpu.memory()[0x3F], cpu.memory()[0x40]), (0x00, 0xAA));
+    assert_eq!((cpu.memory()[0x5C], cpu.sreg()), (0xAA, 0x00))
+}
+
+#[test]
+/// Random SRAM is the same for every run
+fn tst_sram_init_random() {
+    let cpu = ATmemory::init_with_sram(SramInit::Random);
+    let other = ATmemory::init_with_sram(SramInit::Random);
+    assert_eq!(cpu.memory(), other.memory());
+    assert!(
+        cpu.memory()[0x60..]
+            .iter()
+            .any(|b| *b != cpu.memory()[0x60])
+    )
+}
+
+#[test]
+/// Diff of two runs reports only the register that differs
+fn tst_snapshot_diff() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 5
+    cpu.load_flash_from_vec(vec![0x05, 0xE0]).ok();
+    cpu.step().ok();
+    let reference = cpu.snapshot();
+
+    let mut other = ATmemory::init();
+    // ldi r16, 7
+    other.load_flash_from_vec(vec![0x07, 0xE0]).ok();
+    other.step().ok();
+    assert_eq!(
+        reference.diff(&other.snapshot()),
+        vec![StateDelta::Register {
+            reg: 16,
+            old: 5,
+            new: 7
+        }]
+    );
+    assert!(reference.diff(&cpu.snapshot()).is_empty())
+}
+
+#[test]
+/// Loop body executes as many times as the loop counter says
+fn tst_exec_counts() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 5; dec r16; brne -2; nop
+    let program: Vec<u8> = vec![0x05, 0xE0, 0x0A, 0x95, 0xF1, 0xF7, 0x00, 0x00];
+    cpu.load_flash_from_vec(program).ok();
+    cpu.run_until(6).ok();
+    assert_eq!(
+        (0..4)
+            .map(|addr| cpu.exec_count_at(addr))
+            .collect::<Vec<_>>(),
+        vec![1, 5, 5, 0]
+    );
+    cpu.reset();
+    assert_eq!(cpu.max_exec_count(), 0)
+}
+
+#[test]
+/// STD and LDD address Y and Z plus displacement and leave the pointers alone
+fn tst_ldd_std() {
+    let mut cpu = ATmemory::init();
+    // ldi r28, 0x00; ldi r29, 0x01; ldi r16, 0x42; std Y+5, r16
+    // ldi r30, 0x03; ldi r31, 0x01; ldd r17, Z+2
+    let program: Vec<u8> = vec![
+        0xC0, 0xE0, 0xD1, 0xE0, 0x02, 0xE4, 0x0D, 0x83, 0xE3, 0xE0, 0xF1, 0xE0, 0x12, 0x81,
+    ];
+    cpu.load_flash_from_vec(program).ok();
+    for _ in 0..7 {
+        cpu.step().ok();
+    }
+    assert_eq!(
+        (cpu.memory()[0x105], cpu.memory()[17], cpu.yp(), cpu.zp()),
+        (0x42, 0x42, 0x0100, 0x0103)
+    );
+    assert_eq!(
+        cpu.decode(0xAE2F),
+        Ok(Instruction::STD {
+            src: 2,
+            ptr: 28,
+            disp: 0x3F
+        })
+    )
+}
+
+#[test]
+/// Reduced core STS/LDS decode the scattered 7-bit address, the ATmega16 keeps LDD/STD
+fn tst_reduced_sts_lds() {
+    let mut cpu = ATmemory::init();
+    assert!(matches!(cpu.decode(0xA900), Ok(Instruction::LDD { .. })));
+
+    cpu.set_device(Device::ATtiny10);
+    // sts 0x40, r16; sts 0xBF, r16; lds r17, 0x40
+    assert_eq!(
+        cpu.decode(0xA900),
+        Ok(Instruction::STS16 {
+            addr: 0x40,
+            src: 16
+        })
+    );
+    assert_eq!(
+        cpu.decode(0xAE0F),
+        Ok(Instruction::STS16 {
+            addr: 0xBF,
+            src: 16
+        })
+    );
+    assert_eq!(
+        cpu.decode(0xA110),
+        Ok(Instruction::LDS16 {
+            dest: 17,
+            addr: 0x40
+        })
+    );
+
+    // ldi r16, 0x5A; sts 0x40, r16; lds r17, 0x40
+    cpu.load_flash_from_vec(vec![0x0A, 0xE5, 0x00, 0xA9, 0x10, 0xA1])
+        .ok();
+    for _ in 0..3 {
+        cpu.step().ok();
+    }
+    assert_eq!((cpu.memory()[0x40], cpu.memory()[17]), (0x5A, 0x5A))
+}
+
+#[test]
+/// Flag masks match the SREG bits execute actually changes over random operands
+fn tst_flags_affected() {
+    #[rustfmt::skip]
+    let table = [
+        (0x0F01, 0b0011_1111), // add r16, r17
+        (0x1F01, 0b0011_1111), // adc r16, r17
+        (0x1B01, 0b0011_1111), // sub r16, r17
+        (0x1701, 0b0011_1111), // cp r16, r17
+        (0x9501, 0b0011_1111), // neg r16
+        (0x9601, 0b0001_1111), // adiw r24, 1
+        (0x9505, 0b0001_1111), // asr r16
+        (0x9500, 0b0001_1111), // com r16
+        (0x9506, 0b0001_1111), // lsr r16
+        (0x9507, 0b0001_1111), // ror r16
+        (0x2301, 0b0001_1110), // and r16, r17
+        (0x700F, 0b0001_1110), // andi r16, 0x0F
+        (0x2701, 0b0001_1110), // eor r16, r17
+        (0x2B01, 0b0001_1110), // or r16, r17
+        (0x6F00, 0b0001_1110), // ori r16, 0xF0
+        (0x9503, 0b0001_1110), // inc r16
+        (0x950A, 0b0001_1110), // dec r16
+        (0x9502, 0b0000_0000), // swap r16
+        (0x2F01, 0b0000_0000), // mov r16, r17
+        (0xE001, 0b0000_0000), // ldi r16, 1
+        (0x9408, 0b0000_0001), // sec
+        (0x9498, 0b0000_0010), // clz
+    ];
+
+    let mut rng = rand::rng();
+    for (opcode, mask) in table {
+        let mut cpu = ATmemory::init();
+        let instruction = cpu.decode(opcode).unwrap();
+        assert_eq!(instruction.flags_affected(), mask, "{}", instruction);
+
+        let mut changed = 0;
+        for _ in 0..256 {
+            cpu.load_flash_from_vec(opcode.to_le_bytes().to_vec()).ok();
+            for reg in 16..32 {
+                cpu.set_register(reg, rng.random_range(0..=255)).ok();
+            }
+            let before: u8 = rng.random_range(0..=0x7F); // I stays clear
+            cpu.set_sreg(before);
+            cpu.step().unwrap();
+            changed |= before ^ cpu.sreg();
+        }
+        assert_eq!(changed, mask, "{}", instruction);
+    }
+}
+
+#[test]
+/// IN reaches SREG as I/O 0x3F and LD reads SRAM from 0x0060 with no extended I/O gap
+fn tst_io_and_sram_map() {
+    let mut cpu = ATmemory::init();
+    cpu.set_sreg(0b1000_0001);
+    cpu.fill(MemSpace::Data, 0x0060, 0x0061, 0xA5).ok();
+    cpu.set_sp(0x0400).ok();
+    // in r16, 0x3F; ldi r28, 0x60; ldi r29, 0x00; ld r17, Y; in r18, 0x3E
+    cpu.load_flash_from_vec(vec![
+        0x0F, 0xB7, 0xC0, 0xE6, 0xD0, 0xE0, 0x18, 0x81, 0x2E, 0xB7,
+    ])
+    .ok();
+    for _ in 0..5 {
+        cpu.step().ok();
+    }
+    assert_eq!(
+        (cpu.memory()[16], cpu.memory()[17], cpu.memory()[18]),
+        (0b1000_0001, 0xA5, 0x04)
+    )
+}
+
+#[test]
+/// ELPM reads past 64K bytes of flash through RAMPZ:Z, the ATmega16 does not decode it
+fn tst_elpm_rampz() {
+    let mut cpu = ATmemory::init();
+    assert!(cpu.decode(0x9117).is_err());
+
+    cpu.set_device(Device::ATmega128);
+    // ldi r16, 1; out RAMPZ, r16; ldi r30, 0x05; ldi r31, 0x00; elpm r17, Z+
+    let mut program = vec![0x01, 0xE0, 0x0B, 0xBF, 0xE5, 0xE0, 0xF0, 0xE0, 0x17, 0x91];
+    program.resize(0x1_0006, 0x00);
+    program[0x1_0005] = 0x5A;
+    cpu.load_flash_from_vec(program).unwrap();
+    for _ in 0..5 {
+        cpu.step().unwrap();
+    }
+    assert_eq!(cpu.memory()[17], 0x5A);
+    // Z+ carries into RAMPZ
+    assert_eq!((cpu.zp(), cpu.memory()[0x5B]), (0x0006, 0x01))
+}
+
+#[test]
+/// Register dependencies of two-operand, immediate and flag-only instructions
+fn tst_instruction_reads_writes() {
+    let add = Instruction::ADD { dest: 16, src: 17 };
+    assert_eq!((add.reads(), add.writes()), (vec![16, 17], vec![16]));
+
+    let ldi = Instruction::LDI {
+        dest: 20,
+        value: 0x42,
+    };
+    assert_eq!((ldi.reads(), ldi.writes()), (vec![], vec![20]));
+
+    let nop = Instruction::NOP;
+    assert_eq!((nop.reads(), nop.writes()), (vec![], vec![]));
+
+    // sec
+    let sec = Instruction::BSET { dest: 0 };
+    assert_eq!((sec.reads(), sec.writes()), (vec![], vec![]))
+}
+
+#[test]
+/// SREG saved by an interrupt prologue survives being clobbered and restored by the epilogue
+fn tst_save_restore_sreg() {
+    let mut cpu = ATmemory::init();
+    // sec; sez; in r0, SREG; push r0; clc; clz; sei; pop r0; out SREG, r0
+    let program: Vec<u8> = vec![
+        0x08, 0x94, 0x18, 0x94, 0x0F, 0xB6, 0x0F, 0x92, 0x88, 0x94, 0x98, 0x94, 0x78, 0x94, 0x0F,
+        0x90, 0x0F, 0xBE,
+    ];
+    cpu.load_flash_from_vec(program).ok();
+    for _ in 0..7 {
+        cpu.step().ok();
+    }
+    assert_eq!(cpu.sreg(), 0b1000_0000);
+    for _ in 0..2 {
+        cpu.step().ok();
+    }
+    assert_eq!((cpu.sreg(), cpu.sp(), cpu.pc()), (0b0000_0011, 0x45F, 9))
+}
+
+#[test]
+/// Fast forward runs exactly N instructions
+fn tst_step_n() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 0; inc r16; rjmp -2
+    let program: Vec<u8> = vec![0x00, 0xE0, 0x03, 0x95, 0xFE, 0xCF];
+    cpu.load_flash_from_vec(program).ok();
+    assert_eq!(cpu.step_n(21), Ok(21));
+    assert_eq!((cpu.memory()[16], cpu.pc()), (10, 1));
+
+    cpu.toggle_watchpoint(16);
+    assert_eq!(cpu.step_n(100), Ok(1));
+    assert_eq!(cpu.memory()[16], 11)
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Output pins show PORTx, input pins show PINx
+fn tst_pin_states() {
+    use crate::gui::{GUInterface, PinState};
+    let states = GUInterface::pin_states(0b0000_0011, 0b0000_0110, 0b1000_0001);
+    assert_eq!(
+        states[..3],
+        [
+            PinState::Output(false),
+            PinState::Output(true),
+            PinState::Input(false)
+        ]
+    );
+    assert_eq!(states[7], PinState::Input(true))
+}
+
+#[test]
+/// Load .eep records into EEPROM, records past its end are rejected
+fn tst_load_eep() {
+    let path = std::env::temp_dir().join("breadboard-tst-load-eep.eep");
+    std::fs::write(&path, ":0400000048656C6C77\n:0201FE00ABCD87\n:00000001FF\n").unwrap();
+    let mut cpu = ATmemory::init();
+    let loaded = cpu.load_eep(path.to_str().unwrap());
+    assert_eq!(loaded, Ok(()));
+    assert_eq!(&cpu.eeprom()[..4], b"Hell");
+    assert_eq!(&cpu.eeprom()[0x1FE..], &[0xAB, 0xCD]);
+
+    std::fs::write(&path, ":0201FF000102FB\n").unwrap();
+    let overflow = cpu.load_eep(path.to_str().unwrap());
+    std::fs::remove_file(&path).ok();
+    assert_eq!(overflow, Err(EmuError::OutOfBounds { addr: 0x200 }));
+    assert!(cpu.flash().iter().all(|b| *b == 0x00))
+}
+
+#[test]
+/// Endless recursion stops at the bottom of SRAM instead of overwriting I/O registers
+fn tst_rcall_stack_overflow() {
+    let mut cpu = ATmemory::init();
+    // rcall -1
+    cpu.load_flash_from_vec(vec![0xFF, 0xDF]).ok();
+    let result = cpu.step_n(1000);
+    assert!(matches!(
+        result,
+        Err(EmuError::StoppedAfter { error, .. })
+            if *error == EmuError::StackOverflow { pc: 0, sp: 0x61 }
+    ));
+    assert_eq!(cpu.step(), Err(EmuError::StackOverflow { pc: 0, sp: 0x61 }));
+    assert_eq!((cpu.call_depth(), cpu.sp()), (511, 0x61));
+    // Below the stack only SPL holds anything, the low byte of SP
+    assert_eq!(cpu.memory()[0x5D], 0x61);
+    assert!(cpu.memory()[..0x5D].iter().all(|b| *b == 0x00));
+    assert!(cpu.memory()[0x5E..0x61].iter().all(|b| *b == 0x00))
+}
+
+#[test]
+/// A call in the last flash word returns to word 0 instead of overflowing PC
+fn tst_call_wraps_return() {
+    let mut cpu = ATmemory::init();
+    cpu.set_device(Device::ATmega128);
+    // ret at word 0, rcall .+0 at word 0xFFFF
+    cpu.fill(MemSpace::Flash, 0x0000, 0x0001, 0x08).ok();
+    cpu.fill(MemSpace::Flash, 0x0001, 0x0002, 0x95).ok();
+    cpu.fill(MemSpace::Flash, 0x1FFFF, 0x20000, 0xD0).ok();
+    cpu.set_pc(0xFFFF).unwrap();
+    assert_eq!(cpu.step_over(), Ok(()));
+    assert_eq!((cpu.pc(), cpu.sp()), (0, *cpu.stack_region().end()))
+}
+
+#[test]
+/// A return with one byte on the stack fails before popping anything
+fn tst_ret_stack_underflow() {
+    let mut cpu = ATmemory::init();
+    // push r16; ret
+    cpu.load_flash_from_vec(vec![0x0F, 0x93, 0x08, 0x95]).ok();
+    assert!(cpu.step().is_ok());
+    assert_eq!(cpu.step(), Err(EmuError::StackUnderflow { sp: 0x045E }));
+    assert_eq!((cpu.pc(), cpu.sp()), (1, 0x045E));
+    assert_eq!(&cpu.memory()[0x5D..0x5F], &[0x5E, 0x04])
+}
+
+#[test]
+/// Instructions print in assembler syntax that assembles back to the same opcode
+fn tst_instruction_display() {
+    use breadboard::asm::assemble_line;
+    let cpu = ATmemory::init();
+    for opcode in [
+        0x1F01, 0xEF1F, 0x9503, 0xB60F, 0xF7F1, 0xCFFE, 0x9A1B, 0x9508,
+    ] {
+        let text = format!("{}", cpu.decode(opcode).unwrap());
+        assert_eq!(
+            assemble_line(&text),
+            Ok(opcode.to_le_bytes().to_vec()),
+            "{}",
+            text
+        );
+    }
+    assert_eq!(format!("{}", cpu.decode(0xEF1F).unwrap()), "LDI r17, 0xFF")
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Toolbar shows the instruction at PC once a program is loaded
+fn tst_next_instruction_label() {
+    use crate::gui::GUInterface;
+    let mut cpu = ATmemory::init();
+    assert_eq!(GUInterface::next_instruction_label(&cpu, false), "Next: —");
+    // ldi r17, 255
+    cpu.load_flash_from_vec(vec![0x1F, 0xEF]).ok();
+    assert_eq!(
+        GUInterface::next_instruction_label(&cpu, true),
+        "Next: LDI r17, 0xFF"
+    )
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Selected region is decoded on its own, data words show up as `.dw`
+fn tst_disassembly_lines() {
+    use crate::gui::GUInterface;
+    use breadboard::config::AddressBase;
+    let mut cpu = ATmemory::init();
+    // nop; ldi r16, 0xFF; add r16, r17; reserved 0x9404; nop
+    cpu.load_flash_from_vec(vec![
+        0x00, 0x00, 0x0F, 0xEF, 0x01, 0x0F, 0x04, 0x94, 0x00, 0x00,
+    ])
+    .ok();
+    assert_eq!(
+        GUInterface::disassembly_lines(
+            &cpu,
+            2,
+            6,
+            &std::collections::HashMap::new(),
+            AddressBase::Hexadecimal
+        ),
+        vec![
+            "0002: LDI r16, 0xFF",
+            "0004: ADD r16, r17",
+            "0006: .dw 0x9404"
+        ]
+    );
+    assert_eq!(
+        GUInterface::disassembly_lines(
+            &cpu,
+            2,
+            2,
+            &std::collections::HashMap::new(),
+            AddressBase::Octal
+        ),
+        vec!["000002: LDI r16, 0xFF"]
+    )
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Inspecting an ADD word lists its encoding, register use and the flags it sets
+fn tst_inspect_add() {
+    use crate::gui::GUInterface;
+    use breadboard::config::AddressBase;
+    let mut cpu = ATmemory::init();
+    // nop; add r16, r17
+    cpu.load_flash_from_vec(vec![0x00, 0x00, 0x01, 0x0F]).ok();
+    assert_eq!(
+        GUInterface::inspect_lines(&cpu, 1, AddressBase::Hexadecimal),
+        vec![
+            "0001: ADD r16, r17",
+            "Encoding: 0F01",
+            "Cycles: 1",
+            "Reads: r16 r17",
+            "Writes: r16",
+            "Flags: H S V N Z C",
+        ]
+    )
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Stack usage counts down from the top of SRAM and never goes negative
+fn tst_stack_used() {
+    use crate::gui::GUInterface;
+
+    assert_eq!(GUInterface::stack_used(0x045F, 0x045F), 0);
+    assert_eq!(GUInterface::stack_used(0x045D, 0x045F), 2);
+    assert_eq!(GUInterface::stack_used(0x0060, 0x045F), 0x03FF);
+    assert_eq!(GUInterface::stack_used(0x0500, 0x045F), 0);
+    assert_eq!(GUInterface::stack_used(0x02F0, 0x02FF), 0x0F)
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Narrow windows get the compact layout
+fn tst_layout_for_width() {
+    use crate::gui::{GUInterface, Layout};
+
+    assert_eq!(GUInterface::layout_for_width(800.0), Layout::Compact);
+    assert_eq!(GUInterface::layout_for_width(999.0), Layout::Compact);
+    assert_eq!(GUInterface::layout_for_width(1000.0), Layout::Wide);
+    assert_eq!(GUInterface::layout_for_width(1920.0), Layout::Wide)
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Configured hex dump size is used as is, hand-edited sizes are clamped to the slider range
+fn tst_hex_text_size() {
+    use crate::gui::GUInterface;
+    assert_eq!(
+        GUInterface::hex_text_size(Config::default().display.hex_font_size),
+        16.0
+    );
+    assert_eq!(GUInterface::hex_text_size(24), 24.0);
+    assert_eq!(GUInterface::hex_text_size(2), 10.0);
+    assert_eq!(GUInterface::hex_text_size(200), 32.0)
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Auto Run highlights last the configured number of ticks, changes restart the count
+fn tst_highlights() {
+    use crate::gui::Highlights;
+    let mut highlights = Highlights::default();
+    // r16 written on the first tick, Z and C flipped on the second
+    highlights.tick(&[16], 0, 2);
+    assert!(highlights.register(16) && !highlights.register(17));
+    highlights.tick(&[], 0b0000_0011, 2);
+    assert!(highlights.register(16) && highlights.flag(0) && highlights.flag(1));
+    highlights.tick(&[], 0, 2);
+    assert!(!highlights.register(16) && highlights.flag(1));
+    highlights.tick(&[16], 0, 2);
+    assert!(highlights.register(16) && !highlights.flag(1));
+    highlights.tick(&[], 0, 2);
+    highlights.tick(&[], 0, 2);
+    assert_eq!(highlights, Highlights::default());
+
+    // Zero duration turns highlighting off
+    highlights.tick(&[16], 0xFF, 0);
+    assert_eq!(highlights, Highlights::default())
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Step N, Run Until, Step Over and Step Out run in batches, watch and breakpoint hits end them
+fn tst_step_batch() {
+    use crate::gui::StepBatch;
+    let mut cpu = ATmemory::init();
+    // loop: inc r16; rjmp loop
+    let program: Vec<u8> = vec![0x03, 0x95, 0xFE, 0xCF];
+    cpu.load_flash_from_vec(program).ok();
+
+    let mut batch = StepBatch::new(10);
+    assert_eq!(batch.advance(&mut cpu, 4), Ok(true));
+    assert_eq!(batch.advance(&mut cpu, 4), Ok(true));
+    assert_eq!(batch.advance(&mut cpu, 4), Ok(false));
+    assert_eq!((batch.done, cpu.memory()[16]), (10, 5));
+
+    cpu.toggle_watchpoint(16);
+    let mut batch = StepBatch::new(10);
+    assert_eq!(batch.advance(&mut cpu, 4), Ok(false));
+    assert_eq!((batch.done, cpu.memory()[16]), (1, 6));
+    assert!(cpu.take_watch_hit().is_some());
+
+    // Undecodable word, the error counts the whole run
+    cpu.load_flash_from_vec(vec![0x00, 0x00, 0xFF, 0xFF]).ok();
+    let mut batch = StepBatch::new(10);
+    let error = batch.advance(&mut cpu, 4).unwrap_err();
+    assert!(error.to_string().contains("after 1 instructions"));
+
+    // rcall sub; loop: rjmp loop; sub: inc r16; ret
+    let program: Vec<u8> = vec![0x01, 0xD0, 0xFF, 0xCF, 0x03, 0x95, 0x08, 0x95];
+    let mut cpu = ATmemory::init();
+    cpu.load_flash_from_vec(program).ok();
+    let mut batch = StepBatch::until(3);
+    assert_eq!(batch.advance(&mut cpu, 4), Ok(false));
+    assert_eq!(
+        (batch.done, batch.summary(&cpu).as_str()),
+        (2, "Stopped at 0x0006")
+    );
+    assert!(StepBatch::out(&cpu).is_ok());
+
+    cpu.reset();
+    assert!(StepBatch::out(&cpu).is_err());
+    let mut batch = StepBatch::over(&cpu).unwrap();
+    assert_eq!(batch.advance(&mut cpu, 4), Ok(false));
+    assert_eq!((batch.done, cpu.pc(), cpu.memory()[16]), (3, 1, 1));
+    assert_eq!(batch.summary(&cpu), "Returned to 0x0002");
+    // rjmp is not a call, Step Over steps it singly
+    assert_eq!(StepBatch::over(&cpu), None);
+
+    cpu.reset();
+    cpu.step().ok();
+    let mut batch = StepBatch::out(&cpu).unwrap();
+    assert_eq!(batch.advance(&mut cpu, 1), Ok(true));
+    assert_eq!(batch.advance(&mut cpu, 1), Ok(false));
+    assert_eq!((batch.done, cpu.pc()), (2, 1));
+
+    // Breakpoint on the loop ends the batch on its second pass
+    cpu.set_breakpoint(1, 2);
+    let mut batch = StepBatch::new(10);
+    assert_eq!(batch.advance(&mut cpu, 10), Ok(false));
+    assert_eq!(batch.done, 2)
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Heatmap shading scales with the execution count, never executed bytes stay unshaded
+fn tst_heat_color() {
+    use crate::gui::GUInterface;
+    assert_eq!(GUInterface::heat_color(0, 10), None);
+    let alpha = |count| GUInterface::heat_color(count, 10).unwrap().a;
+    assert!((alpha(10) - 0.6).abs() < 1e-6);
+    assert!((alpha(5) - 0.3).abs() < 1e-6)
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Grid layout fills each column top to bottom with eight consecutive registers
+fn tst_register_grid() {
+    use crate::gui::GUInterface;
+    assert!(!Config::default().display.registers_grid);
+    assert_eq!(GUInterface::register_grid_cell(0), (0, 0));
+    assert_eq!(GUInterface::register_grid_cell(7), (7, 0));
+    assert_eq!(GUInterface::register_grid_cell(8), (0, 1));
+    assert_eq!(GUInterface::register_grid_cell(17), (1, 2));
+    assert_eq!(GUInterface::register_grid_cell(31), (7, 3));
+    for reg in 0..32 {
+        let (row, col) = GUInterface::register_grid_cell(reg);
+        assert_eq!(col * 8 + row, reg);
+    }
+}
+
+#[test]
+/// Address columns pad to the digits of a 16-bit address in every base
+fn tst_address_base() {
+    use breadboard::config::AddressBase;
+
+    let formatted: Vec<String> = AddressBase::ALL
+        .iter()
+        .flat_map(|base| [base.format(0x0060), base.format(0xFFFF)])
+        .collect();
+    assert_eq!(
+        formatted,
+        vec!["0060", "FFFF", "00096", "65535", "000140", "177777"]
+    );
+    assert_eq!(
+        Config::default().display.address_base,
+        AddressBase::Hexadecimal
+    )
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Footer SREG letters are uppercase for set flags and lowercase for clear ones
+fn tst_state_footer() {
+    use crate::gui::GUInterface;
+    use breadboard::config::AddressBase;
+    assert_eq!(GUInterface::sreg_letters(0b1000_0011), "IthsvnZC");
+    assert_eq!(GUInterface::sreg_letters(0x00), "ithsvnzc");
+    assert_eq!(GUInterface::sreg_letters(0xFF), "ITHSVNZC");
+
+    let mut cpu = ATmemory::init();
+    // sec; rjmp PC
+    cpu.load_flash_from_vec(vec![0x08, 0x94, 0xFF, 0xCF]).ok();
+    cpu.step().ok();
+    assert_eq!(
+        GUInterface::state_footer(&cpu, false, AddressBase::Hexadecimal),
+        "PC=0002 SP=045F SREG=ithsvnzC cyc=1"
+    );
+    assert_eq!(
+        GUInterface::state_footer(&cpu, true, AddressBase::Hexadecimal),
+        "PC=0001 SP=045F SREG=ithsvnzC cyc=1"
+    );
+    assert_eq!(
+        GUInterface::state_footer(&cpu, false, AddressBase::Decimal),
+        "PC=00002 SP=01119 SREG=ithsvnzC cyc=1"
+    )
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Word addresses halve byte addresses, typed word addresses map back to the same byte
+fn tst_word_addresses() {
+    use crate::gui::GUInterface;
+    assert!(!Config::default().display.word_addresses);
+    assert_eq!(GUInterface::shown_flash_addr(0x0104, false), 0x0104);
+    assert_eq!(GUInterface::shown_flash_addr(0x0104, true), 0x0082);
+    assert_eq!(GUInterface::shown_flash_addr(0x0105, true), 0x0082);
+    assert_eq!(GUInterface::flash_byte_addr(0x0082, true), 0x0104);
+    assert_eq!(GUInterface::flash_byte_addr(0x0104, false), 0x0104);
+    for byte_addr in (0..0x4000).step_by(2) {
+        let shown = GUInterface::shown_flash_addr(byte_addr, true);
+        assert_eq!(GUInterface::flash_byte_addr(shown, true), byte_addr);
+    }
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Typed words decode without a loaded program, bad input gets a readable message
+fn tst_decode_word_lines() {
+    use crate::gui::GUInterface;
+    let cpu = ATmemory::init();
+    assert_eq!(
+        GUInterface::decode_word_lines(&cpu, "0xEF1F"),
+        vec![
+            "LDI r17, 0xFF",
+            "Operands: LDI { dest: 17, value: 255 }",
+            "Cycles: 1"
+        ]
+    );
+    assert_eq!(
+        GUInterface::decode_word_lines(&cpu, "ef1f")[0],
+        "LDI r17, 0xFF"
+    );
+    assert_eq!(
+        GUInterface::decode_word_lines(&cpu, "9404"),
+        vec!["0x9404 is not a supported instruction"]
+    );
+    assert_eq!(
+        GUInterface::decode_word_lines(&cpu, "12345"),
+        vec!["12345 is not a 16-bit hex word"]
+    )
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Printable bytes keep their character, the rest become dimmed placeholders
+fn tst_ascii_cells() {
+    use crate::gui::{AsciiCell, GUInterface};
+    let row = [b'H', 0x00, b'.', 0x7F, b' ', 0xFF];
+    let cells: Vec<AsciiCell> = row.iter().map(|b| GUInterface::byte_to_ascii(*b)).collect();
+    assert_eq!(
+        cells,
+        vec![
+            AsciiCell::Printable('H'),
+            AsciiCell::Placeholder,
+            AsciiCell::Printable('.'),
+            AsciiCell::Placeholder,
+            AsciiCell::Printable(' '),
+            AsciiCell::Placeholder,
+        ]
+    )
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Copied values follow the display base, rows are space separated
+fn tst_clipboard_text() {
+    use crate::gui::GUInterface;
+    use breadboard::config::DisplayBase;
+
+    assert_eq!(
+        GUInterface::clipboard_text(&[0x2A], DisplayBase::Decimal),
+        "42"
+    );
+    assert_eq!(
+        GUInterface::clipboard_text(&[0x2A], DisplayBase::Binary),
+        "0b00101010"
+    );
+    assert_eq!(
+        GUInterface::clipboard_text(&[0x0C, 0x94, 0xFF], DisplayBase::Hexadecimal),
+        "0x0C 0x94 0xFF"
+    )
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// After a step the last instruction is the one that was at the old PC
+fn tst_last_instruction_label() {
+    use crate::gui::GUInterface;
+    let mut cpu = ATmemory::init();
+    let mut last: Option<String> = None;
+    assert_eq!(
+        GUInterface::last_instruction_label(last.as_deref()),
+        "Last: —"
+    );
+    // ldi r17, 255; inc r17
+    cpu.load_flash_from_vec(vec![0x1F, 0xEF, 0x13, 0x95]).ok();
+    GUInterface::step_recording(&mut cpu, &mut last).ok();
+    assert_eq!(
+        GUInterface::last_instruction_label(last.as_deref()),
+        "Last: LDI r17, 0xFF"
+    );
+    assert_eq!(
+        GUInterface::next_instruction_label(&cpu, true),
+        "Next: INC r17"
+    )
+}
+
+#[test]
+/// Every low nibble of the 1001 010d dddd xxxx block decodes to its own instruction
+fn tst_decode_one_operand_block() {
+    let cpu = ATmemory::init();
+    #[rustfmt::skip]
+    let table = [
+        (0x9510, Ok(Instruction::COM { dest: 17 })),
+        (0x9511, Ok(Instruction::NEG { dest: 17 })),
+        (0x9512, Ok(Instruction::SWAP { dest: 17 })),
+        (0x9513, Ok(Instruction::INC { reg: 17 })),
+        (0x9514, Err(EmuError::DecodeFailed { opcode: 0x9514, pc: 0 })),
+        (0x9515, Ok(Instruction::ASR { dest: 17 })),
+        (0x9516, Ok(Instruction::LSR { dest: 17 })),
+        (0x9517, Ok(Instruction::ROR { dest: 17 })),
+        (0x9518, Ok(Instruction::RETI)),
+        (0x9519, Err(EmuError::DecodeFailed { opcode: 0x9519, pc: 0 })),
+        (0x951A, Ok(Instruction::DEC { reg: 17 })),
+        (0x951B, Err(EmuError::DecodeFailed { opcode: 0x951B, pc: 0 })),
+        (0x951C, Ok(Instruction::JMP { dest: 0x220000 })),
+        (0x951D, Ok(Instruction::JMP { dest: 0x230000 })),
+        (0x951E, Ok(Instruction::CALL { dest: 0x220000 })),
+        (0x951F, Ok(Instruction::CALL { dest: 0x230000 })),
+    ];
+    for (opcode, expected) in table {
+        assert_eq!(cpu.decode(opcode), expected, "{:#06X}", opcode);
+    }
+}
+
+#[test]
+/// COM, NEG, SWAP, LSR and ROR results and flags
+fn tst_one_operand_exec() {
+    #[rustfmt::skip]
+    let cases: [(Vec<u8>, u8, u8); 5] = [
+        // ldi r16, 0x0F; com r16
+        (vec![0x0F, 0xE0, 0x00, 0x95], 0xF0, 0b0001_0101),
+        // ldi r16, 0x01; neg r16
+        (vec![0x01, 0xE0, 0x01, 0x95], 0xFF, 0b0011_0101),
+        // ldi r16, 0x1E; swap r16
+        (vec![0x0E, 0xE1, 0x02, 0x95], 0xE1, 0b0000_0000),
+        // ldi r16, 0x81; lsr r16
+        (vec![0x01, 0xE8, 0x06, 0x95], 0x40, 0b0001_1001),
+        // sec; ldi r16, 0x02; ror r16
+        (vec![0x08, 0x94, 0x02, 0xE0, 0x07, 0x95], 0x81, 0b0000_1100),
+    ];
+    for (program, result, sreg) in cases {
+        let mut cpu = ATmemory::init();
+        let steps = program.len() / 2;
+        cpu.load_flash_from_vec(program).ok();
+        for _ in 0..steps {
+            cpu.step().ok();
+        }
+        assert_eq!((cpu.memory()[16], cpu.sreg()), (result, sreg));
+    }
+}
+
+#[test]
+/// Bundled examples only use implemented instructions
+fn tst_examples_run() {
+    use breadboard::examples::ExampleProgram;
+    for example in ExampleProgram::ALL {
+        let mut cpu = ATmemory::init();
+        assert_eq!(cpu.load_flash_from_vec(example.bytes().to_vec()), Ok(()));
+        assert_eq!(cpu.step_n(10), Ok(10), "{}", example);
+    }
+
+    let mut cpu = ATmemory::init();
+    cpu.load_flash_from_vec(ExampleProgram::AddLoop.bytes().to_vec())
+        .ok();
+    cpu.step_n(40).ok();
+    assert_eq!(cpu.memory()[16], 55)
+}
+
+#[test]
+/// Execution starts at the configured reset vector, also after loading and reset
+fn tst_start_pc() {
+    let mut cpu = ATmemory::init();
+    cpu.set_start_pc(0x10);
+    // ldi r16, 1 at 0x0000, ldi r16, 2 at word 0x10
+    let mut program = vec![0x01, 0xE0];
+    program.resize(0x20, 0x00);
+    program.extend([0x02, 0xE0]);
+    cpu.load_flash_from_vec(program).ok();
+    cpu.step().ok();
+    assert_eq!((cpu.memory()[16], cpu.pc()), (2, 0x11));
+    cpu.reset();
+    assert_eq!(cpu.pc(), 0x10)
+}
+
+#[test]
+/// 12-bit offsets of RJMP and RCALL are sign extended at both ends of the range
+fn tst_relative_jump_extremes() {
+    let table: [(u16, i16, u16, u16); 6] = [
+        // (opcode, offset, at word, PC after)
+        (0xC800, -2048, 0x0800, 0x0001),
+        (0xC7FF, 2047, 0x0000, 0x0800),
+        (0xCFFF, -1, 0x0000, 0x0000),
+        (0xD800, -2048, 0x0800, 0x0001),
+        (0xD7FF, 2047, 0x0000, 0x0800),
+        (0xDFFF, -1, 0x0000, 0x0000),
+    ];
+
+    for (opcode, offset, at, pc) in table {
+        let mut cpu = ATmemory::init();
+        let expected = if opcode & 0xF000 == 0xC000 {
+            Instruction::RJMP { offset }
+        } else {
+            Instruction::RCALL { offset }
+        };
+        assert_eq!(cpu.decode(opcode), Ok(expected), "opcode {:#06X}", opcode);
+
+        let mut program = vec![0x00; at as usize * 2];
+        program.extend(opcode.to_le_bytes());
+        cpu.load_flash_from_vec(program).ok();
+        cpu.set_start_pc(at);
+        cpu.step().ok();
+        assert_eq!(cpu.pc(), pc, "opcode {:#06X}", opcode);
+    }
+}
+
+/// Runs a program until PC leaves it, returns the CPU and the cycles its steps reported
+fn run_and_count(program: Vec<u8>) -> (ATmemory, u64) {
+    let mut cpu = ATmemory::init();
+    cpu.load_flash_from_vec(program).unwrap();
+    let mut cycles = 0;
+    while !cpu.pc_beyond_program() {
+        cycles += u64::from(cpu.step().unwrap());
+    }
+    (cpu, cycles)
+}
+
+#[test]
+/// Delay loops take the datasheet cycle count, taken branches cost 2 and falling through 1
+fn tst_delay_loop_cycles() {
+    // ldi r16, 10; loop: dec r16; brne loop
+    let (cpu, cycles) = run_and_count(vec![0x0A, 0xE0, 0x0A, 0x95, 0xF1, 0xF7]);
+    // 1 + 10 * 1 (dec) + 9 * 2 + 1 (brne)
+    assert_eq!(cycles, 30);
+    assert_eq!(u64::from(cpu.cycle_cnt()), cycles);
+
+    // ldi r17, 3; outer: ldi r16, 10; inner: dec r16; brne inner; dec r17; brne outer
+    let (cpu, cycles) = run_and_count(vec![
+        0x13, 0xE0, 0x0A, 0xE0, 0x0A, 0x95, 0xF1, 0xF7, 0x1A, 0x95, 0xD9, 0xF7,
+    ]);
+    // 1 + 3 * (1 + 29 + 1) + 2 * 2 + 1
+    assert_eq!(cycles, 99);
+    assert_eq!((cpu.memory()[16], cpu.memory()[17]), (0, 0))
+}
+
+#[test]
+/// Restarting with the kept program bytes runs the same program to the same state
+fn tst_restart_keeping_program() {
+    // ldi r16, 5; loop: inc r17; dec r16; brne loop
+    let (first, first_cycles) = run_and_count(vec![0x05, 0xE0, 0x13, 0x95, 0x0A, 0x95, 0xE9, 0xF7]);
+    let (second, second_cycles) = run_and_count(first.program().to_vec());
+    assert_eq!(first.program(), second.program());
+    assert_eq!(second.memory()[17], 5);
+    assert_eq!(
+        (first.memory(), first.pc(), first_cycles),
+        (second.memory(), second.pc(), second_cycles)
+    )
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// Restart Keeping Program loads the kept bytes into the fresh CPU, a too small device rejects them
+fn tst_restart_with_program() {
+    use crate::gui::GUInterface;
+    // ldi r16, 5; loop: inc r17; dec r16; brne loop
+    let (first, _) = run_and_count(vec![0x05, 0xE0, 0x13, 0x95, 0x0A, 0x95, 0xE9, 0xF7]);
+    let mut cpu = GUInterface::restart_with_program(ATmemory::init(), first.program()).unwrap();
+    assert_eq!((cpu.pc(), cpu.memory()[17]), (0, 0));
+    while !cpu.pc_beyond_program() {
+        cpu.step().unwrap();
+    }
+    assert_eq!((cpu.memory(), cpu.pc()), (first.memory(), first.pc()));
+
+    let mut tiny = ATmemory::init();
+    tiny.set_device(Device::ATtiny10);
+    assert!(GUInterface::restart_with_program(tiny, &[0; 2048]).is_err())
+}
+
+#[test]
+/// 7-bit offsets of BRBS and BRBC are sign extended from bit 6, -64..=63 words
+fn tst_conditional_branch_extremes() {
+    let table: [(u16, i8, u16, u16); 6] = [
+        // (opcode, offset, at word, PC after)
+        (0xF1F8, 63, 0x0000, 0x0040),
+        (0xF200, -64, 0x0040, 0x0001),
+        (0xF000, 0, 0x0000, 0x0001),
+        (0xF5F8, 63, 0x0000, 0x0040),
+        (0xF600, -64, 0x0040, 0x0001),
+        (0xF400, 0, 0x0000, 0x0001),
+    ];
+
+    for (opcode, offset, at, pc) in table {
+        let mut cpu = ATmemory::init();
+        let bit = (opcode & 0b111) as u8;
+        let expected = if opcode & 0x0400 == 0 {
+            Instruction::BRBS { offset, bit }
+        } else {
+            Instruction::BRBC { offset, bit }
+        };
+        assert_eq!(cpu.decode(opcode), Ok(expected), "opcode {:#06X}", opcode);
+
+        let mut program = vec![0x00; at as usize * 2];
+        program.extend(opcode.to_le_bytes());
+        cpu.load_flash_from_vec(program).ok();
+        cpu.set_start_pc(at);
+        // BRBS tests C, set here, BRBC tests C, left clear
+        if opcode & 0x0400 == 0 {
+            cpu.set_sreg(0x01);
+        }
+        cpu.step().ok();
+        assert_eq!(cpu.pc(), pc, "opcode {:#06X}", opcode);
+    }
+}
+
+#[test]
+/// decode and exec subcommands print instructions and final state, other arguments fall through
+fn tst_cli_decode() {
+    let args = |line: &str| line.split(' ').map(OsString::from).collect::<Vec<_>>();
+
+    let mut out = Vec::new();
+    let handled = breadboard::cli::run(&args("decode EF1F,9508"), &mut out);
+    assert_eq!(handled, Ok(true));
+    assert_eq!(String::from_utf8(out).unwrap(), "LDI r17, 0xFF\nRET\n");
+
+    let mut out = Vec::new();
+    let exec = ["exec", "1F EF 00 00", "--steps", "2"].map(OsString::from);
+    assert_eq!(breadboard::cli::run(&exec, &mut out), Ok(true));
+    let state = String::from_utf8(out).unwrap();
+    assert_eq!(state, "PC=0004 SP=045F SREG=0x00 cyc=2\nr17=0xFF\n");
+
+    assert!(breadboard::cli::run(&args("decode XYZ"), &mut Vec::new()).is_err());
+    let handled = breadboard::cli::run(&args("program.bin"), &mut Vec::new());
+    assert_eq!(handled, Ok(false));
+    assert_eq!(breadboard::cli::run(&[], &mut Vec::new()), Ok(false))
+}
+
+#[test]
+/// Diff against the state stored at a breakpoint pause covers every step since, not just the last
+fn tst_pause_snapshot() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 1; ldi r17, 2; ldi r18, 3; ldi r19, 4
+    cpu.load_flash_from_vec(vec![0x01, 0xE0, 0x12, 0xE0, 0x23, 0xE0, 0x34, 0xE0])
+        .ok();
+    cpu.set_breakpoint(1, 1);
+    cpu.run_to_completion(&[]).ok();
+    assert_eq!(cpu.pc(), 1);
+    let pause_snapshot = cpu.snapshot();
+
+    cpu.step().ok();
+    let last_step = cpu.snapshot();
+    cpu.step().ok();
+    assert_eq!(
+        pause_snapshot.diff(&cpu.snapshot()),
+        vec![
+            StateDelta::Pc { old: 1, new: 3 },
+            StateDelta::Register {
+                reg: 17,
+                old: 0,
+                new: 2
+            },
+            StateDelta::Register {
+                reg: 18,
+                old: 0,
+                new: 3
+            },
+        ]
+    );
+    assert_eq!(last_step.diff(&cpu.snapshot()).len(), 2);
+}
+
+#[test]
+#[cfg(unix)]
+/// Paths that are not valid UTF-8 still load
+fn tst_load_non_utf8_path() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let name = std::ffi::OsStr::from_bytes(b"breadboard-tst-\xFF\xFE.bin");
+    let path = std::env::temp_dir().join(name);
+    assert!(path.to_str().is_none());
+    std::fs::write(&path, [0x01, 0xE0]).unwrap();
+    let mut cpu = ATmemory::init();
+    let result = cpu.load_bin(&path);
+    std::fs::remove_file(&path).ok();
+    assert_eq!((result, &cpu.flash()[..2]), (Ok(()), &[0x01, 0xE0][..]))
+}
+
+#[test]
+/// Loop body mnemonics are counted once per pass, reset clears the histogram
+fn tst_mnemonic_counts() {
+    let mut cpu = ATmemory::init();
+    // ldi r16, 5; dec r16; brne -2; nop
+    let program: Vec<u8> = vec![0x05, 0xE0, 0x0A, 0x95, 0xF1, 0xF7, 0x00, 0x00];
+    cpu.load_flash_from_vec(program).ok();
+    cpu.run_until(6).ok();
+    assert_eq!(
+        cpu.mnemonic_counts(),
+        vec![("BRBC", 5), ("DEC", 5), ("LDI", 1)]
+    );
+    cpu.reset();
+    assert!(cpu.mnemonic_counts().is_empty())
+}
+
+#[test]
+/// Stack bounds follow the SRAM of the device or a custom region from a linker script
+fn tst_stack_region() {
+    assert_eq!(
+        *breadboard::device::stack_region(0x60, 2 * 1024).end(),
+        0x085F
+    );
+    assert_eq!(Device::ATmega16.stack_region(), 0x0060..=0x045F);
+    assert_eq!(Device::ATtiny10.stack_region(), 0x0040..=0x005F);
+
+    // SPL, SPH and SREG sit at the top of the ATtiny10 SRAM, the stack ends below them
+    let mut cpu = ATmemory::init();
+    cpu.set_device(Device::ATtiny10);
+    assert_eq!((cpu.stack_region(), cpu.sp()), (0x0040..=0x005C, 0x005C));
+    assert_eq!(
+        cpu.set_stack_region(0x0040..=0x005F),
+        Err(EmuError::OutOfBounds { addr: 0x005D })
+    );
+    // The data space is sized for the device, the whole ATmega128 SRAM holds the stack
+    cpu.set_device(Device::ATmega128);
+    assert_eq!((cpu.stack_region(), cpu.sp()), (0x0100..=0x10FF, 0x10FF));
+    assert_eq!(cpu.memory().len(), 0x1100);
+
+    let mut cpu = ATmemory::init();
+    assert_eq!(cpu.set_stack_region(0x0200..=0x02FF), Ok(()));
+    cpu.reset();
+    assert_eq!(cpu.sp(), 0x02FF);
+    assert_eq!(
+        cpu.set_sp(0x0300),
+        Err(EmuError::OutOfBounds { addr: 0x0300 })
+    );
+    assert_eq!(
+        cpu.set_stack_region(0x0020..=0x02FF),
+        Err(EmuError::OutOfBounds { addr: 0x0020 })
+    );
+    assert_eq!(
+        cpu.set_stack_region(0x0200..=0x0460),
+        Err(EmuError::OutOfBounds { addr: 0x0460 })
+    );
+}
+
+#[test]
+/// RCALL and RET round-trip on the ATtiny10, the pushed bytes leave SP and SREG alone
+fn tst_tiny_call_return() {
+    let mut cpu = ATmemory::init();
+    cpu.set_device(Device::ATtiny10);
+    // rcall sub; loop: rjmp loop; sub: ldi r16, 5; ret
+    cpu.load_flash_from_vec(vec![0x01, 0xD0, 0xFF, 0xCF, 0x05, 0xE0, 0x08, 0x95])
+        .ok();
+    cpu.step().unwrap();
+    assert_eq!((cpu.pc(), cpu.sp(), cpu.sreg()), (2, 0x5A, 0));
+    assert_eq!(&cpu.memory()[0x5A..=0x5E], &[0x00, 0x01, 0x00, 0x5A, 0x00]);
+
+    cpu.step_n(2).unwrap();
+    assert_eq!(
+        (cpu.pc(), cpu.sp(), cpu.sreg(), cpu.memory()[16]),
+        (1, 0x5C, 0, 5)
+    );
+    assert_eq!(cpu.call_depth(), 0)
+}
+
+#[test]
+/// Edited EEPROM survives an export to `.eep` and a reload
+fn tst_eep_round_trip() {
+    let mut cpu = ATmemory::init();
+    cpu.write_eeprom(0x000, 0x42).unwrap();
+    cpu.write_eeprom(0x1FF, 0xA5).unwrap();
+    assert_eq!(
+        cpu.write_eeprom(0x200, 0x01),
+        Err(EmuError::OutOfBounds { addr: 0x200 })
+    );
+
+    let path = std::env::temp_dir().join("breadboard-tst-eep-round-trip.eep");
+    std::fs::write(&path, cpu.dump_eep()).unwrap();
+    let mut reloaded = ATmemory::init();
+    let result = reloaded.load_eep(&path);
+    std::fs::remove_file(&path).ok();
+    assert_eq!(result, Ok(()));
+    assert_eq!(reloaded.eeprom(), cpu.eeprom())
+}
+
+#[test]
+/// A wait loop polling an unmodeled status register only exits once the register is pinned
+fn tst_io_override() {
+    // in r16, 0x0B; andi r16, 0x80; breq -3; nop
+    let program: Vec<u8> = vec![0x0B, 0xB1, 0x00, 0x78, 0xE9, 0xF3, 0x00, 0x00];
+    let mut cpu = ATmemory::init();
+    cpu.load_flash_from_vec(program.clone()).ok();
+    cpu.step_n(9).ok();
+    assert!(cpu.pc() < 3);
+
+    cpu.reset();
+    let pinned = cpu.set_io_override(0x2B, Some(IoOverride::Fixed(0x80)));
+    assert_eq!(pinned, Ok(()));
+    cpu.step_n(3).ok();
+    assert_eq!((cpu.pc(), cpu.memory()[0x2B]), (3, 0x00));
+
+    // Toggling reads 0 first, so the loop takes one more pass
+    let mut cpu = ATmemory::init();
+    cpu.load_flash_from_vec(program).ok();
+    let toggle = IoOverride::Toggle {
+        value: 0x80,
+        high: false,
+    };
+    cpu.set_io_override(0x2B, Some(toggle)).ok();
+    cpu.step_n(3).ok();
+    assert_eq!(cpu.pc(), 0);
+    cpu.step_n(3).ok();
+    assert_eq!(cpu.pc(), 3);
+
+    assert_eq!(
+        cpu.set_io_override(0x60, Some(IoOverride::Fixed(0))),
+        Err(EmuError::OutOfBounds { addr: 0x60 })
+    );
+}
+
+#[test]
+/// Immediate instructions encode r16-r31 in 4 bits, the lowest and highest must not shift by 16
+fn tst_immediate_dest() {
+    // (opcode, destination, result with the destination preset to 0x3C)
+    let table = [
+        (0xEA05, 16, 0xA5), // ldi r16, 0xA5
+        (0xEAF5, 31, 0xA5), // ldi r31, 0xA5
+        (0x700F, 16, 0x0C), // andi r16, 0x0F
+        (0x70FF, 31, 0x0C), // andi r31, 0x0F
+        (0x6F00, 16, 0xFC), // ori r16, 0xF0
+        (0x6FF0, 31, 0xFC), // ori r31, 0xF0
+    ];
+
+    for (opcode, dest, result) in table {
+        let mut cpu = ATmemory::init();
+        let decoded = cpu.decode(opcode);
+        assert!(
+            matches!(
+                decoded,
+                Ok(Instruction::LDI { dest: d, .. }
+                    | Instruction::ANDI { dest: d, .. }
+                    | Instruction::ORI { dest: d, .. }) if d == dest
+            ),
+            "opcode {:#06X} decoded as {:?}",
+            opcode,
+            decoded
+        );
+
+        cpu.set_register(dest, 0x3C).ok();
+        cpu.load_flash_from_vec(opcode.to_le_bytes().to_vec()).ok();
+        cpu.step().ok();
+        // Only the destination changed, not the register 16 below or above it
+        let changed: Vec<_> = (0..32).filter(|&reg| cpu.memory()[reg] != 0).collect();
+        assert_eq!(changed, vec![usize::from(dest)], "opcode {:#06X}", opcode);
+        let value = cpu.memory()[usize::from(dest)];
+        assert_eq!(value, result, "opcode {:#06X}", opcode);
+    }
+}
+
+#[test]
+/// Random flash never panics the emulator, whatever decode and execute return
+fn tst_random_opcodes() {
+    // Fixed seed LCG, a failure reproduces on every run
+    let mut seed: u64 = 0x00C0_FFEE;
+    let mut next_byte = || {
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (seed >> 56) as u8
+    };
+
+    for device in Device::ALL {
+        let mut cpu = ATmemory::init();
+        cpu.set_device(*device);
+        let program: Vec<u8> = (0..device.flash_size()).map(|_| next_byte()).collect();
+        cpu.load_flash_from_vec(program).unwrap();
+        cpu.set_skip_undecodable(true);
+        for _ in 0..5000 {
+            // Stack errors leave the CPU where it was, start over instead of failing again
+            if cpu.step().is_err() {
+                cpu.reset();
+            }
+        }
+    }
+}
+
+#[test]
+/// Free run pauses at the first undecodable word and reports it, unless the policy skips it
+fn tst_error_policy() {
+    // ldi r16, 1; ldi r17, 2; reserved 0x9404; ldi r18, 3
+    let program = vec![0x01, 0xE0, 0x12, 0xE0, 0x04, 0x94, 0x23, 0xE0];
+
+    let mut cpu = ATmemory::init();
+    cpu.set_skip_undecodable(ErrorPolicy::Pause.skips_undecodable());
+    cpu.load_flash_from_vec(program.clone()).ok();
+    let error = cpu.run_to_completion(&[]).unwrap_err();
+    assert_eq!(
+        error,
+        EmuError::DecodeFailed {
+            opcode: 0x9404,
+            pc: 0x0002
+        }
+    );
+    assert_eq!((cpu.pc(), cpu.memory()[17], cpu.memory()[18]), (2, 2, 0));
+    #[cfg(feature = "gui")]
+    {
+        use crate::gui::GUInterface;
+        use breadboard::config::AddressBase;
+
+        let banner = |word_addresses, base| {
+            GUInterface::error_banner(&cpu, &error, ErrorPolicy::Pause, word_addresses, base)
+        };
+        assert_eq!(
+            banner(false, AddressBase::Hexadecimal).as_deref(),
+            Some("Auto Run paused at 0004: Cannot decode 0x9404")
+        );
+        assert_eq!(
+            banner(true, AddressBase::Octal).as_deref(),
+            Some("Auto Run paused at 000002: Cannot decode 0x9404")
+        );
+        let skipped = GUInterface::error_banner(
+            &cpu,
+            &error,
+            ErrorPolicy::SkipUndecodable,
+            false,
+            AddressBase::Hexadecimal,
+        );
+        assert_eq!(skipped, None);
+    }
+
+    let mut cpu = ATmemory::init();
+    cpu.set_skip_undecodable(ErrorPolicy::SkipUndecodable.skips_undecodable());
+    cpu.load_flash_from_vec(program).ok();
+    assert_eq!(cpu.run_to_completion(&[]), Ok(()));
+    assert_eq!(cpu.memory()[18], 3);
+}
+
+#[test]
+/// Configs saved with the old `skip_undecodable` flag load it as the matching error policy
+fn tst_error_policy_migration() {
+    let dir = std::env::temp_dir().join(format!("breadboard-policy-{}", std::process::id()));
+    let path = dir.join("config.toml");
+    Config::default().save_to(&path).unwrap();
+    let saved = std::fs::read_to_string(&path).unwrap();
+    for (flag, policy) in [
+        ("true", ErrorPolicy::SkipUndecodable),
+        ("false", ErrorPolicy::Pause),
+    ] {
+        let old = saved.replace(
+            "error_policy = \"Pause\"",
+            &format!("skip_undecodable = {}", flag),
+        );
+        std::fs::write(&path, old).unwrap();
+        let (config, warning) = Config::load_from(&path).unwrap();
+        assert_eq!((config.error_policy, warning), (policy, None));
+    }
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+/// A timer interrupt is pending once its flag and enable bits are set, whatever the I flag says
+fn tst_pending_interrupts() {
+    let mut cpu = ATmemory::init();
+    assert!(cpu.pending_interrupts().is_empty());
+    // TOV0 in TIFR without TOIE0 in TIMSK
+    cpu.fill(MemSpace::Data, 0x58, 0x59, 0x01).ok();
+    assert!(cpu.pending_interrupts().is_empty());
+    cpu.fill(MemSpace::Data, 0x59, 0x5A, 0x05).ok();
+    assert_eq!(cpu.sreg() & 0x80, 0);
+    assert_eq!(cpu.pending_interrupts(), vec![("TOV0", 9)]);
+
+    cpu.set_device(Device::ATmega128);
+    cpu.fill(MemSpace::Data, 0x56, 0x58, 0x01).ok();
+    assert_eq!(cpu.pending_interrupts(), vec![("TOV0", 16)]);
+
+    cpu.set_device(Device::ATtiny10);
+    assert!(cpu.pending_interrupts().is_empty());
+}
+
+#[test]
+#[cfg(feature = "gui")]
+/// TOV0 pending with I clear is called out as disabled, SEI turns it into a plain pending note
+fn tst_interrupt_pending_disabled() {
+    use crate::gui::GUInterface;
+    let mut cpu = ATmemory::init();
+    assert_eq!(GUInterface::pending_interrupt_status(&cpu), None);
+
+    cpu.fill(MemSpace::Data, 0x58, 0x5A, 0x01).ok();
+    assert_eq!(
+        GUInterface::pending_interrupt_status(&cpu).as_deref(),
+        Some("Interrupt pending (disabled): TOV0 (vector 9)")
+    );
+    cpu.set_sreg(0x80);
+    assert_eq!(
+        GUInterface::pending_interrupt_status(&cpu).as_deref(),
+        Some("Interrupt pending: TOV0 (vector 9)")
+    );
+}
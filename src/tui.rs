@@ -1,10 +1,9 @@
 use std::{cell::RefCell, rc::Rc};
 
-use crate::{
-    config::Config,
-    memory::ATmemory,
-    tui::{desktop::TDesktop, memory::MemoryWindow, ports::PortsWindow, status::StatusWindow},
+use crate::tui::{
+    desktop::TDesktop, memory::MemoryWindow, ports::PortsWindow, status::StatusWindow,
 };
+use breadboard::{config::Config, memory::ATmemory};
 
 mod config;
 mod desktop;
@@ -20,8 +19,14 @@ pub struct TUInterface {
 
 impl TUInterface {
     pub fn new() -> Self {
-        let config = Config::load().unwrap_or_default();
-        let mut cpu = ATmemory::init();
+        let (config, warning) = Config::load().unwrap_or_else(|e| (Config::default(), Some(e)));
+        if let Some(warning) = warning {
+            appcui::log!("WARNING", "{}", warning);
+        }
+        let mut cpu = ATmemory::init_with_sram(config.sram_init);
+        cpu.set_start_pc(config.start_pc);
+        cpu.set_skip_undecodable(config.error_policy.skips_undecodable());
+        cpu.set_lint_zero_register(config.lint_zero_register);
         cpu.connect_to_hw(&config.bridge_address).ok();
 
         Self { cpu, config }
@@ -47,7 +52,10 @@ impl TUInterface {
             .build()
             .unwrap();
 
-        app.add_window(StatusWindow::new(Rc::clone(&cpu_shared)));
+        app.add_window(StatusWindow::new(
+            Rc::clone(&config_shared),
+            Rc::clone(&cpu_shared),
+        ));
         app.add_window(MemoryWindow::new(
             Rc::clone(&config_shared),
             Rc::clone(&cpu_shared),
@@ -2,11 +2,11 @@ use std::{cell::RefCell, rc::Rc};
 
 use appcui::prelude::{ButtonEvents, EventProcessStatus, ModalWindow};
 
-use crate::config::{Config, DisplayBase, DisplayBaseConfig};
+use breadboard::config::{Config, DisplayBase, DisplayBaseConfig};
 
 #[ModalWindow(events=[WindowEvents, ButtonEvents], response=ConfigDialogResult)]
 pub struct ConfigDialog {
-    config: Rc<RefCell<crate::config::Config>>,
+    config: Rc<RefCell<breadboard::config::Config>>,
     close_btn: Handle<Button>,
     save_btn: Handle<Button>,
     memory_bytes_per_row: Handle<NumericSelector<usize>>,
@@ -19,7 +19,7 @@ pub struct ConfigDialog {
 }
 
 impl ConfigDialog {
-    pub fn new(config: Rc<RefCell<crate::config::Config>>, cpu_frequency: u8) -> Self {
+    pub fn new(config: Rc<RefCell<breadboard::config::Config>>, cpu_frequency: u8) -> Self {
         let mut win = Self {
             base: ModalWindow::new(
                 "Config",
@@ -6,10 +6,10 @@ use crate::tui::{
     config::ConfigDialog, flash::FlashWindow, memory::MemoryWindow, ports::PortsWindow,
 };
 
-#[Desktop(events = [MenuEvents, AppBarEvents, DesktopEvents, TimerEvents], commands=[OpenBin, OpenHex, ShowAbout, ShowConfig, ShowFlash, ShowMemory, ShowPorts, CPUStep, CPUAuto, CPUReset, AppExit])]
+#[Desktop(events = [MenuEvents, AppBarEvents, DesktopEvents, TimerEvents], commands=[OpenBin, OpenHex, ShowAbout, ShowConfig, ShowFlash, ShowMemory, ShowPorts, CPUStep, CPUStepOver, CPUAuto, CPUReset, AppExit])]
 pub struct TDesktop {
-    config: Rc<RefCell<crate::config::Config>>,
-    cpu: Rc<RefCell<crate::memory::ATmemory>>,
+    config: Rc<RefCell<breadboard::config::Config>>,
+    cpu: Rc<RefCell<breadboard::memory::ATmemory>>,
     cpu_auto_step: bool,
     cpu_frequency: u8,
     flash_window_handler: Handle<FlashWindow>,
@@ -21,8 +21,8 @@ pub struct TDesktop {
 
 impl TDesktop {
     pub fn new(
-        config: Rc<RefCell<crate::config::Config>>,
-        cpu: Rc<RefCell<crate::memory::ATmemory>>,
+        config: Rc<RefCell<breadboard::config::Config>>,
+        cpu: Rc<RefCell<breadboard::memory::ATmemory>>,
     ) -> Self {
         Self {
             base: Desktop::new(),
@@ -146,6 +146,15 @@ License: MIT",
                     }
                 }
             }
+            tdesktop::Commands::CPUStepOver => {
+                self.cpu_auto_step = false;
+                match self.cpu.borrow_mut().step_over() {
+                    Ok(_) => {}
+                    Err(e) => {
+                        log!("ERROR", "Failed to step over: {}", e)
+                    }
+                }
+            }
             tdesktop::Commands::CPUAuto => {
                 self.cpu_auto_step = !self.cpu_auto_step;
                 if let Some(timer) = self.base.timer() {
@@ -240,6 +249,11 @@ impl DesktopEvents for TDesktop {
             key!("F8"),
             tdesktop::Commands::CPUStep,
         ));
+        menu_edit.add(menu::Command::new(
+            "Step &Over",
+            key!("F10"),
+            tdesktop::Commands::CPUStepOver,
+        ));
         menu_edit.add(menu::CheckBox::new(
             "&Auto Run",
             key!("F5"),
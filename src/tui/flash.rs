@@ -2,19 +2,19 @@ use std::{cell::RefCell, rc::Rc};
 
 use appcui::prelude::{ActionRequest, Window, WindowEvents};
 
-use crate::memory::ATmemory;
+use breadboard::memory::ATmemory;
 
 #[Window(events=WindowEvents)]
 pub struct FlashWindow {
-    config: Rc<RefCell<crate::config::Config>>,
-    cpu: Rc<RefCell<crate::memory::ATmemory>>,
+    config: Rc<RefCell<breadboard::config::Config>>,
+    cpu: Rc<RefCell<breadboard::memory::ATmemory>>,
     flash: Handle<TextArea>,
 }
 
 impl FlashWindow {
     pub fn new(
-        config: Rc<RefCell<crate::config::Config>>,
-        cpu: Rc<RefCell<crate::memory::ATmemory>>,
+        config: Rc<RefCell<breadboard::config::Config>>,
+        cpu: Rc<RefCell<breadboard::memory::ATmemory>>,
     ) -> Self {
         let max_window: usize = config.borrow().display.memory_bytes_per_column * 2;
         let bytes_per_row: usize = config.borrow().display.memory_bytes_per_row;
@@ -47,9 +47,12 @@ impl FlashWindow {
     pub fn load_flash(&mut self) -> String {
         let mut flash = String::new();
         let bytes_per_row = self.config.borrow().display.memory_bytes_per_row;
+        let word_addresses = self.config.borrow().display.word_addresses;
 
-        for addr in (0..16384).step_by(bytes_per_row) {
-            flash.push_str(&format!("{:04X}: ", addr));
+        let flash_size = self.cpu.borrow().flash().len();
+        for addr in (0..flash_size).step_by(bytes_per_row) {
+            let shown = if word_addresses { addr / 2 } else { addr };
+            flash.push_str(&format!("{:04X}: ", shown));
 
             // Hex
             for seg in addr..addr + bytes_per_row {
@@ -88,7 +91,12 @@ impl FlashWindow {
 
 impl WindowEvents for FlashWindow {
     fn on_cancel(&mut self) -> ActionRequest {
-        self.cpu.replace(ATmemory::init());
+        let config = self.config.borrow();
+        let mut cpu = ATmemory::init_with_sram(config.sram_init);
+        cpu.set_start_pc(config.start_pc);
+        cpu.set_skip_undecodable(config.error_policy.skips_undecodable());
+        cpu.set_lint_zero_register(config.lint_zero_register);
+        self.cpu.replace(cpu);
         ActionRequest::Allow
     }
 }
@@ -4,8 +4,8 @@ use appcui::prelude::Window;
 
 #[Window(events = [TimerEvents])]
 pub struct MemoryWindow {
-    config: Rc<RefCell<crate::config::Config>>,
-    cpu: Rc<RefCell<crate::memory::ATmemory>>,
+    config: Rc<RefCell<breadboard::config::Config>>,
+    cpu: Rc<RefCell<breadboard::memory::ATmemory>>,
     list: Handle<ListView<MemoryItem>>,
     g_reg: listview::Group,
     g_stc: listview::Group,
@@ -13,8 +13,8 @@ pub struct MemoryWindow {
 
 impl MemoryWindow {
     pub fn new(
-        config: Rc<RefCell<crate::config::Config>>,
-        cpu: Rc<RefCell<crate::memory::ATmemory>>,
+        config: Rc<RefCell<breadboard::config::Config>>,
+        cpu: Rc<RefCell<breadboard::memory::ATmemory>>,
     ) -> Self {
         let mut win = Self {
             base: window!("'Internal Memory',a:bl,w:28,h:36"),
@@ -61,7 +61,7 @@ impl MemoryWindow {
     }
 
     pub fn update(&mut self) {
-        let registers = *self.cpu.borrow().memory();
+        let registers = self.cpu.borrow().memory().to_vec();
 
         let mut reg_values = Vec::new();
         for reg in 0..32 {
@@ -90,18 +90,20 @@ impl MemoryWindow {
     }
 
     fn format_registers(&self, value: u8) -> String {
+        let signed = self.config.borrow().display_base.registers_signed;
         match self.config.borrow().display_base.registers {
-            crate::config::DisplayBase::Binary => format!("{:#010b}", value),
-            crate::config::DisplayBase::Decimal => format!("{:03}", value),
-            crate::config::DisplayBase::Hexadecimal => format!("{:#04X}", value),
+            breadboard::config::DisplayBase::Binary => format!("{:#010b}", value),
+            breadboard::config::DisplayBase::Decimal if signed => format!("{:04}", value as i8),
+            breadboard::config::DisplayBase::Decimal => format!("{:03}", value),
+            breadboard::config::DisplayBase::Hexadecimal => format!("{:#04X}", value),
         }
     }
 
     fn format_stack(&self, value: u8) -> String {
         match self.config.borrow().display_base.stack {
-            crate::config::DisplayBase::Binary => format!("{:#010b}", value),
-            crate::config::DisplayBase::Decimal => format!("{:03}", value),
-            crate::config::DisplayBase::Hexadecimal => format!("{:#04X}", value),
+            breadboard::config::DisplayBase::Binary => format!("{:#010b}", value),
+            breadboard::config::DisplayBase::Decimal => format!("{:03}", value),
+            breadboard::config::DisplayBase::Hexadecimal => format!("{:#04X}", value),
         }
     }
 }
@@ -6,7 +6,7 @@ use appcui::ui::hline::Flags;
 
 #[Window(events = TimerEvents)]
 pub struct PortsWindow {
-    cpu: Rc<RefCell<crate::memory::ATmemory>>,
+    cpu: Rc<RefCell<breadboard::memory::ATmemory>>,
     status_label: Handle<Label>,
     port_a_label: Handle<Label>,
     port_b_label: Handle<Label>,
@@ -15,7 +15,7 @@ pub struct PortsWindow {
 }
 
 impl PortsWindow {
-    pub fn new(cpu: Rc<RefCell<crate::memory::ATmemory>>) -> Self {
+    pub fn new(cpu: Rc<RefCell<breadboard::memory::ATmemory>>) -> Self {
         let mut win = Self {
             base: window!("'Ports',x:30,y:1,w:30,h:19,pivot:topright"),
             cpu,
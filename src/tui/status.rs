@@ -8,7 +8,8 @@ use appcui::{
 #[Window(events=[WindowEvents, TimerEvents])]
 pub struct StatusWindow {
     cc_lb: Handle<Label>,
-    cpu: Rc<RefCell<crate::memory::ATmemory>>,
+    config: Rc<RefCell<breadboard::config::Config>>,
+    cpu: Rc<RefCell<breadboard::memory::ATmemory>>,
     pc_lb: Handle<Label>,
     sp_lb: Handle<Label>,
     xp_lb: Handle<Label>,
@@ -18,10 +19,14 @@ pub struct StatusWindow {
 }
 
 impl StatusWindow {
-    pub fn new(cpu: Rc<RefCell<crate::memory::ATmemory>>) -> Self {
+    pub fn new(
+        config: Rc<RefCell<breadboard::config::Config>>,
+        cpu: Rc<RefCell<breadboard::memory::ATmemory>>,
+    ) -> Self {
         let mut win = Self {
             base: window!("'Status',x:0,y:1,w:32,h:10,flags:NoCloseButton"),
             cc_lb: Handle::None,
+            config,
             cpu,
             pc_lb: Handle::None,
             sp_lb: Handle::None,
@@ -81,7 +86,13 @@ impl WindowEvents for StatusWindow {
 
 impl TimerEvents for StatusWindow {
     fn on_update(&mut self, _ticks: u64) -> EventProcessStatus {
-        let text = format!("Program Counter | {:#08X}", self.cpu.borrow().pc());
+        // Byte address like the flash window, unless word addresses are configured
+        let pc = u32::from(self.cpu.borrow().pc());
+        let pc = match self.config.borrow().display.word_addresses {
+            true => pc,
+            false => pc * 2,
+        };
+        let text = format!("Program Counter | {:#08X}", pc);
         let h = self.pc_lb;
         if let Some(lb) = self.control_mut(h) {
             lb.set_caption(&text);
@@ -93,19 +104,29 @@ impl TimerEvents for StatusWindow {
             lb.set_caption(&text);
         }
 
-        let text = format!("X Pointer | {:#06X}", self.cpu.borrow().xp());
+        let order = self.config.borrow().display_base.pair_order;
+        let text = format!(
+            "X Pointer | {}",
+            order.format_pair(26, self.cpu.borrow().xp())
+        );
         let h = self.xp_lb;
         if let Some(lb) = self.control_mut(h) {
             lb.set_caption(&text);
         }
 
-        let text = format!("Y Pointer | {:#06X}", self.cpu.borrow().yp());
+        let text = format!(
+            "Y Pointer | {}",
+            order.format_pair(28, self.cpu.borrow().yp())
+        );
         let h = self.yp_lb;
         if let Some(lb) = self.control_mut(h) {
             lb.set_caption(&text);
         }
 
-        let text = format!("Z Pointer | {:#06X}", self.cpu.borrow().zp());
+        let text = format!(
+            "Z Pointer | {}",
+            order.format_pair(30, self.cpu.borrow().zp())
+        );
         let h = self.zp_lb;
         if let Some(lb) = self.control_mut(h) {
             lb.set_caption(&text);
@@ -117,11 +138,10 @@ impl TimerEvents for StatusWindow {
             lb.set_caption(&text);
         }
 
-        let mut text = format!("Current Instruction | {}", self.cpu.borrow().get_instruction());
-        if let (Some(start), Some(end)) = (text.find('{'), text.find('}')) {
-            let result = format!("{}{}", &text[..start], &text[end+1..]);
-            text = result.trim_ascii().to_string();
-        };
+        let text = format!(
+            "Current Instruction | {}",
+            self.cpu.borrow().get_instruction()
+        );
         let h = self.inst_lb;
         if let Some(lb) = self.control_mut(h) {
             lb.set_caption(&text);
@@ -0,0 +1,53 @@
+//! Headless subcommands run through the built binary, as a shell or CI job would call them.
+//! The decode and exec commands are the ones the README documents.
+
+use std::process::Command;
+
+fn breadboard(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_Breadboard"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+/// decode prints one instruction per line and exits without starting a frontend
+fn tst_cli_decode_binary() {
+    let output = breadboard(&["decode", "EF1F"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "LDI r17, 0xFF\n");
+}
+
+#[test]
+/// exec prints the final state and the registers the program changed
+fn tst_cli_exec_binary() {
+    let output = breadboard(&["exec", "1F EF 00 00", "--steps", "2"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "PC=0004 SP=045F SREG=0x00 cyc=2\nr17=0xFF\n"
+    );
+}
+
+#[test]
+/// Bad arguments go to stderr with a failing exit code
+fn tst_cli_error_binary() {
+    let output = breadboard(&["decode", "XYZ"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty() && !output.stderr.is_empty());
+}
+
+#[test]
+#[cfg(unix)]
+/// Arguments that are not UTF-8 are reported instead of panicking
+fn tst_cli_non_utf8_binary() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_Breadboard"))
+        .arg("decode")
+        .arg(std::ffi::OsStr::from_bytes(b"EF1F\xFF"))
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not valid UTF-8"))
+}